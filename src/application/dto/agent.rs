@@ -1,5 +1,6 @@
 //! Agent DTOs
 
+use crate::domain::entities::agent::{Agent, AgentPlatform};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -11,3 +12,22 @@ pub struct AgentDto {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterAgentRequest {
+    pub name: String,
+    pub max_concurrent_jobs: usize,
+    pub platform: AgentPlatform,
+    pub version: String,
+    pub ip_address: String,
+}
+
+impl From<&Agent> for AgentDto {
+    fn from(agent: &Agent) -> Self {
+        Self {
+            id: agent.id().to_string(),
+            name: agent.name().to_string(),
+            status: format!("{:?}", agent.status()),
+            created_at: agent.created_at(),
+        }
+    }
+}
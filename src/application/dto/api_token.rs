@@ -0,0 +1,59 @@
+//! API token DTOs
+
+use crate::domain::entities::api_token::{ApiToken, TokenScope};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTokenDto {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub project_id: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A newly created token with its secret, which is only ever shown once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedApiTokenDto {
+    #[serde(flatten)]
+    pub token: ApiTokenDto,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    /// Restrict the token to one project
+    pub project_id: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Create the token for another user, e.g. a service account (admins only)
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListApiTokensQuery {
+    /// List the tokens of another user (admins only)
+    pub user_id: Option<String>,
+}
+
+impl From<&ApiToken> for ApiTokenDto {
+    fn from(token: &ApiToken) -> Self {
+        Self {
+            id: token.id().to_string(),
+            user_id: token.user_id().to_string(),
+            name: token.name().to_string(),
+            scopes: token.scopes().to_vec(),
+            project_id: token.project_id().map(ToString::to_string),
+            expires_at: token.expires_at(),
+            last_used_at: token.last_used_at(),
+            revoked_at: token.revoked_at(),
+            created_at: token.created_at(),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Artifact DTOs
+
+use crate::domain::entities::artifact::{Artifact, ArtifactType};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactDto {
+    pub id: String,
+    pub build_id: String,
+    pub job: Option<String>,
+    pub name: String,
+    pub artifact_type: ArtifactType,
+    pub size: u64,
+    pub checksum: String,
+    pub content_type: String,
+    pub expired: bool,
+    /// Whether the artifact is kept regardless of retention
+    pub pinned: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Artifact> for ArtifactDto {
+    fn from(artifact: &Artifact) -> Self {
+        Self {
+            id: artifact.id().to_string(),
+            build_id: artifact.build_id().to_string(),
+            job: artifact.job().map(str::to_string),
+            name: artifact.name().to_string(),
+            artifact_type: artifact.artifact_type().clone(),
+            size: artifact.size(),
+            checksum: artifact.checksum().to_string(),
+            content_type: artifact.content_type().to_string(),
+            expired: artifact.is_expired(),
+            pinned: artifact.is_pinned(),
+            expires_at: artifact.expires_at(),
+            created_at: artifact.created_at(),
+        }
+    }
+}
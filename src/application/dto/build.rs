@@ -1,5 +1,7 @@
 //! Build DTOs
 
+use crate::domain::entities::build::{Build, BuildTrigger};
+use crate::domain::value_objects::commit_info::CommitInfo;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -12,6 +14,53 @@ pub struct BuildDto {
     pub status: String,
     pub commit_sha: String,
     pub branch: String,
+    pub commit_message: Option<String>,
+    pub commit_author: Option<String>,
+    pub commit_committer: Option<String>,
+    pub committed_at: Option<DateTime<Utc>>,
+    /// Commits since the last successful build of the branch, newest first
+    pub changes: Vec<CommitInfo>,
+    pub trigger: BuildTrigger,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerBuildRequest {
+    pub pipeline_id: String,
+    /// Commit to build; the head of `branch` when omitted
+    #[serde(default)]
+    pub commit_sha: Option<String>,
+    pub branch: String,
+}
+
+/// Build list filters, as accepted in query strings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListBuildsQuery {
+    pub project_id: Option<String>,
+    pub pipeline_id: Option<String>,
+    pub status: Option<String>,
+    pub branch: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl From<&Build> for BuildDto {
+    fn from(build: &Build) -> Self {
+        Self {
+            id: build.id().to_string(),
+            pipeline_id: build.pipeline_id().to_string(),
+            project_id: build.project_id().to_string(),
+            number: build.number(),
+            status: build.status().to_string(),
+            commit_sha: build.commit_sha().to_string(),
+            branch: build.branch().to_string(),
+            commit_message: build.commit_message().map(str::to_string),
+            commit_author: build.commit_author().map(str::to_string),
+            commit_committer: build.commit_committer().map(str::to_string),
+            committed_at: build.committed_at(),
+            changes: build.changes().to_vec(),
+            trigger: build.trigger().clone(),
+            created_at: build.created_at(),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Event log DTOs
+
+use crate::domain::repositories::event::{EventQueryOptions, StoredEvent};
+use crate::domain::value_objects::project_id::ProjectId;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventDto {
+    pub sequence: u64,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub project_id: Option<ProjectId>,
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    pub recorded_at: DateTime<Utc>,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEventsRequest {
+    /// Name of the subscribed handler to re-deliver events to
+    pub handler: String,
+    /// Re-deliver events that occurred at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only re-deliver events recorded after this sequence number, e.g. the
+    /// `last_sequence` of the previous replay
+    #[serde(default)]
+    pub after: Option<u64>,
+    /// Number of events to replay at once; 1000 by default, at most 10000
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEventsResponse {
+    pub handler: String,
+    pub delivered: usize,
+    /// Sequence number of the last event replayed; pass it as `after` to
+    /// continue, until it is `null`
+    pub last_sequence: Option<u64>,
+}
+
+impl From<StoredEvent> for EventDto {
+    fn from(stored: StoredEvent) -> Self {
+        Self {
+            sequence: stored.sequence,
+            aggregate_type: stored.aggregate_type,
+            aggregate_id: stored.aggregate_id,
+            project_id: stored.project_id,
+            event_type: stored.event_type,
+            occurred_at: stored.occurred_at,
+            recorded_at: stored.recorded_at,
+            payload: serde_json::to_value(&stored.event).unwrap_or_default(),
+        }
+    }
+}
+
+/// Event list filters, as accepted in query strings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListEventsQuery {
+    /// Aggregate type (build, pipeline, project, agent, user)
+    pub aggregate_type: Option<String>,
+    pub aggregate_id: Option<String>,
+    /// Comma-separated event types
+    pub event_type: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Only events recorded after this sequence number
+    pub after: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl From<ListEventsQuery> for EventQueryOptions {
+    fn from(query: ListEventsQuery) -> Self {
+        Self {
+            aggregate_type: query.aggregate_type,
+            aggregate_id: query.aggregate_id,
+            project_id: None,
+            event_types: query
+                .event_type
+                .map(|types| types.split(',').map(|t| t.trim().to_string()).collect())
+                .unwrap_or_default(),
+            since: query.since,
+            until: query.until,
+            after_sequence: query.after,
+            limit: query.limit,
+        }
+    }
+}
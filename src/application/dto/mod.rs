@@ -5,6 +5,12 @@ pub mod pipeline;
 pub mod project;
 pub mod agent;
 pub mod user;
+pub mod api_token;
+pub mod secret;
+pub mod event;
+pub mod webhook;
+pub mod storage;
+pub mod artifact;
 
 // Re-export common DTOs
 pub use build::*;
@@ -12,4 +18,10 @@ pub use pipeline::*;
 pub use project::*;
 pub use agent::*;
 pub use user::*;
+pub use api_token::*;
+pub use secret::*;
+pub use event::*;
+pub use webhook::*;
+pub use storage::*;
+pub use artifact::*;
 
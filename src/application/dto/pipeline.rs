@@ -1,5 +1,7 @@
 //! Pipeline DTOs
 
+use crate::domain::entities::pipeline::Pipeline;
+use crate::domain::value_objects::pipeline_config::PipelineConfig;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -12,3 +14,26 @@ pub struct PipelineDto {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePipelineRequest {
+    pub project_id: String,
+    pub name: String,
+    pub config: PipelineConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePipelineRequest {
+    pub config: PipelineConfig,
+}
+
+impl From<&Pipeline> for PipelineDto {
+    fn from(pipeline: &Pipeline) -> Self {
+        Self {
+            id: pipeline.id().to_string(),
+            project_id: pipeline.project_id().to_string(),
+            name: pipeline.name().to_string(),
+            enabled: pipeline.is_enabled(),
+            created_at: pipeline.created_at(),
+        }
+    }
+}
@@ -1,5 +1,9 @@
 //! Project DTOs
 
+use crate::domain::entities::{
+    membership::{ProjectMember, ProjectRole},
+    project::{Project, ProjectVisibility},
+};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -8,6 +12,62 @@ pub struct ProjectDto {
     pub id: String,
     pub name: String,
     pub repository_url: String,
+    pub visibility: ProjectVisibility,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateProjectRequest {
+    pub name: String,
+    pub repository_url: String,
+    /// Defaults to `main`
+    pub default_branch: Option<String>,
+    pub description: Option<String>,
+    /// Defaults to `Private`
+    pub visibility: Option<ProjectVisibility>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateProjectRequest {
+    pub name: Option<String>,
+    pub repository_url: Option<String>,
+    pub default_branch: Option<String>,
+    pub description: Option<String>,
+    pub visibility: Option<ProjectVisibility>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectMemberDto {
+    pub project_id: String,
+    pub user_id: String,
+    pub role: ProjectRole,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetProjectMemberRequest {
+    pub role: ProjectRole,
+}
+
+impl From<&Project> for ProjectDto {
+    fn from(project: &Project) -> Self {
+        Self {
+            id: project.id().to_string(),
+            name: project.name().to_string(),
+            repository_url: project.repository_url().to_string(),
+            visibility: project.visibility().clone(),
+            created_at: project.created_at(),
+        }
+    }
+}
+
+impl From<&ProjectMember> for ProjectMemberDto {
+    fn from(member: &ProjectMember) -> Self {
+        Self {
+            project_id: member.project_id().to_string(),
+            user_id: member.user_id().to_string(),
+            role: member.role(),
+            created_at: member.created_at(),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Secret DTOs
+//!
+//! Secret values are write-only: they can be set but never read back.
+
+use crate::domain::entities::secret::Secret;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretDto {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SetSecretRequest {
+    pub value: String,
+}
+
+impl From<&Secret> for SecretDto {
+    fn from(secret: &Secret) -> Self {
+        Self {
+            name: secret.name().to_string(),
+            created_at: secret.created_at(),
+            updated_at: secret.updated_at(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Storage usage DTOs
+
+use crate::domain::repositories::blob::BlobStats;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageStatsDto {
+    pub artifacts: BlobStatsDto,
+    pub caches: BlobStatsDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobStatsDto {
+    /// Number of stored artifacts or caches
+    pub objects: u64,
+    /// Number of distinct contents kept
+    pub blobs: u64,
+    /// Bytes uploaded, counting identical uploads every time
+    pub logical_bytes: u64,
+    /// Bytes actually stored
+    pub physical_bytes: u64,
+    /// Bytes saved by storing identical uploads once
+    pub saved_bytes: u64,
+}
+
+impl From<BlobStats> for BlobStatsDto {
+    fn from(stats: BlobStats) -> Self {
+        Self {
+            objects: stats.objects,
+            blobs: stats.blobs,
+            logical_bytes: stats.logical_bytes,
+            physical_bytes: stats.physical_bytes,
+            saved_bytes: stats.logical_bytes.saturating_sub(stats.physical_bytes),
+        }
+    }
+}
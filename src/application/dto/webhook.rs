@@ -0,0 +1,12 @@
+//! Webhook DTOs
+
+use super::build::BuildDto;
+use serde::{Deserialize, Serialize};
+
+/// Result of a webhook delivery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookResponse {
+    /// Event kind, or `None` when the delivery was ignored
+    pub event: Option<String>,
+    pub builds: Vec<BuildDto>,
+}
@@ -3,28 +3,79 @@
 pub mod use_cases;
 pub mod dto;
 
-use crate::config::Config;
+use crate::config::{Config, StorageConfig};
 use crate::domain::repositories::{
     pipeline::PipelineRepository,
     build::BuildRepository,
     agent::AgentRepository,
+    project::ProjectRepository,
+    user::UserRepository,
+    membership::MembershipRepository,
+    api_token::ApiTokenRepository,
+    secret::SecretRepository,
+    event::EventRepository,
+    artifact::ArtifactRepository,
+    cache::CacheRepository,
+    commit::CommitRepository,
 };
 use crate::domain::services::{
     pipeline::PipelineService,
     build::BuildService,
     agent::AgentService,
+    project::ProjectService,
+    auth::AuthService,
+    authorization::AuthorizationService,
+    api_token::ApiTokenService,
+    secret::SecretService,
+    webhook::WebhookService,
+    event_log::EventLogService,
 };
-use crate::domain::events::{EventPublisher, InMemoryEventPublisher};
+use crate::domain::entities::user::UserRole;
+use crate::domain::events::EventPublisher;
+use crate::infrastructure::events::{
+    EventBus,
+    FanoutEventPublisher,
+    RedisStreamPublisher,
+    RedisStreamSubscriber,
+};
+use crate::infrastructure::executor::{JobExecutor, cache::JobCache};
+use crate::infrastructure::git::{history::GitCommitRepository, mirror::MirrorCache, poller::RepositoryPoller};
+use crate::infrastructure::storage::{
+    ArtifactStore,
+    dedup::DedupArtifactStore,
+    local::LocalArtifactStore,
+    retention::{ArtifactSweeper, CacheSweeper},
+    s3::S3ArtifactStore,
+};
+use std::path::Path;
 use std::sync::Arc;
+use tokio::sync::watch;
 
 /// Application instance
 pub struct Application {
     config: Config,
-    #[allow(dead_code)]
-    event_publisher: Arc<dyn EventPublisher>,
+    event_bus: Arc<EventBus>,
+    event_shutdown: watch::Sender<bool>,
+    event_log_service: Arc<EventLogService>,
     pipeline_service: Arc<PipelineService>,
     build_service: Arc<BuildService>,
     agent_service: Arc<AgentService>,
+    project_service: Arc<ProjectService>,
+    auth_service: Arc<AuthService>,
+    authorization_service: Arc<AuthorizationService>,
+    api_token_service: Arc<ApiTokenService>,
+    secret_service: Arc<SecretService>,
+    webhook_service: Arc<WebhookService>,
+    mirror_cache: Arc<MirrorCache>,
+    artifacts: ArtifactServices,
+}
+
+/// Stores of artifacts and caches, and the executor using them
+struct ArtifactServices {
+    artifact_store: Arc<DedupArtifactStore>,
+    artifacts: Arc<dyn ArtifactRepository>,
+    cache_store: Arc<DedupArtifactStore>,
+    job_executor: Arc<JobExecutor>,
 }
 
 impl Application {
@@ -33,8 +84,99 @@ impl Application {
         // Validate configuration
         config.validate()?;
         
-        // Create event publisher (in-memory for now)
-        let event_publisher = Arc::new(InMemoryEventPublisher::new());
+        // Create event bus
+        let event_bus = Arc::new(EventBus::new(config.events.clone()));
+        let (event_shutdown, shutdown_signal) = watch::channel(false);
+        
+        // Record every event in the event log, under the project it concerns
+        let pipeline_repo = create_placeholder_pipeline_repo();
+        let build_repo = create_placeholder_build_repo();
+        let event_log_service = Arc::new(
+            EventLogService::new(create_event_repo(config.events.log_path.as_deref()).await?)
+                .with_project_lookup(pipeline_repo.clone(), build_repo.clone()),
+        );
+        event_bus.subscribe(event_log_service.clone()).await?;
+        
+        // Share events with other replicas through Redis Streams when configured
+        let event_publisher: Arc<dyn EventPublisher> = match &config.events.redis {
+            Some(redis_config) => {
+                let stream = Arc::new(RedisStreamPublisher::connect(redis_config).await?);
+                RedisStreamSubscriber::connect(redis_config, event_bus.clone())
+                    .await?
+                    .spawn(shutdown_signal);
+                
+                Arc::new(FanoutEventPublisher::new(event_bus.clone(), vec![stream]))
+            }
+            None => event_bus.clone(),
+        };
+        
+        let pipeline_service = Arc::new(PipelineService::new(pipeline_repo, event_publisher.clone()));
+        let project_service = Arc::new(ProjectService::new(
+            create_placeholder_project_repo(),
+            event_publisher.clone(),
+        ));
+        let secret_service = Arc::new(SecretService::new(
+            create_placeholder_secret_repo(),
+            &config.security,
+        ));
+        
+        // Keep repository mirrors fresh and drop those of deleted projects
+        let mirror_cache = Arc::new(MirrorCache::new(
+            &config.storage.cache_path,
+            config.git.clone(),
+            secret_service.clone(),
+        ));
+        mirror_cache.clone().spawn(project_service.clone(), event_shutdown.subscribe());
+        
+        // Builds read their commit details and webhooks their changed files
+        // from the mirrors
+        let commits: Arc<dyn CommitRepository> =
+            Arc::new(GitCommitRepository::new(mirror_cache.clone(), project_service.clone()));
+        let build_service = Arc::new(
+            BuildService::new(build_repo, event_publisher.clone()).with_commit_repository(commits.clone()),
+        );
+        let webhook_service = Arc::new(
+            WebhookService::new(pipeline_service.clone(), build_service.clone()).with_commit_repository(commits),
+        );
+        let artifacts = create_artifact_services(&config.storage, &build_service, &secret_service, &event_shutdown).await?;
+        
+        // Poll repositories of pipelines that cannot be triggered by webhooks
+        Arc::new(RepositoryPoller::new(
+            config.git.clone(),
+            project_service.clone(),
+            pipeline_service.clone(),
+            build_service.clone(),
+            secret_service.clone(),
+        ))
+        .spawn(event_shutdown.subscribe());
+        
+        let user_repo = create_placeholder_user_repo();
+        let auth_service = Arc::new(AuthService::new(
+            user_repo.clone(),
+            event_publisher.clone(),
+            &config.security,
+        ));
+        let api_token_service = Arc::new(ApiTokenService::new(
+            create_placeholder_api_token_repo(),
+            user_repo,
+        ));
+        
+        // Create the initial administrator so someone can log in
+        if let Some(admin) = &config.security.initial_admin {
+            match auth_service
+                .register_user(
+                    admin.username.clone(),
+                    admin.email.clone(),
+                    admin.password.clone(),
+                    UserRole::Admin,
+                )
+                .await
+            {
+                Ok(_) => tracing::info!("Created initial administrator {}", admin.username),
+                Err(crate::Error::Conflict(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
         
         // TODO: Initialize database connection
         // TODO: Initialize repository implementations
@@ -44,19 +186,25 @@ impl Application {
         
         Ok(Self {
             config,
-            event_publisher: event_publisher.clone(),
-            pipeline_service: Arc::new(PipelineService::new(
-                create_placeholder_pipeline_repo(),
-                event_publisher.clone(),
-            )),
-            build_service: Arc::new(BuildService::new(
-                create_placeholder_build_repo(),
-                event_publisher.clone(),
-            )),
+            event_bus,
+            event_shutdown,
+            event_log_service,
+            pipeline_service,
+            build_service,
             agent_service: Arc::new(AgentService::new(
                 create_placeholder_agent_repo(),
                 event_publisher,
             )),
+            project_service,
+            auth_service,
+            authorization_service: Arc::new(AuthorizationService::new(
+                create_placeholder_membership_repo(),
+            )),
+            api_token_service,
+            secret_service,
+            webhook_service,
+            mirror_cache,
+            artifacts,
         })
     }
     
@@ -65,6 +213,23 @@ impl Application {
         &self.config
     }
     
+    /// Get the event bus (subscribe handlers here)
+    pub fn event_bus(&self) -> &Arc<EventBus> {
+        &self.event_bus
+    }
+    
+    /// Stop background tasks and event delivery, waiting for queued events to
+    /// be handled
+    pub async fn shutdown(&self) {
+        let _ = self.event_shutdown.send(true);
+        self.event_bus.shutdown().await;
+    }
+    
+    /// Get the event log service
+    pub fn event_log_service(&self) -> &EventLogService {
+        &self.event_log_service
+    }
+    
     /// Get the pipeline service
     pub fn pipeline_service(&self) -> &PipelineService {
         &self.pipeline_service
@@ -79,6 +244,138 @@ impl Application {
     pub fn agent_service(&self) -> &AgentService {
         &self.agent_service
     }
+    
+    /// Get the project service
+    pub fn project_service(&self) -> &ProjectService {
+        &self.project_service
+    }
+    
+    /// Get the authentication service
+    pub fn auth_service(&self) -> &AuthService {
+        &self.auth_service
+    }
+    
+    /// Get the authorization service
+    pub fn authorization_service(&self) -> &AuthorizationService {
+        &self.authorization_service
+    }
+    
+    /// Get the API token service
+    pub fn api_token_service(&self) -> &ApiTokenService {
+        &self.api_token_service
+    }
+    
+    /// Get the secret service
+    pub fn secret_service(&self) -> &SecretService {
+        &self.secret_service
+    }
+    
+    /// Get the webhook service
+    pub fn webhook_service(&self) -> &WebhookService {
+        &self.webhook_service
+    }
+    
+    /// Get the repository mirror cache
+    pub fn mirror_cache(&self) -> &MirrorCache {
+        &self.mirror_cache
+    }
+    
+    /// Get the artifact store
+    pub fn artifact_store(&self) -> &Arc<DedupArtifactStore> {
+        &self.artifacts.artifact_store
+    }
+    
+    /// Get the artifact records
+    pub fn artifact_repository(&self) -> &Arc<dyn ArtifactRepository> {
+        &self.artifacts.artifacts
+    }
+    
+    /// Get the dependency cache store
+    pub fn cache_store(&self) -> &Arc<DedupArtifactStore> {
+        &self.artifacts.cache_store
+    }
+    
+    /// Get the job executor
+    pub fn job_executor(&self) -> &JobExecutor {
+        &self.artifacts.job_executor
+    }
+}
+
+/// Create the artifact and cache stores and the job executor using them, and
+/// expire artifacts and caches past their retention in the background
+///
+/// Dependency caches are kept on the local disk under `cache_path`. Both
+/// stores keep identical contents once and collect unreferenced ones in the
+/// background. Their blob indexes are kept under `cache_path/index` and the
+/// artifact and cache records referencing them under `cache_path/records`, so
+/// both survive a restart together.
+async fn create_artifact_services(
+    config: &StorageConfig,
+    build_service: &Arc<BuildService>,
+    secret_service: &Arc<SecretService>,
+    shutdown: &watch::Sender<bool>,
+) -> crate::Result<ArtifactServices> {
+    use crate::infrastructure::repositories::file::{FileArtifactRepository, FileCacheRepository};
+    
+    let index_path = Path::new(&config.cache_path).join("index");
+    let records_path = Path::new(&config.cache_path).join("records");
+    let artifact_store =
+        create_dedup_store(create_artifact_store(config), &index_path.join("artifacts.jsonl"), shutdown).await?;
+    let artifacts: Arc<dyn ArtifactRepository> =
+        Arc::new(FileArtifactRepository::open(records_path.join("artifacts.jsonl")).await?);
+    Arc::new(ArtifactSweeper::new(
+        artifact_store.clone(),
+        artifacts.clone(),
+        build_service.clone(),
+        config.retention_days,
+    ))
+    .spawn(shutdown.subscribe());
+    
+    let cache_store = create_dedup_store(
+        Arc::new(LocalArtifactStore::new(
+            Path::new(&config.cache_path).join("caches"),
+            config.max_artifact_size.saturating_mul(1024 * 1024),
+        )),
+        &index_path.join("caches.jsonl"),
+        shutdown,
+    )
+    .await?;
+    let caches: Arc<dyn CacheRepository> =
+        Arc::new(FileCacheRepository::open(records_path.join("caches.jsonl")).await?);
+    Arc::new(CacheSweeper::new(
+        cache_store.clone(),
+        caches.clone(),
+        config.cache_retention_days,
+    ))
+    .spawn(shutdown.subscribe());
+    let cache = JobCache::new(cache_store.clone(), caches);
+    let job_executor = Arc::new(JobExecutor::new(artifact_store.clone(), artifacts.clone(), secret_service.clone()).with_cache(cache));
+    
+    Ok(ArtifactServices {
+        artifact_store,
+        artifacts,
+        cache_store,
+        job_executor,
+    })
+}
+
+async fn create_dedup_store(
+    blobs: Arc<dyn ArtifactStore>,
+    index_path: &Path,
+    shutdown: &watch::Sender<bool>,
+) -> crate::Result<Arc<DedupArtifactStore>> {
+    use crate::infrastructure::repositories::file::FileBlobRepository;
+    let index = FileBlobRepository::open(index_path).await?;
+    let store = Arc::new(DedupArtifactStore::new(blobs, Arc::new(index)));
+    store.clone().spawn(shutdown.subscribe());
+    Ok(store)
+}
+
+fn create_artifact_store(config: &StorageConfig) -> Arc<dyn ArtifactStore> {
+    match S3ArtifactStore::from_config(config) {
+        Some(store) => Arc::new(store),
+        None => Arc::new(LocalArtifactStore::from_config(config)),
+    }
 }
 
 // Placeholder functions - will be replaced with actual implementations
@@ -97,3 +394,39 @@ fn create_placeholder_agent_repo() -> Arc<dyn AgentRepository> {
     Arc::new(InMemoryAgentRepository::new())
 }
 
+fn create_placeholder_project_repo() -> Arc<dyn ProjectRepository> {
+    use crate::infrastructure::repositories::in_memory::InMemoryProjectRepository;
+    Arc::new(InMemoryProjectRepository::new())
+}
+
+fn create_placeholder_user_repo() -> Arc<dyn UserRepository> {
+    use crate::infrastructure::repositories::in_memory::InMemoryUserRepository;
+    Arc::new(InMemoryUserRepository::new())
+}
+
+fn create_placeholder_membership_repo() -> Arc<dyn MembershipRepository> {
+    use crate::infrastructure::repositories::in_memory::InMemoryMembershipRepository;
+    Arc::new(InMemoryMembershipRepository::new())
+}
+
+fn create_placeholder_api_token_repo() -> Arc<dyn ApiTokenRepository> {
+    use crate::infrastructure::repositories::in_memory::InMemoryApiTokenRepository;
+    Arc::new(InMemoryApiTokenRepository::new())
+}
+
+fn create_placeholder_secret_repo() -> Arc<dyn SecretRepository> {
+    use crate::infrastructure::repositories::in_memory::InMemorySecretRepository;
+    Arc::new(InMemorySecretRepository::new())
+}
+
+async fn create_event_repo(log_path: Option<&str>) -> crate::Result<Arc<dyn EventRepository>> {
+    use crate::infrastructure::repositories::{
+        file::FileEventRepository,
+        in_memory::InMemoryEventRepository,
+    };
+    
+    Ok(match log_path {
+        Some(path) => Arc::new(FileEventRepository::open(path).await?),
+        None => Arc::new(InMemoryEventRepository::new()),
+    })
+}
@@ -33,6 +33,10 @@ pub struct Config {
     /// Monitoring configuration
     #[serde(default)]
     pub monitoring: MonitoringConfig,
+    
+    /// Event bus configuration
+    #[serde(default)]
+    pub events: EventsConfig,
 }
 
 /// Server configuration
@@ -121,10 +125,17 @@ pub struct StorageConfig {
     #[serde(default = "default_max_artifact_size")]
     pub max_artifact_size: u64,
     
-    /// Artifact retention days
+    /// Artifact retention days; 0 keeps artifacts until their own expiration
+    /// date. The artifacts of the latest successful build of each branch and
+    /// pinned ones are always kept
     #[serde(default = "default_retention_days")]
     pub retention_days: u32,
     
+    /// Days a dependency cache is kept after it was last saved or restored;
+    /// 0 keeps caches forever
+    #[serde(default = "default_cache_retention_days")]
+    pub cache_retention_days: u32,
+    
     /// S3 configuration for artifact storage
     #[serde(default)]
     pub s3: Option<S3Config>,
@@ -155,10 +166,14 @@ pub struct SecurityConfig {
     /// JWT secret for authentication
     pub jwt_secret: String,
     
-    /// Session timeout in seconds
+    /// Session timeout in seconds (lifetime of access tokens)
     #[serde(default = "default_session_timeout")]
     pub session_timeout: u64,
     
+    /// Lifetime of refresh tokens in seconds
+    #[serde(default = "default_refresh_token_timeout")]
+    pub refresh_token_timeout: u64,
+    
     /// Password hash cost
     #[serde(default = "default_hash_cost")]
     pub password_hash_cost: u32,
@@ -174,6 +189,29 @@ pub struct SecurityConfig {
     /// Allowed CORS origins
     #[serde(default)]
     pub cors_origins: Vec<String>,
+    
+    /// Administrator created on startup when no user with that name exists
+    #[serde(default)]
+    pub initial_admin: Option<InitialAdminConfig>,
+    
+    /// Key material the secrets store encryption key is derived from
+    ///
+    /// Falls back to the JWT secret. Changing it makes stored secrets unreadable.
+    #[serde(default)]
+    pub secrets_key: Option<String>,
+}
+
+/// Initial administrator account
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct InitialAdminConfig {
+    /// Username
+    pub username: String,
+    
+    /// Email address
+    pub email: String,
+    
+    /// Password
+    pub password: String,
 }
 
 /// Git configuration
@@ -196,6 +234,10 @@ pub struct GitConfig {
     /// Maximum repository size in MB
     #[serde(default = "default_max_repo_size")]
     pub max_repo_size: u64,
+    
+    /// Interval between background fetches of repository mirrors, in seconds
+    #[serde(default = "default_mirror_refresh_interval")]
+    pub mirror_refresh_interval: u64,
 }
 
 /// Agent configuration
@@ -325,6 +367,95 @@ pub struct MonitoringConfig {
     pub sampling_rate: f64,
 }
 
+/// Event bus configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EventsConfig {
+    /// Capacity of each handler queue
+    #[serde(default = "default_event_queue_capacity")]
+    pub queue_capacity: usize,
+    
+    /// Number of queues per handler (events of one aggregate share a queue)
+    #[serde(default = "default_event_partitions")]
+    pub partitions: usize,
+    
+    /// Delivery attempts before an event is dead-lettered
+    #[serde(default = "default_event_max_attempts")]
+    pub max_attempts: u32,
+    
+    /// Delay before the first retry in milliseconds (doubled on each retry)
+    #[serde(default = "default_event_retry_backoff")]
+    pub retry_backoff_ms: u64,
+    
+    /// Longest delay between retries in milliseconds
+    #[serde(default = "default_event_max_retry_backoff")]
+    pub max_retry_backoff_ms: u64,
+    
+    /// Dead letters kept in memory; the oldest are dropped beyond this, and
+    /// stay in the event log
+    #[serde(default = "default_event_max_dead_letters")]
+    pub max_dead_letters: usize,
+    
+    /// Event log file; events are only kept in memory when unset
+    #[serde(default)]
+    pub log_path: Option<String>,
+    
+    /// Redis Streams backend for sharing events between server replicas
+    #[serde(default)]
+    pub redis: Option<RedisStreamConfig>,
+}
+
+/// Redis Streams event backend configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RedisStreamConfig {
+    /// Redis connection URL
+    pub url: String,
+    
+    /// Stream key events are written to
+    #[serde(default = "default_event_stream")]
+    pub stream: String,
+    
+    /// Name of this server replica; each replica reads through its own consumer group.
+    /// Defaults to the `HOSTNAME` environment variable, then `/etc/hostname`; it
+    /// must stay the same across restarts.
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    
+    /// Maximum number of entries kept in the stream (0 disables trimming)
+    #[serde(default = "default_event_stream_max_len")]
+    pub max_len: usize,
+    
+    /// Trim the stream to exactly `max_len` entries instead of approximately
+    #[serde(default)]
+    pub exact_trim: bool,
+    
+    /// Maximum number of entries read per request
+    #[serde(default = "default_event_stream_batch_size")]
+    pub batch_size: usize,
+    
+    /// How long a read blocks waiting for new entries, in milliseconds
+    #[serde(default = "default_event_stream_block")]
+    pub block_ms: u64,
+    
+    /// Delay before reading again after a connection failure, in milliseconds
+    #[serde(default = "default_event_stream_reconnect_delay")]
+    pub reconnect_delay_ms: u64,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: default_event_queue_capacity(),
+            partitions: default_event_partitions(),
+            max_attempts: default_event_max_attempts(),
+            retry_backoff_ms: default_event_retry_backoff(),
+            max_retry_backoff_ms: default_event_max_retry_backoff(),
+            max_dead_letters: default_event_max_dead_letters(),
+            log_path: None,
+            redis: None,
+        }
+    }
+}
+
 // Default value functions
 fn default_host() -> String {
     "0.0.0.0".to_string()
@@ -370,10 +501,18 @@ fn default_retention_days() -> u32 {
     30
 }
 
+fn default_cache_retention_days() -> u32 {
+    7
+}
+
 fn default_session_timeout() -> u64 {
     3600
 }
 
+fn default_refresh_token_timeout() -> u64 {
+    604_800 // 7 days
+}
+
 fn default_hash_cost() -> u32 {
     12
 }
@@ -394,6 +533,10 @@ fn default_max_repo_size() -> u64 {
     1000 // MB
 }
 
+fn default_mirror_refresh_interval() -> u64 {
+    300 // 5 minutes
+}
+
 fn default_max_concurrent_builds() -> usize {
     5
 }
@@ -414,6 +557,50 @@ fn default_sampling_rate() -> f64 {
     0.1
 }
 
+fn default_event_queue_capacity() -> usize {
+    1024
+}
+
+fn default_event_partitions() -> usize {
+    4
+}
+
+fn default_event_max_attempts() -> u32 {
+    3
+}
+
+fn default_event_retry_backoff() -> u64 {
+    100
+}
+
+fn default_event_max_retry_backoff() -> u64 {
+    30_000
+}
+
+fn default_event_max_dead_letters() -> usize {
+    1000
+}
+
+fn default_event_stream() -> String {
+    "ferrous:events".to_string()
+}
+
+fn default_event_stream_max_len() -> usize {
+    100_000
+}
+
+fn default_event_stream_batch_size() -> usize {
+    100
+}
+
+fn default_event_stream_block() -> u64 {
+    5000
+}
+
+fn default_event_stream_reconnect_delay() -> u64 {
+    1000
+}
+
 fn default_true() -> bool {
     true
 }
@@ -454,15 +641,19 @@ impl Config {
                 cache_path: default_cache_path(),
                 max_artifact_size: default_max_artifact_size(),
                 retention_days: default_retention_days(),
+                cache_retention_days: default_cache_retention_days(),
                 s3: None,
             },
             security: SecurityConfig {
                 jwt_secret: "change-me-in-production".to_string(),
                 session_timeout: default_session_timeout(),
+                refresh_token_timeout: default_refresh_token_timeout(),
                 password_hash_cost: default_hash_cost(),
                 rate_limiting_enabled: true,
                 rate_limit_per_minute: default_rate_limit(),
                 cors_origins: vec![],
+                initial_admin: None,
+                secrets_key: None,
             },
             git: GitConfig {
                 ssh_key_path: None,
@@ -470,6 +661,7 @@ impl Config {
                 clone_timeout: default_clone_timeout(),
                 fetch_timeout: default_fetch_timeout(),
                 max_repo_size: default_max_repo_size(),
+                mirror_refresh_interval: default_mirror_refresh_interval(),
             },
             agents: AgentConfig {
                 max_concurrent_builds: default_max_concurrent_builds(),
@@ -479,6 +671,7 @@ impl Config {
             },
             notifications: NotificationConfig::default(),
             monitoring: MonitoringConfig::default(),
+            events: EventsConfig::default(),
         }
     }
     
@@ -141,6 +141,11 @@ impl Agent {
         &self.status
     }
     
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
     /// Check if the agent can accept a new job
     pub fn can_accept_job(&self) -> bool {
         self.status == AgentStatus::Online && self.current_jobs < self.max_concurrent_jobs
@@ -0,0 +1,299 @@
+//! API token entity - A revocable, scoped credential for scripts and service accounts
+
+use crate::domain::value_objects::{
+    api_token_id::ApiTokenId,
+    project_id::ProjectId,
+    user_id::UserId,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Permissions an API token can be granted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TokenScope {
+    /// Read projects, pipelines, builds and their events
+    #[serde(rename = "projects:read")]
+    ProjectsRead,
+    /// Create, update and delete projects and manage their members
+    #[serde(rename = "projects:write")]
+    ProjectsWrite,
+    /// Create, update, delete, enable and disable pipelines
+    #[serde(rename = "pipelines:write")]
+    PipelinesWrite,
+    /// Trigger and retry builds
+    #[serde(rename = "builds:trigger")]
+    BuildsTrigger,
+    /// Cancel builds
+    #[serde(rename = "builds:cancel")]
+    BuildsCancel,
+    /// Download artifacts
+    #[serde(rename = "artifacts:read")]
+    ArtifactsRead,
+    /// Upload artifacts
+    #[serde(rename = "artifacts:write")]
+    ArtifactsWrite,
+    /// Read agents
+    #[serde(rename = "agents:read")]
+    AgentsRead,
+    /// Register and manage agents
+    #[serde(rename = "agents:write")]
+    AgentsWrite,
+    /// Read the global event log
+    #[serde(rename = "events:read")]
+    EventsRead,
+}
+
+impl TokenScope {
+    /// Get the scope name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::ProjectsRead => "projects:read",
+            TokenScope::ProjectsWrite => "projects:write",
+            TokenScope::PipelinesWrite => "pipelines:write",
+            TokenScope::BuildsTrigger => "builds:trigger",
+            TokenScope::BuildsCancel => "builds:cancel",
+            TokenScope::ArtifactsRead => "artifacts:read",
+            TokenScope::ArtifactsWrite => "artifacts:write",
+            TokenScope::AgentsRead => "agents:read",
+            TokenScope::AgentsWrite => "agents:write",
+            TokenScope::EventsRead => "events:read",
+        }
+    }
+}
+
+impl fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TokenScope {
+    type Err = crate::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| crate::Error::validation(format!("Unknown token scope: {s}")))
+    }
+}
+
+/// API token entity
+///
+/// Only a SHA256 hash of the token secret is stored; the secret itself is shown
+/// once when the token is created.
+/// The hash is kept when serialized so stored tokens load back; API responses
+/// use `ApiTokenDto`, which leaves it out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    /// Unique identifier
+    id: ApiTokenId,
+    
+    /// User the token acts as
+    user_id: UserId,
+    
+    /// Human-readable name
+    name: String,
+    
+    /// Hex-encoded SHA256 hash of the secret
+    secret_hash: String,
+    
+    /// Granted scopes
+    scopes: Vec<TokenScope>,
+    
+    /// Project the token is restricted to
+    project_id: Option<ProjectId>,
+    
+    /// Expiry time
+    expires_at: Option<DateTime<Utc>>,
+    
+    /// Last time the token authenticated a request
+    last_used_at: Option<DateTime<Utc>>,
+    
+    /// Revocation time
+    revoked_at: Option<DateTime<Utc>>,
+    
+    /// Creation timestamp
+    created_at: DateTime<Utc>,
+}
+
+impl ApiToken {
+    /// Create a new API token
+    pub fn new(
+        user_id: UserId,
+        name: String,
+        secret_hash: String,
+        scopes: Vec<TokenScope>,
+        project_id: Option<ProjectId>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> crate::Result<Self> {
+        let now = Utc::now();
+        
+        if name.trim().is_empty() {
+            return Err(crate::Error::validation("Token name cannot be empty"));
+        }
+        if scopes.is_empty() {
+            return Err(crate::Error::validation("Token needs at least one scope"));
+        }
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(crate::Error::validation("Token expiry must be in the future"));
+        }
+        
+        let mut scopes = scopes;
+        scopes.sort_by_key(TokenScope::as_str);
+        scopes.dedup();
+        
+        Ok(Self {
+            id: ApiTokenId::new(),
+            user_id,
+            name,
+            secret_hash,
+            scopes,
+            project_id,
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: now,
+        })
+    }
+    
+    /// Get the token ID
+    pub fn id(&self) -> &ApiTokenId {
+        &self.id
+    }
+    
+    /// Get the ID of the user the token acts as
+    pub fn user_id(&self) -> &UserId {
+        &self.user_id
+    }
+    
+    /// Get the token name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    
+    /// Get the hash of the secret
+    pub fn secret_hash(&self) -> &str {
+        &self.secret_hash
+    }
+    
+    /// Get the granted scopes
+    pub fn scopes(&self) -> &[TokenScope] {
+        &self.scopes
+    }
+    
+    /// Get the project the token is restricted to
+    pub fn project_id(&self) -> Option<&ProjectId> {
+        self.project_id.as_ref()
+    }
+    
+    /// Get the expiry time
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+    
+    /// Get the last time the token was used
+    pub fn last_used_at(&self) -> Option<DateTime<Utc>> {
+        self.last_used_at
+    }
+    
+    /// Get the revocation time
+    pub fn revoked_at(&self) -> Option<DateTime<Utc>> {
+        self.revoked_at
+    }
+    
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
+    /// Check if the token has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+    
+    /// Check if the token has been revoked
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+    
+    /// Check if the token can still authenticate requests
+    pub fn is_valid(&self) -> bool {
+        !self.is_expired() && !self.is_revoked()
+    }
+    
+    /// Check if the token grants a scope
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+    
+    /// Record that the token authenticated a request
+    pub fn record_use(&mut self) {
+        self.last_used_at = Some(Utc::now());
+    }
+    
+    /// Revoke the token
+    pub fn revoke(&mut self) -> crate::Result<()> {
+        if self.is_revoked() {
+            return Err(crate::Error::conflict("Token is already revoked"));
+        }
+        
+        self.revoked_at = Some(Utc::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    
+    fn new_token(expires_at: Option<DateTime<Utc>>) -> crate::Result<ApiToken> {
+        ApiToken::new(
+            UserId::new(),
+            "ci".to_string(),
+            "hash".to_string(),
+            vec![TokenScope::BuildsTrigger, TokenScope::ProjectsRead, TokenScope::BuildsTrigger],
+            None,
+            expires_at,
+        )
+    }
+    
+    #[test]
+    fn test_new_token() {
+        let token = new_token(None).unwrap();
+        
+        assert_eq!(token.scopes(), &[TokenScope::BuildsTrigger, TokenScope::ProjectsRead]);
+        assert!(token.has_scope(TokenScope::BuildsTrigger));
+        assert!(!token.has_scope(TokenScope::AgentsWrite));
+        assert!(token.is_valid());
+        assert!(new_token(Some(Utc::now() - Duration::hours(1))).is_err());
+    }
+    
+    #[test]
+    fn test_revoke_token() {
+        let mut token = new_token(Some(Utc::now() + Duration::days(30))).unwrap();
+        
+        token.revoke().unwrap();
+        assert!(!token.is_valid());
+        assert!(token.revoke().is_err());
+    }
+    
+    #[test]
+    fn test_serialization_roundtrip() {
+        let token = new_token(None).unwrap();
+        let json = serde_json::to_string(&token).unwrap();
+        let restored: ApiToken = serde_json::from_str(&json).unwrap();
+        
+        assert_eq!(restored.id(), token.id());
+        assert_eq!(restored.secret_hash(), "hash");
+        assert_eq!(restored.scopes(), token.scopes());
+    }
+    
+    #[test]
+    fn test_scope_names() {
+        assert_eq!("builds:trigger".parse::<TokenScope>().unwrap(), TokenScope::BuildsTrigger);
+        assert_eq!(TokenScope::ArtifactsRead.to_string(), "artifacts:read");
+        assert!("builds:*".parse::<TokenScope>().is_err());
+    }
+}
@@ -16,12 +16,21 @@ pub struct Artifact {
     /// Build this artifact belongs to
     build_id: BuildId,
     
+    /// Job that produced the artifact
+    #[serde(default)]
+    job: Option<String>,
+    
     /// Artifact name
     name: String,
     
     /// Artifact path
     path: String,
     
+    /// Workspace paths of the files in the artifact; artifacts with several
+    /// files are archives of them
+    #[serde(default)]
+    files: Vec<String>,
+    
     /// Artifact type
     artifact_type: ArtifactType,
     
@@ -37,6 +46,10 @@ pub struct Artifact {
     /// Whether the artifact is expired
     expired: bool,
     
+    /// Whether the artifact is kept regardless of retention
+    #[serde(default)]
+    pinned: bool,
+    
     /// Expiration date
     expires_at: Option<DateTime<Utc>>,
     
@@ -68,6 +81,31 @@ pub enum ArtifactType {
     Other,
 }
 
+impl ArtifactType {
+    /// Guess the type of an artifact from its file path
+    pub fn for_path(path: &str) -> Self {
+        let path = path.to_ascii_lowercase();
+        let file_name = path.rsplit('/').next().unwrap_or_default();
+        let extension = file_name.rsplit_once('.').map(|(_, extension)| extension).unwrap_or_default();
+        
+        if [".zip", ".tar", ".tar.gz", ".tgz", ".tar.zst", ".7z"].iter().any(|ext| file_name.ends_with(ext)) {
+            ArtifactType::Archive
+        } else if extension == "trx"
+            || (extension == "xml" && (file_name.contains("junit") || file_name.starts_with("test-")))
+        {
+            ArtifactType::TestResults
+        } else if file_name == "lcov.info" || file_name.starts_with("cobertura") || file_name.starts_with("coverage") {
+            ArtifactType::Coverage
+        } else if extension == "log" {
+            ArtifactType::Logs
+        } else if path.split('/').any(|segment| segment == "doc" || segment == "docs") {
+            ArtifactType::Documentation
+        } else {
+            ArtifactType::BuildOutput
+        }
+    }
+}
+
 impl Artifact {
     /// Create a new artifact
     pub fn new(
@@ -83,13 +121,16 @@ impl Artifact {
         Self {
             id: ArtifactId::new(),
             build_id,
+            job: None,
             name,
             path,
+            files: Vec::new(),
             artifact_type,
             size,
             checksum,
             content_type: "application/octet-stream".to_string(),
             expired: false,
+            pinned: false,
             expires_at: None,
             created_at: now,
             accessed_at: now,
@@ -101,6 +142,16 @@ impl Artifact {
         &self.id
     }
     
+    /// Get the build this artifact belongs to
+    pub fn build_id(&self) -> &BuildId {
+        &self.build_id
+    }
+    
+    /// Get the job that produced the artifact
+    pub fn job(&self) -> Option<&str> {
+        self.job.as_deref()
+    }
+    
     /// Get the artifact name
     pub fn name(&self) -> &str {
         &self.name
@@ -111,6 +162,11 @@ impl Artifact {
         &self.path
     }
     
+    /// Get the workspace paths of the files in the artifact
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+    
     /// Get the artifact size
     pub fn size(&self) -> u64 {
         self.size
@@ -121,6 +177,26 @@ impl Artifact {
         &self.checksum
     }
     
+    /// Get the artifact type
+    pub fn artifact_type(&self) -> &ArtifactType {
+        &self.artifact_type
+    }
+    
+    /// Get the content type
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+    
+    /// Get the expiration date
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+    
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
     /// Check if the artifact is expired
     pub fn is_expired(&self) -> bool {
         if self.expired {
@@ -134,6 +210,27 @@ impl Artifact {
         false
     }
     
+    /// Check if the artifact was marked as expired, rather than only being
+    /// past its expiration date
+    pub fn is_marked_expired(&self) -> bool {
+        self.expired
+    }
+    
+    /// Check if the artifact is kept regardless of retention
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+    
+    /// Set the job that produced the artifact
+    pub fn set_job(&mut self, job: String) {
+        self.job = Some(job);
+    }
+    
+    /// Set the workspace paths of the files in the artifact
+    pub fn set_files(&mut self, files: Vec<String>) {
+        self.files = files;
+    }
+    
     /// Set content type
     pub fn set_content_type(&mut self, content_type: String) {
         self.content_type = content_type;
@@ -149,6 +246,16 @@ impl Artifact {
         self.expired = true;
     }
     
+    /// Keep the artifact regardless of retention
+    pub fn pin(&mut self) {
+        self.pinned = true;
+    }
+    
+    /// Let retention expire the artifact again
+    pub fn unpin(&mut self) {
+        self.pinned = false;
+    }
+    
     /// Record access to the artifact
     pub fn record_access(&mut self) {
         self.accessed_at = Utc::now();
@@ -208,11 +315,13 @@ mod tests {
         let past = Utc::now() - chrono::Duration::days(1);
         artifact.set_expiration(past);
         assert!(artifact.is_expired());
+        assert!(!artifact.is_marked_expired());
         
         // Mark as expired
         let mut artifact2 = create_test_artifact();
         artifact2.expire();
         assert!(artifact2.is_expired());
+        assert!(artifact2.is_marked_expired());
     }
 
     #[test]
@@ -226,6 +335,17 @@ mod tests {
         assert!(artifact.accessed_at > initial_accessed);
     }
 
+    #[test]
+    fn test_artifact_type_for_path() {
+        assert_eq!(ArtifactType::for_path("dist/app.tar.zst"), ArtifactType::Archive);
+        assert_eq!(ArtifactType::for_path("reports/TEST-unit.xml"), ArtifactType::TestResults);
+        assert_eq!(ArtifactType::for_path("target/junit.xml"), ArtifactType::TestResults);
+        assert_eq!(ArtifactType::for_path("coverage/lcov.info"), ArtifactType::Coverage);
+        assert_eq!(ArtifactType::for_path("build.log"), ArtifactType::Logs);
+        assert_eq!(ArtifactType::for_path("target/doc/index.html"), ArtifactType::Documentation);
+        assert_eq!(ArtifactType::for_path("target/release/app"), ArtifactType::BuildOutput);
+    }
+
     #[test]
     fn test_artifact_validation() {
         let artifact = create_test_artifact();
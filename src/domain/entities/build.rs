@@ -6,6 +6,7 @@ use crate::domain::value_objects::{
     project_id::ProjectId,
     agent_id::AgentId,
     build_status::BuildStatus,
+    commit_info::CommitInfo,
 };
 use crate::domain::events::DomainEvent;
 use chrono::{DateTime, Duration, Utc};
@@ -36,12 +37,28 @@ pub struct Build {
     /// Git branch
     branch: String,
     
+    /// Whether `branch` names a tag rather than a branch
+    #[serde(default)]
+    tag: bool,
+    
     /// Commit message
     commit_message: Option<String>,
     
     /// Commit author
     commit_author: Option<String>,
     
+    /// Commit committer
+    #[serde(default)]
+    commit_committer: Option<String>,
+    
+    /// Commit time
+    #[serde(default)]
+    committed_at: Option<DateTime<Utc>>,
+    
+    /// Commits since the last successful build of the branch, newest first
+    #[serde(default)]
+    changes: Vec<CommitInfo>,
+    
     /// Agent executing the build
     agent_id: Option<AgentId>,
     
@@ -91,10 +108,12 @@ pub enum BuildTrigger {
     PullRequest { pr_number: u32 },
     /// Scheduled trigger
     Schedule { cron: String },
-    /// API trigger
-    Api { token: String },
+    /// API trigger, recording the ID of the API token used (never its secret)
+    Api { token_id: String },
     /// Webhook trigger
     Webhook { source: String },
+    /// New commit found by polling the repository
+    Poll,
 }
 
 impl Build {
@@ -118,8 +137,12 @@ impl Build {
             status: BuildStatus::Pending,
             commit_sha: commit_sha.clone(),
             branch: branch.clone(),
+            tag: false,
             commit_message: None,
             commit_author: None,
+            commit_committer: None,
+            committed_at: None,
+            changes: Vec::new(),
             agent_id: None,
             parameters: HashMap::new(),
             environment: HashMap::new(),
@@ -170,6 +193,65 @@ impl Build {
         &self.status
     }
     
+    /// Get the Git commit SHA
+    pub fn commit_sha(&self) -> &str {
+        &self.commit_sha
+    }
+    
+    /// Get the Git branch
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+    
+    /// Check whether the build runs on a tag rather than a branch
+    pub fn is_tag(&self) -> bool {
+        self.tag
+    }
+    
+    /// Get the full name of the built ref, e.g. `refs/heads/main`
+    pub fn git_ref(&self) -> String {
+        if self.tag {
+            format!("refs/tags/{}", self.branch)
+        } else {
+            format!("refs/heads/{}", self.branch)
+        }
+    }
+    
+    /// Get the commit message
+    pub fn commit_message(&self) -> Option<&str> {
+        self.commit_message.as_deref()
+    }
+    
+    /// Get the commit author
+    pub fn commit_author(&self) -> Option<&str> {
+        self.commit_author.as_deref()
+    }
+    
+    /// Get the commit committer
+    pub fn commit_committer(&self) -> Option<&str> {
+        self.commit_committer.as_deref()
+    }
+    
+    /// Get the commit time
+    pub fn committed_at(&self) -> Option<DateTime<Utc>> {
+        self.committed_at
+    }
+    
+    /// Get the commits since the last successful build of the branch
+    pub fn changes(&self) -> &[CommitInfo] {
+        &self.changes
+    }
+    
+    /// Get the build trigger
+    pub fn trigger(&self) -> &BuildTrigger {
+        &self.trigger
+    }
+    
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
     /// Get the build duration
     pub fn duration(&self) -> Option<Duration> {
         match (self.started_at, self.completed_at) {
@@ -275,6 +357,11 @@ impl Build {
         }
     }
     
+    /// Mark the build as running on a tag rather than a branch
+    pub fn set_tag(&mut self, tag: bool) {
+        self.tag = tag;
+    }
+    
     /// Set commit details
     pub fn set_commit_details(&mut self, message: String, author: String) {
         self.commit_message = Some(message);
@@ -282,6 +369,21 @@ impl Build {
         self.updated_at = Utc::now();
     }
     
+    /// Set all commit details from the built commit
+    pub fn set_commit(&mut self, commit: &CommitInfo) {
+        self.commit_message = Some(commit.message.clone());
+        self.commit_author = Some(commit.author.clone());
+        self.commit_committer = Some(commit.committer.clone());
+        self.committed_at = Some(commit.timestamp);
+        self.updated_at = Utc::now();
+    }
+    
+    /// Set the commits since the last successful build of the branch
+    pub fn set_changes(&mut self, changes: Vec<CommitInfo>) {
+        self.changes = changes;
+        self.updated_at = Utc::now();
+    }
+    
     /// Get the domain events and clear them
     pub fn take_events(&mut self) -> Vec<DomainEvent> {
         std::mem::take(&mut self.events)
@@ -141,6 +141,11 @@ impl Job {
         &self.status
     }
     
+    /// Get the job logs
+    pub fn logs(&self) -> &str {
+        &self.logs
+    }
+    
     /// Get the job duration
     pub fn duration(&self) -> Option<Duration> {
         match (self.started_at, self.completed_at) {
@@ -249,7 +254,10 @@ impl Job {
     }
     
     /// Append logs
-    pub fn append_logs(&mut self, logs: String) {
+    ///
+    /// The logs must already have their secrets masked, so job output is
+    /// only appended through the executor's `JobRun::append_logs`.
+    pub(crate) fn append_logs(&mut self, logs: String) {
         self.logs.push_str(&logs);
         self.updated_at = Utc::now();
     }
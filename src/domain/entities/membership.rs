@@ -0,0 +1,76 @@
+//! Project membership entity - Grants a user a role within one project
+
+use crate::domain::value_objects::{project_id::ProjectId, user_id::UserId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Roles a user can hold within a project, from least to most privileged
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProjectRole {
+    /// Read the project, its pipelines and builds
+    Viewer,
+    /// Viewer permissions plus triggering and cancelling builds
+    Developer,
+    /// Developer permissions plus managing the project, pipelines and members
+    Maintainer,
+}
+
+/// Project membership entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectMember {
+    /// Project the membership belongs to
+    project_id: ProjectId,
+    
+    /// Member
+    user_id: UserId,
+    
+    /// Role within the project
+    role: ProjectRole,
+    
+    /// Creation timestamp
+    created_at: DateTime<Utc>,
+    
+    /// Last update timestamp
+    updated_at: DateTime<Utc>,
+}
+
+impl ProjectMember {
+    /// Create a new membership
+    pub fn new(project_id: ProjectId, user_id: UserId, role: ProjectRole) -> Self {
+        let now = Utc::now();
+        
+        Self {
+            project_id,
+            user_id,
+            role,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+    
+    /// Get the project ID
+    pub fn project_id(&self) -> &ProjectId {
+        &self.project_id
+    }
+    
+    /// Get the user ID
+    pub fn user_id(&self) -> &UserId {
+        &self.user_id
+    }
+    
+    /// Get the role within the project
+    pub fn role(&self) -> ProjectRole {
+        self.role
+    }
+    
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
+    /// Change the role within the project
+    pub fn set_role(&mut self, role: ProjectRole) {
+        self.role = role;
+        self.updated_at = Utc::now();
+    }
+}
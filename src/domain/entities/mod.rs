@@ -5,6 +5,9 @@ pub mod build;
 pub mod project;
 pub mod agent;
 pub mod user;
+pub mod membership;
+pub mod api_token;
+pub mod secret;
 pub mod job;
 pub mod stage;
 pub mod artifact;
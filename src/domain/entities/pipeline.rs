@@ -105,11 +105,21 @@ impl Pipeline {
         &self.config
     }
     
+    /// Get the pipeline environment variables
+    pub fn environment(&self) -> &HashMap<String, String> {
+        &self.environment
+    }
+    
     /// Check if the pipeline is enabled
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
     
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
     /// Update the pipeline configuration
     pub fn update_config(&mut self, config: PipelineConfig) -> crate::Result<()> {
         // Validate the new configuration
@@ -221,7 +231,7 @@ mod tests {
                     ],
                 ),
             ],
-            vec![Trigger::Push { branches: vec!["main".to_string()] }],
+            vec![Trigger::Push { branches: vec!["main".to_string()], paths: vec![], paths_ignore: vec![] }],
         );
         
         Pipeline::new(project_id, "test-pipeline".to_string(), config)
@@ -1,6 +1,7 @@
 //! Project entity - Represents a software project with CI/CD pipelines
 
 use crate::domain::value_objects::project_id::ProjectId;
+use crate::domain::value_objects::ref_pattern::{self, RefMatcher};
 use crate::domain::events::DomainEvent;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -69,10 +70,17 @@ pub struct ProjectSettings {
     /// Enable pull request builds
     pub pr_builds_enabled: bool,
     
-    /// Protected branches
+    /// Protected branch patterns
     pub protected_branches: Vec<String>,
 }
 
+impl ProjectSettings {
+    /// Check if a branch is protected
+    pub fn is_protected_branch(&self, branch: &str) -> bool {
+        RefMatcher::new(&self.protected_branches).is_ok_and(|matcher| matcher.matches(branch))
+    }
+}
+
 impl Default for ProjectSettings {
     fn default() -> Self {
         Self {
@@ -134,6 +142,55 @@ impl Project {
         &self.repository_url
     }
     
+    /// Get the project description
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    
+    /// Get the project visibility
+    pub fn visibility(&self) -> &ProjectVisibility {
+        &self.visibility
+    }
+    
+    /// Get the default branch
+    pub fn default_branch(&self) -> &str {
+        &self.default_branch
+    }
+    
+    /// Get the project settings
+    pub fn settings(&self) -> &ProjectSettings {
+        &self.settings
+    }
+    
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
+    /// Rename the project
+    pub fn rename(&mut self, name: String) {
+        self.name = name;
+        self.updated_at = Utc::now();
+    }
+    
+    /// Set the repository URL
+    pub fn set_repository_url(&mut self, repository_url: String) {
+        self.repository_url = repository_url;
+        self.updated_at = Utc::now();
+    }
+    
+    /// Set the default branch
+    pub fn set_default_branch(&mut self, default_branch: String) {
+        self.default_branch = default_branch;
+        self.updated_at = Utc::now();
+    }
+    
+    /// Set the project description
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+        self.updated_at = Utc::now();
+    }
+    
     /// Update project settings
     pub fn update_settings(&mut self, settings: ProjectSettings) {
         self.settings = settings;
@@ -167,6 +224,8 @@ impl Project {
             return Err(crate::Error::validation("Repository URL cannot be empty"));
         }
         
+        ref_pattern::validate_patterns(&self.settings.protected_branches)?;
+        
         Ok(())
     }
 }
@@ -204,4 +263,26 @@ mod tests {
         project.update_settings(settings);
         assert_eq!(project.settings.max_concurrent_builds, 10);
     }
+
+    #[test]
+    fn test_protected_branches() {
+        let mut project = Project::new(
+            "test".to_string(),
+            "https://repo.git".to_string(),
+            "main".to_string(),
+        );
+        assert!(project.settings().is_protected_branch("main"));
+        assert!(!project.settings().is_protected_branch("feature/x"));
+        
+        let mut settings = ProjectSettings::default();
+        settings.protected_branches = vec!["release/**".to_string(), "!release/*/wip".to_string()];
+        project.update_settings(settings);
+        assert!(project.settings().is_protected_branch("release/1.0"));
+        assert!(!project.settings().is_protected_branch("release/1.0/wip"));
+        
+        let mut settings = ProjectSettings::default();
+        settings.protected_branches = vec!["/[/".to_string()];
+        project.update_settings(settings);
+        assert!(project.validate().is_err());
+    }
 }
@@ -0,0 +1,152 @@
+//! Secret entity - An encrypted value made available to jobs
+
+use crate::domain::value_objects::{pipeline_id::PipelineId, project_id::ProjectId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a secret is visible to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "type", content = "id")]
+pub enum SecretScope {
+    /// Every pipeline of a project
+    Project(ProjectId),
+    /// A single pipeline, overriding project secrets with the same name
+    Pipeline(PipelineId),
+    /// Webhook verification secrets of a project; never given to jobs
+    Webhook(ProjectId),
+    /// Credentials fetching a project's repository; never given to jobs
+    Repository(ProjectId),
+}
+
+impl fmt::Display for SecretScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretScope::Project(id) => write!(f, "project:{id}"),
+            SecretScope::Pipeline(id) => write!(f, "pipeline:{id}"),
+            SecretScope::Webhook(id) => write!(f, "webhook:{id}"),
+            SecretScope::Repository(id) => write!(f, "repository:{id}"),
+        }
+    }
+}
+
+/// Secret entity
+///
+/// Holds only the encrypted value; decryption is left to the secret service.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Secret {
+    /// Visibility of the secret
+    scope: SecretScope,
+
+    /// Secret name, usable as an environment variable name
+    name: String,
+
+    /// Nonce followed by the AES-256-GCM ciphertext
+    encrypted_value: Vec<u8>,
+
+    /// Creation timestamp
+    created_at: DateTime<Utc>,
+
+    /// Last update timestamp
+    updated_at: DateTime<Utc>,
+}
+
+impl Secret {
+    /// Create a new secret
+    pub fn new(scope: SecretScope, name: String, encrypted_value: Vec<u8>) -> crate::Result<Self> {
+        validate_name(&name)?;
+        let now = Utc::now();
+
+        Ok(Self {
+            scope,
+            name,
+            encrypted_value,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Get the scope
+    pub fn scope(&self) -> &SecretScope {
+        &self.scope
+    }
+
+    /// Get the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the encrypted value
+    pub fn encrypted_value(&self) -> &[u8] {
+        &self.encrypted_value
+    }
+
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// Get the last update timestamp
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    /// Replace the encrypted value
+    pub fn set_encrypted_value(&mut self, encrypted_value: Vec<u8>) {
+        self.encrypted_value = encrypted_value;
+        self.updated_at = Utc::now();
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secret")
+            .field("scope", &self.scope)
+            .field("name", &self.name)
+            .field("encrypted_value", &"<redacted>")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Check that a secret name is a valid environment variable name
+pub fn validate_name(name: &str) -> crate::Result<()> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(crate::Error::validation(format!(
+            "Invalid secret name '{name}': use letters, digits and underscores, not starting with a digit"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_names() {
+        assert!(validate_name("REGISTRY_PASSWORD").is_ok());
+        assert!(validate_name("_token2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("2FA").is_err());
+        assert!(validate_name("DEPLOY-KEY").is_err());
+    }
+
+    #[test]
+    fn test_debug_redacts_value() {
+        let secret = Secret::new(
+            SecretScope::Project(ProjectId::new()),
+            "TOKEN".to_string(),
+            b"ciphertext".to_vec(),
+        ).unwrap();
+
+        assert!(!format!("{secret:?}").contains("99, 105"));
+        assert!(format!("{secret:?}").contains("<redacted>"));
+    }
+}
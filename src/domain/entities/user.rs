@@ -122,6 +122,11 @@ impl User {
         &self.role
     }
     
+    /// Get the creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
     /// Check if user is active
     pub fn is_active(&self) -> bool {
         self.active
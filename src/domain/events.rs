@@ -121,6 +121,55 @@ impl DomainEvent {
         }
     }
     
+    /// Get the type of the aggregate the event belongs to
+    pub fn aggregate_type(&self) -> &str {
+        match self {
+            DomainEvent::BuildCreated { .. }
+            | DomainEvent::BuildStarted { .. }
+            | DomainEvent::BuildCompleted { .. }
+            | DomainEvent::BuildCancelled { .. } => "build",
+            DomainEvent::PipelineCreated { .. }
+            | DomainEvent::PipelineConfigUpdated { .. }
+            | DomainEvent::PipelineEnabled { .. }
+            | DomainEvent::PipelineDisabled { .. } => "pipeline",
+            DomainEvent::ProjectCreated { .. } => "project",
+            DomainEvent::AgentRegistered { .. } | DomainEvent::AgentDisconnected { .. } => "agent",
+            DomainEvent::UserCreated { .. }
+            | DomainEvent::UserPasswordChanged { .. }
+            | DomainEvent::UserDeactivated { .. } => "user",
+        }
+    }
+    
+    /// Get the identifier of the aggregate the event belongs to
+    pub fn aggregate_id(&self) -> String {
+        match self {
+            DomainEvent::BuildCreated { build_id, .. }
+            | DomainEvent::BuildStarted { build_id, .. }
+            | DomainEvent::BuildCompleted { build_id, .. }
+            | DomainEvent::BuildCancelled { build_id, .. } => build_id.to_string(),
+            DomainEvent::PipelineCreated { pipeline_id, .. }
+            | DomainEvent::PipelineConfigUpdated { pipeline_id, .. }
+            | DomainEvent::PipelineEnabled { pipeline_id, .. }
+            | DomainEvent::PipelineDisabled { pipeline_id, .. } => pipeline_id.to_string(),
+            DomainEvent::ProjectCreated { project_id, .. } => project_id.to_string(),
+            DomainEvent::AgentRegistered { agent_id, .. }
+            | DomainEvent::AgentDisconnected { agent_id, .. } => agent_id.to_string(),
+            DomainEvent::UserCreated { user_id, .. }
+            | DomainEvent::UserPasswordChanged { user_id, .. }
+            | DomainEvent::UserDeactivated { user_id, .. } => user_id.to_string(),
+        }
+    }
+    
+    /// Get the project the event concerns, when the event carries it
+    pub fn project_id(&self) -> Option<&ProjectId> {
+        match self {
+            DomainEvent::BuildCreated { project_id, .. }
+            | DomainEvent::PipelineCreated { project_id, .. }
+            | DomainEvent::ProjectCreated { project_id, .. } => Some(project_id),
+            _ => None,
+        }
+    }
+    
     /// Get the timestamp of the event
     pub fn timestamp(&self) -> DateTime<Utc> {
         match self {
@@ -158,8 +207,13 @@ pub trait EventHandler: Send + Sync {
     /// Handle a domain event
     async fn handle(&self, event: &DomainEvent) -> crate::Result<()>;
     
-    /// Get the events this handler is interested in
+    /// Get the events this handler is interested in (`"*"` matches every event)
     fn interested_in(&self) -> Vec<&str>;
+    
+    /// Get the handler name used in logs and dead letters
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
 }
 
 /// In-memory event publisher for testing
@@ -222,6 +276,18 @@ mod tests {
         assert_eq!(event.event_type(), "build.created");
     }
 
+    #[test]
+    fn test_event_aggregate_id() {
+        let build_id = BuildId::new();
+        let event = DomainEvent::BuildCancelled {
+            build_id: build_id.clone(),
+            cancelled_at: Utc::now(),
+        };
+        
+        assert_eq!(event.aggregate_id(), build_id.to_string());
+        assert_eq!(event.aggregate_type(), "build");
+    }
+
     #[test]
     fn test_event_timestamp() {
         let now = Utc::now();
@@ -0,0 +1,24 @@
+//! API token repository interface
+
+use crate::domain::entities::api_token::ApiToken;
+use crate::domain::value_objects::{api_token_id::ApiTokenId, user_id::UserId};
+use async_trait::async_trait;
+
+/// API token repository interface
+#[async_trait]
+pub trait ApiTokenRepository: Send + Sync {
+    /// Save a token
+    async fn save(&self, token: &ApiToken) -> crate::Result<()>;
+    
+    /// Find a token by ID
+    async fn find_by_id(&self, id: &ApiTokenId) -> crate::Result<Option<ApiToken>>;
+    
+    /// Find a token by the hash of its secret
+    async fn find_by_secret_hash(&self, secret_hash: &str) -> crate::Result<Option<ApiToken>>;
+    
+    /// Find all tokens of a user
+    async fn find_by_user(&self, user_id: &UserId) -> crate::Result<Vec<ApiToken>>;
+    
+    /// Update a token
+    async fn update(&self, token: &ApiToken) -> crate::Result<()>;
+}
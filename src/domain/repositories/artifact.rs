@@ -0,0 +1,30 @@
+//! Artifact repository interface
+
+use crate::domain::entities::artifact::Artifact;
+use crate::domain::value_objects::{
+    artifact_id::ArtifactId,
+    build_id::BuildId,
+};
+use async_trait::async_trait;
+
+/// Artifact repository interface
+#[async_trait]
+pub trait ArtifactRepository: Send + Sync {
+    /// Save an artifact
+    async fn save(&self, artifact: &Artifact) -> crate::Result<()>;
+    
+    /// Find an artifact by ID
+    async fn find_by_id(&self, id: &ArtifactId) -> crate::Result<Option<Artifact>>;
+    
+    /// Find the artifacts of a build, oldest first
+    async fn find_by_build(&self, build_id: &BuildId) -> crate::Result<Vec<Artifact>>;
+    
+    /// Find every artifact, oldest first
+    async fn find_all(&self) -> crate::Result<Vec<Artifact>>;
+    
+    /// Update an artifact
+    async fn update(&self, artifact: &Artifact) -> crate::Result<()>;
+    
+    /// Delete an artifact
+    async fn delete(&self, id: &ArtifactId) -> crate::Result<()>;
+}
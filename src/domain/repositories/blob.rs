@@ -0,0 +1,62 @@
+//! Blob repository interface
+//!
+//! Content-addressed stores keep each distinct content once, as a blob named
+//! by its SHA256. The repository records which key links to which blob and
+//! how many keys reference each blob.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Contents stored once, however many keys link to them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Blob {
+    /// Hex encoded SHA256 of the contents
+    pub checksum: String,
+
+    /// Size in bytes
+    pub size: u64,
+
+    /// Number of keys linking to the blob
+    pub ref_count: u64,
+}
+
+/// Storage used by a content-addressed store
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlobStats {
+    /// Number of keys
+    pub objects: u64,
+
+    /// Number of blobs, including unreferenced ones not collected yet
+    pub blobs: u64,
+
+    /// Bytes of all keys, as if every key had its own copy
+    pub logical_bytes: u64,
+
+    /// Bytes of all blobs
+    pub physical_bytes: u64,
+}
+
+/// Blob repository interface
+#[async_trait]
+pub trait BlobRepository: Send + Sync {
+    /// Link a key to the blob with a checksum, creating the blob if needed
+    ///
+    /// A key linked to another blob before releases its reference to it.
+    async fn link(&self, key: &str, checksum: &str, size: u64) -> crate::Result<()>;
+
+    /// Remove the link of a key, returning the blob it linked to
+    async fn unlink(&self, key: &str) -> crate::Result<Option<Blob>>;
+
+    /// Find the blob a key links to
+    async fn find(&self, key: &str) -> crate::Result<Option<Blob>>;
+
+    /// Find the blobs no key links to
+    async fn find_unreferenced(&self) -> crate::Result<Vec<Blob>>;
+
+    /// Delete a blob unless a key links to it again, returning whether it
+    /// was deleted
+    async fn delete_unreferenced(&self, checksum: &str) -> crate::Result<bool>;
+
+    /// Get the storage used by the keys and blobs
+    async fn stats(&self) -> crate::Result<BlobStats>;
+}
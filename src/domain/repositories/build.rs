@@ -15,6 +15,9 @@ pub struct BuildQueryOptions {
     /// Filter by project ID
     pub project_id: Option<ProjectId>,
     
+    /// Filter by any of several project IDs
+    pub project_ids: Option<Vec<ProjectId>>,
+    
     /// Filter by pipeline ID
     pub pipeline_id: Option<PipelineId>,
     
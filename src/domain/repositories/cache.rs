@@ -0,0 +1,58 @@
+//! Dependency cache repository interface
+
+use crate::domain::value_objects::project_id::ProjectId;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A saved dependency cache of a project
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// Project the cache belongs to
+    pub project_id: ProjectId,
+
+    /// Full name of the ref whose build saved the cache, e.g. `refs/heads/main`
+    ///
+    /// Empty for caches saved before they were scoped by ref; those are never
+    /// restored and are left to expire.
+    #[serde(default)]
+    pub git_ref: String,
+
+    /// Cache key, with its expressions expanded
+    pub key: String,
+
+    /// Key of the archive in the cache store
+    pub path: String,
+
+    /// Archive size in bytes
+    pub size: u64,
+
+    /// Hex encoded SHA256 of the archive
+    pub checksum: String,
+
+    /// When the cache was saved
+    pub created_at: DateTime<Utc>,
+
+    /// When the cache was last saved or restored
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// Dependency cache repository interface
+#[async_trait]
+pub trait CacheRepository: Send + Sync {
+    /// Save a cache entry, replacing the entry with the same key
+    async fn save(&self, entry: &CacheEntry) -> crate::Result<()>;
+
+    /// Find the cache saved by a project's ref with a key
+    async fn find(&self, project_id: &ProjectId, git_ref: &str, key: &str) -> crate::Result<Option<CacheEntry>>;
+
+    /// Find the caches saved by a project's ref whose key starts with a
+    /// prefix, newest first
+    async fn find_by_prefix(&self, project_id: &ProjectId, git_ref: &str, prefix: &str) -> crate::Result<Vec<CacheEntry>>;
+
+    /// Find the caches of every project
+    async fn find_all(&self) -> crate::Result<Vec<CacheEntry>>;
+
+    /// Delete the cache saved by a project's ref with a key
+    async fn delete(&self, project_id: &ProjectId, git_ref: &str, key: &str) -> crate::Result<()>;
+}
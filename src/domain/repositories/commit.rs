@@ -0,0 +1,44 @@
+//! Commit repository interface
+
+use crate::domain::value_objects::{
+    commit_info::CommitInfo,
+    project_id::ProjectId,
+};
+use async_trait::async_trait;
+
+/// Read access to the commit history of project repositories
+#[async_trait]
+pub trait CommitRepository: Send + Sync {
+    /// Resolve a full ref name, e.g. `refs/heads/main`, or a commit SHA of a
+    /// project to its commit
+    async fn resolve(&self, project_id: &ProjectId, revision: &str) -> crate::Result<CommitInfo>;
+    
+    /// List the commits reachable from `commit_sha` but not from `since`,
+    /// newest first and at most `limit` of them
+    ///
+    /// An unknown `since`, e.g. after a force push, is ignored.
+    async fn history(
+        &self,
+        project_id: &ProjectId,
+        commit_sha: &str,
+        since: Option<&str>,
+        limit: usize,
+    ) -> crate::Result<Vec<CommitInfo>>;
+    
+    /// List the files changed between two commits
+    async fn changed_files(
+        &self,
+        project_id: &ProjectId,
+        before: &str,
+        after: &str,
+    ) -> crate::Result<Vec<String>>;
+    
+    /// List the files a pull request from `head` changes, compared with its
+    /// merge base on the target branch
+    async fn pull_request_changed_files(
+        &self,
+        project_id: &ProjectId,
+        target_branch: &str,
+        head: &str,
+    ) -> crate::Result<Vec<String>>;
+}
@@ -0,0 +1,106 @@
+//! Event log repository interface
+
+use crate::domain::events::DomainEvent;
+use crate::domain::value_objects::project_id::ProjectId;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A domain event recorded in the event log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredEvent {
+    /// Position in the log (starts at 1)
+    pub sequence: u64,
+
+    /// Aggregate type (build, pipeline, project, agent, user)
+    pub aggregate_type: String,
+
+    /// Aggregate identifier
+    pub aggregate_id: String,
+
+    /// Project the event concerns, if any
+    #[serde(default)]
+    pub project_id: Option<ProjectId>,
+
+    /// Event type
+    pub event_type: String,
+
+    /// When the event happened
+    pub occurred_at: DateTime<Utc>,
+
+    /// When the event was written to the log
+    pub recorded_at: DateTime<Utc>,
+
+    /// The event itself
+    pub event: DomainEvent,
+}
+
+impl StoredEvent {
+    /// Create a log record for an event concerning a project
+    pub fn new(sequence: u64, event: DomainEvent, project_id: Option<ProjectId>) -> Self {
+        Self {
+            sequence,
+            aggregate_type: event.aggregate_type().to_string(),
+            aggregate_id: event.aggregate_id(),
+            project_id,
+            event_type: event.event_type().to_string(),
+            occurred_at: event.timestamp(),
+            recorded_at: Utc::now(),
+            event,
+        }
+    }
+}
+
+/// Event query options
+#[derive(Debug, Clone, Default)]
+pub struct EventQueryOptions {
+    /// Filter by aggregate type
+    pub aggregate_type: Option<String>,
+
+    /// Filter by aggregate ID
+    pub aggregate_id: Option<String>,
+
+    /// Filter by the project events concern
+    pub project_id: Option<ProjectId>,
+
+    /// Filter by event types (any of)
+    pub event_types: Vec<String>,
+
+    /// Only events that occurred at or after this time
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only events that occurred before this time
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only events recorded after this sequence number
+    pub after_sequence: Option<u64>,
+
+    /// Limit number of results
+    pub limit: Option<usize>,
+}
+
+impl EventQueryOptions {
+    /// Check whether a stored event matches the filters (ignores `limit`)
+    pub fn matches(&self, stored: &StoredEvent) -> bool {
+        self.aggregate_type.as_ref().is_none_or(|t| *t == stored.aggregate_type)
+            && self.aggregate_id.as_ref().is_none_or(|id| *id == stored.aggregate_id)
+            && self.project_id.as_ref().is_none_or(|id| stored.project_id.as_ref() == Some(id))
+            && (self.event_types.is_empty() || self.event_types.contains(&stored.event_type))
+            && self.since.is_none_or(|since| stored.occurred_at >= since)
+            && self.until.is_none_or(|until| stored.occurred_at < until)
+            && self.after_sequence.is_none_or(|after| stored.sequence > after)
+    }
+}
+
+/// Event log repository interface
+#[async_trait]
+pub trait EventRepository: Send + Sync {
+    /// Append an event concerning a project to the log
+    async fn append(&self, event: &DomainEvent, project_id: Option<ProjectId>) -> crate::Result<StoredEvent>;
+
+    /// Find events in sequence order
+    async fn query(&self, options: EventQueryOptions) -> crate::Result<Vec<StoredEvent>>;
+
+    /// Get the sequence number of the latest event (0 if the log is empty)
+    async fn latest_sequence(&self) -> crate::Result<u64>;
+}
@@ -0,0 +1,27 @@
+//! Project membership repository interface
+
+use crate::domain::entities::membership::ProjectMember;
+use crate::domain::value_objects::{project_id::ProjectId, user_id::UserId};
+use async_trait::async_trait;
+
+/// Project membership repository interface
+#[async_trait]
+pub trait MembershipRepository: Send + Sync {
+    /// Save a membership, replacing any existing one for the same project and user
+    async fn save(&self, member: &ProjectMember) -> crate::Result<()>;
+    
+    /// Find the membership of a user in a project
+    async fn find(&self, project_id: &ProjectId, user_id: &UserId) -> crate::Result<Option<ProjectMember>>;
+    
+    /// Find all members of a project
+    async fn find_by_project(&self, project_id: &ProjectId) -> crate::Result<Vec<ProjectMember>>;
+    
+    /// Find all memberships of a user
+    async fn find_by_user(&self, user_id: &UserId) -> crate::Result<Vec<ProjectMember>>;
+    
+    /// Delete the membership of a user in a project
+    async fn delete(&self, project_id: &ProjectId, user_id: &UserId) -> crate::Result<()>;
+    
+    /// Delete all memberships of a project
+    async fn delete_by_project(&self, project_id: &ProjectId) -> crate::Result<()>;
+}
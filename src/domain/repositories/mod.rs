@@ -5,4 +5,12 @@ pub mod build;
 pub mod project;
 pub mod agent;
 pub mod user;
+pub mod membership;
+pub mod api_token;
+pub mod secret;
+pub mod event;
+pub mod commit;
+pub mod artifact;
+pub mod cache;
+pub mod blob;
 
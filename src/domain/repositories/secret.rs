@@ -0,0 +1,23 @@
+//! Secret repository interface
+
+use crate::domain::entities::secret::{Secret, SecretScope};
+use async_trait::async_trait;
+
+/// Secret repository interface
+#[async_trait]
+pub trait SecretRepository: Send + Sync {
+    /// Save a secret, replacing any existing one with the same scope and name
+    async fn save(&self, secret: &Secret) -> crate::Result<()>;
+
+    /// Find a secret by scope and name
+    async fn find(&self, scope: &SecretScope, name: &str) -> crate::Result<Option<Secret>>;
+
+    /// Find all secrets of a scope
+    async fn find_by_scope(&self, scope: &SecretScope) -> crate::Result<Vec<Secret>>;
+
+    /// Delete a secret
+    async fn delete(&self, scope: &SecretScope, name: &str) -> crate::Result<()>;
+
+    /// Delete all secrets of a scope
+    async fn delete_by_scope(&self, scope: &SecretScope) -> crate::Result<()>;
+}
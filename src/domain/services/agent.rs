@@ -83,6 +83,19 @@ impl AgentService {
         Ok(())
     }
     
+    /// Get an agent by ID
+    pub async fn get_agent(&self, agent_id: &AgentId) -> crate::Result<Agent> {
+        self.repository
+            .find_by_id(agent_id)
+            .await?
+            .ok_or_else(|| crate::Error::not_found("Agent not found"))
+    }
+    
+    /// Get all agents
+    pub async fn list_agents(&self) -> crate::Result<Vec<Agent>> {
+        self.repository.find_all().await
+    }
+    
     /// Find available agents for a job
     pub async fn find_available_agents(&self) -> crate::Result<Vec<Agent>> {
         self.repository.find_available().await
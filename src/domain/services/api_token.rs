@@ -0,0 +1,212 @@
+//! API token domain service
+//!
+//! Issues personal and service account tokens. A token secret is a random
+//! string with a recognizable prefix; only its SHA256 hash is stored, which is
+//! enough for high-entropy secrets and keeps per-request lookups cheap.
+
+use crate::domain::entities::api_token::{ApiToken, TokenScope};
+use crate::domain::value_objects::{
+    api_token_id::ApiTokenId,
+    project_id::ProjectId,
+    user_id::UserId,
+};
+use crate::domain::repositories::{api_token::ApiTokenRepository, user::UserRepository};
+use crate::domain::services::auth::{AuthenticatedUser, TokenGrant};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Prefix of every API token secret, used to tell them apart from JWTs
+pub const API_TOKEN_PREFIX: &str = "fci_";
+
+/// API token service
+pub struct ApiTokenService {
+    tokens: Arc<dyn ApiTokenRepository>,
+    users: Arc<dyn UserRepository>,
+}
+
+impl ApiTokenService {
+    /// Create a new API token service
+    pub fn new(tokens: Arc<dyn ApiTokenRepository>, users: Arc<dyn UserRepository>) -> Self {
+        Self { tokens, users }
+    }
+
+    /// Create a token for a user, returning it with its secret
+    ///
+    /// The secret cannot be recovered later.
+    pub async fn create_token(
+        &self,
+        user_id: &UserId,
+        name: String,
+        scopes: Vec<TokenScope>,
+        project_id: Option<ProjectId>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> crate::Result<(ApiToken, String)> {
+        let user = self.users
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| crate::Error::not_found("User not found"))?;
+        if !user.is_active() {
+            return Err(crate::Error::conflict("User is deactivated"));
+        }
+
+        let secret = generate_secret();
+        let token = ApiToken::new(
+            user_id.clone(),
+            name,
+            hash_secret(&secret),
+            scopes,
+            project_id,
+            expires_at,
+        )?;
+
+        self.tokens.save(&token).await?;
+
+        Ok((token, secret))
+    }
+
+    /// Get a token by ID
+    pub async fn get_token(&self, token_id: &ApiTokenId) -> crate::Result<ApiToken> {
+        self.tokens
+            .find_by_id(token_id)
+            .await?
+            .ok_or_else(|| crate::Error::not_found("API token not found"))
+    }
+
+    /// List the tokens of a user, including revoked and expired ones
+    pub async fn list_tokens(&self, user_id: &UserId) -> crate::Result<Vec<ApiToken>> {
+        self.tokens.find_by_user(user_id).await
+    }
+
+    /// Revoke a token
+    pub async fn revoke_token(&self, token_id: &ApiTokenId) -> crate::Result<ApiToken> {
+        let mut token = self.get_token(token_id).await?;
+        token.revoke()?;
+        self.tokens.update(&token).await?;
+        Ok(token)
+    }
+
+    /// Verify a token secret and load its active user
+    pub async fn authenticate(&self, secret: &str) -> crate::Result<AuthenticatedUser> {
+        let mut token = self.tokens
+            .find_by_secret_hash(&hash_secret(secret))
+            .await?
+            .ok_or_else(|| crate::Error::authentication("Invalid API token"))?;
+
+        if token.is_revoked() {
+            return Err(crate::Error::authentication("API token has been revoked"));
+        }
+        if token.is_expired() {
+            return Err(crate::Error::authentication("API token has expired"));
+        }
+
+        let user = self.users
+            .find_by_id(token.user_id())
+            .await?
+            .ok_or_else(|| crate::Error::authentication("User no longer exists"))?;
+        if !user.is_active() {
+            return Err(crate::Error::authentication("User is deactivated"));
+        }
+
+        token.record_use();
+        self.tokens.update(&token).await?;
+
+        Ok(AuthenticatedUser {
+            user_id: user.id().clone(),
+            role: user.role().clone(),
+            api_token: Some(TokenGrant::from(&token)),
+        })
+    }
+}
+
+/// Check whether a bearer token is an API token rather than a JWT
+pub fn is_api_token(token: &str) -> bool {
+    token.starts_with(API_TOKEN_PREFIX)
+}
+
+fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    format!("{API_TOKEN_PREFIX}{}", hex::encode(bytes))
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::user::{User, UserRole};
+    use crate::infrastructure::repositories::in_memory::{
+        InMemoryApiTokenRepository,
+        InMemoryUserRepository,
+    };
+
+    async fn create_service() -> (ApiTokenService, Arc<InMemoryUserRepository>, User) {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let user = User::new(
+            "deploy-bot".to_string(),
+            "deploy@example.com".to_string(),
+            "unused".to_string(),
+            UserRole::Service,
+        ).unwrap();
+        users.save(&user).await.unwrap();
+
+        let service = ApiTokenService::new(Arc::new(InMemoryApiTokenRepository::new()), users.clone());
+        (service, users, user)
+    }
+
+    #[tokio::test]
+    async fn test_create_and_authenticate() {
+        let (service, _, user) = create_service().await;
+        let project_id = ProjectId::new();
+
+        let (token, secret) = service.create_token(
+            user.id(),
+            "deploy".to_string(),
+            vec![TokenScope::BuildsTrigger],
+            Some(project_id.clone()),
+            None,
+        ).await.unwrap();
+        assert!(is_api_token(&secret));
+        assert_ne!(token.secret_hash(), secret);
+
+        let caller = service.authenticate(&secret).await.unwrap();
+        assert_eq!(caller.user_id, *user.id());
+        assert_eq!(caller.role, UserRole::Service);
+        let grant = caller.api_token.unwrap();
+        assert_eq!(grant.token_id, *token.id());
+        assert_eq!(grant.scopes, vec![TokenScope::BuildsTrigger]);
+        assert_eq!(grant.project_id, Some(project_id));
+
+        assert!(service.get_token(token.id()).await.unwrap().last_used_at().is_some());
+        assert!(service.authenticate("fci_unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_revoked_token_is_rejected() {
+        let (service, users, mut user) = create_service().await;
+        let (token, secret) = service.create_token(
+            user.id(),
+            "ci".to_string(),
+            vec![TokenScope::ProjectsRead],
+            None,
+            None,
+        ).await.unwrap();
+
+        user.deactivate();
+        users.update(&user).await.unwrap();
+        assert!(service.authenticate(&secret).await.is_err());
+
+        user.activate();
+        users.update(&user).await.unwrap();
+        assert!(service.authenticate(&secret).await.is_ok());
+
+        service.revoke_token(token.id()).await.unwrap();
+        assert!(matches!(
+            service.authenticate(&secret).await,
+            Err(crate::Error::Authentication(_))
+        ));
+        assert_eq!(service.list_tokens(user.id()).await.unwrap().len(), 1);
+    }
+}
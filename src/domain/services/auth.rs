@@ -0,0 +1,354 @@
+//! Authentication domain service
+
+use crate::config::SecurityConfig;
+use crate::domain::entities::{
+    api_token::{ApiToken, TokenScope},
+    user::{User, UserRole},
+};
+use crate::domain::value_objects::{
+    api_token_id::ApiTokenId,
+    project_id::ProjectId,
+    user_id::UserId,
+};
+use crate::domain::repositories::user::UserRepository;
+use crate::domain::events::EventPublisher;
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Kind of a JWT issued at login
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    /// Short-lived token sent with API requests
+    Access,
+    /// Long-lived token exchanged for a new token pair
+    Refresh,
+}
+
+/// JWT claims
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// User ID
+    pub sub: String,
+
+    /// User role when the token was issued
+    pub role: UserRole,
+
+    /// Token kind
+    pub kind: TokenKind,
+
+    /// Issued at (seconds since the epoch)
+    pub iat: i64,
+
+    /// Expires at (seconds since the epoch)
+    pub exp: i64,
+}
+
+/// Access and refresh tokens issued together
+#[derive(Debug, Clone)]
+pub struct TokenPair {
+    /// Access token
+    pub access_token: String,
+
+    /// Refresh token
+    pub refresh_token: String,
+
+    /// Access token lifetime in seconds
+    pub expires_in: u64,
+}
+
+/// The caller of an authenticated request
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatedUser {
+    /// User ID
+    pub user_id: UserId,
+
+    /// Current user role
+    pub role: UserRole,
+
+    /// Grant of the API token the request was made with, `None` for JWTs
+    pub api_token: Option<TokenGrant>,
+}
+
+/// What an API token allows on top of its user's role
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenGrant {
+    /// Token ID
+    pub token_id: ApiTokenId,
+
+    /// Granted scopes
+    pub scopes: Vec<TokenScope>,
+
+    /// Project the token is restricted to
+    pub project_id: Option<ProjectId>,
+}
+
+impl From<&ApiToken> for TokenGrant {
+    fn from(token: &ApiToken) -> Self {
+        Self {
+            token_id: token.id().clone(),
+            scopes: token.scopes().to_vec(),
+            project_id: token.project_id().cloned(),
+        }
+    }
+}
+
+/// Authentication service issuing and verifying JWTs
+pub struct AuthService {
+    repository: Arc<dyn UserRepository>,
+    event_publisher: Arc<dyn EventPublisher>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    access_token_ttl: u64,
+    refresh_token_ttl: u64,
+    password_hash_cost: u32,
+    /// Hash checked against when a username is unknown, so failed logins
+    /// take as long whether or not the user exists
+    dummy_hash: OnceCell<String>,
+}
+
+impl AuthService {
+    /// Create a new authentication service
+    pub fn new(
+        repository: Arc<dyn UserRepository>,
+        event_publisher: Arc<dyn EventPublisher>,
+        config: &SecurityConfig,
+    ) -> Self {
+        Self {
+            repository,
+            event_publisher,
+            encoding_key: EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+            access_token_ttl: config.session_timeout,
+            refresh_token_ttl: config.refresh_token_timeout,
+            password_hash_cost: config.password_hash_cost,
+            dummy_hash: OnceCell::new(),
+        }
+    }
+
+    /// Create a user with a bcrypt-hashed password
+    pub async fn register_user(
+        &self,
+        username: String,
+        email: String,
+        password: String,
+        role: UserRole,
+    ) -> crate::Result<User> {
+        if self.repository.username_exists(&username).await? {
+            return Err(crate::Error::conflict("Username already exists"));
+        }
+        if self.repository.email_exists(&email).await? {
+            return Err(crate::Error::conflict("Email already exists"));
+        }
+
+        let password_hash = self.hash_password(password).await?;
+        let mut user = User::new(username, email, password_hash, role)?;
+
+        self.repository.save(&user).await?;
+
+        let events = user.take_events();
+        self.event_publisher.publish_batch(events).await?;
+
+        Ok(user)
+    }
+
+    /// Check a username and password and issue a token pair
+    pub async fn login(&self, username: &str, password: &str) -> crate::Result<TokenPair> {
+        let invalid = || crate::Error::authentication("Invalid username or password");
+
+        let Some(mut user) = self.repository.find_by_username(username).await? else {
+            let dummy_hash = self
+                .dummy_hash
+                .get_or_try_init(|| self.hash_password("not a password".to_string()))
+                .await?;
+            verify_password(password.to_string(), dummy_hash.clone()).await?;
+            return Err(invalid());
+        };
+
+        if !verify_password(password.to_string(), user.password_hash().to_string()).await? {
+            return Err(invalid());
+        }
+        if !user.is_active() {
+            return Err(crate::Error::authentication("User is deactivated"));
+        }
+        if *user.role() == UserRole::Service {
+            return Err(crate::Error::authentication("Service accounts authenticate with API tokens"));
+        }
+
+        user.record_login();
+        self.repository.update(&user).await?;
+
+        self.issue_tokens(&user)
+    }
+
+    /// Exchange a refresh token for a new token pair
+    pub async fn refresh(&self, refresh_token: &str) -> crate::Result<TokenPair> {
+        let claims = self.decode(refresh_token, TokenKind::Refresh)?;
+        let user = self.active_user(&claims).await?;
+        self.issue_tokens(&user)
+    }
+
+    /// Verify an access token and load its active user
+    pub async fn authenticate(&self, access_token: &str) -> crate::Result<AuthenticatedUser> {
+        let claims = self.decode(access_token, TokenKind::Access)?;
+        let user = self.active_user(&claims).await?;
+
+        Ok(AuthenticatedUser {
+            user_id: user.id().clone(),
+            role: user.role().clone(),
+            api_token: None,
+        })
+    }
+
+    /// Get a user by ID
+    pub async fn get_user(&self, user_id: &UserId) -> crate::Result<User> {
+        self.repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| crate::Error::not_found("User not found"))
+    }
+
+    /// Hash a password with the configured bcrypt cost
+    pub async fn hash_password(&self, password: String) -> crate::Result<String> {
+        let cost = self.password_hash_cost;
+        tokio::task::spawn_blocking(move || bcrypt::hash(password, cost))
+            .await
+            .map_err(|e| crate::Error::internal(e.to_string()))?
+            .map_err(|e| crate::Error::internal(format!("Failed to hash password: {e}")))
+    }
+
+    fn issue_tokens(&self, user: &User) -> crate::Result<TokenPair> {
+        Ok(TokenPair {
+            access_token: self.encode(user, TokenKind::Access, self.access_token_ttl)?,
+            refresh_token: self.encode(user, TokenKind::Refresh, self.refresh_token_ttl)?,
+            expires_in: self.access_token_ttl,
+        })
+    }
+
+    fn encode(&self, user: &User, kind: TokenKind, ttl: u64) -> crate::Result<String> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: user.id().to_string(),
+            role: user.role().clone(),
+            kind,
+            iat: now,
+            exp: now.saturating_add(i64::try_from(ttl).unwrap_or(i64::MAX)),
+        };
+
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| crate::Error::internal(format!("Failed to issue token: {e}")))
+    }
+
+    fn decode(&self, token: &str, kind: TokenKind) -> crate::Result<Claims> {
+        let claims = jsonwebtoken::decode::<Claims>(
+            token,
+            &self.decoding_key,
+            &Validation::new(Algorithm::HS256),
+        )
+        .map_err(|e| crate::Error::authentication(format!("Invalid token: {e}")))?
+        .claims;
+
+        if claims.kind != kind {
+            return Err(crate::Error::authentication("Invalid token: wrong token kind"));
+        }
+
+        Ok(claims)
+    }
+
+    async fn active_user(&self, claims: &Claims) -> crate::Result<User> {
+        let user_id = UserId::parse(&claims.sub)
+            .map_err(|_| crate::Error::authentication("Invalid token: bad subject"))?;
+
+        let user = self.repository
+            .find_by_id(&user_id)
+            .await?
+            .ok_or_else(|| crate::Error::authentication("User no longer exists"))?;
+
+        if !user.is_active() {
+            return Err(crate::Error::authentication("User is deactivated"));
+        }
+
+        Ok(user)
+    }
+}
+
+async fn verify_password(password: String, hash: String) -> crate::Result<bool> {
+    tokio::task::spawn_blocking(move || bcrypt::verify(password, &hash))
+        .await
+        .map_err(|e| crate::Error::internal(e.to_string()))?
+        .map_err(|e| crate::Error::internal(format!("Failed to verify password: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::domain::events::InMemoryEventPublisher;
+    use crate::infrastructure::repositories::in_memory::InMemoryUserRepository;
+
+    fn create_service() -> (AuthService, Arc<InMemoryUserRepository>) {
+        let repository = Arc::new(InMemoryUserRepository::new());
+        let mut config = Config::default().security;
+        config.password_hash_cost = 4;
+
+        let service = AuthService::new(
+            repository.clone(),
+            Arc::new(InMemoryEventPublisher::new()),
+            &config,
+        );
+        (service, repository)
+    }
+
+    async fn register(service: &AuthService) -> User {
+        service.register_user(
+            "alice".to_string(),
+            "alice@example.com".to_string(),
+            "s3cret-password".to_string(),
+            UserRole::Developer,
+        ).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_login_and_authenticate() {
+        let (service, _) = create_service();
+        let user = register(&service).await;
+
+        assert!(service.login("alice", "wrong").await.is_err());
+        assert!(matches!(
+            service.login("bob", "s3cret-password").await,
+            Err(crate::Error::Authentication(_))
+        ));
+        assert!(service.dummy_hash.initialized());
+
+        let tokens = service.login("alice", "s3cret-password").await.unwrap();
+        let caller = service.authenticate(&tokens.access_token).await.unwrap();
+        assert_eq!(caller.user_id, *user.id());
+        assert_eq!(caller.role, UserRole::Developer);
+
+        // Refresh tokens cannot be used as access tokens and vice versa
+        assert!(service.authenticate(&tokens.refresh_token).await.is_err());
+        assert!(service.refresh(&tokens.access_token).await.is_err());
+
+        let refreshed = service.refresh(&tokens.refresh_token).await.unwrap();
+        assert!(service.authenticate(&refreshed.access_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_deactivated_user_is_rejected() {
+        let (service, repository) = create_service();
+        let mut user = register(&service).await;
+        let tokens = service.login("alice", "s3cret-password").await.unwrap();
+
+        user.deactivate();
+        repository.update(&user).await.unwrap();
+
+        assert!(matches!(
+            service.authenticate(&tokens.access_token).await,
+            Err(crate::Error::Authentication(_))
+        ));
+        assert!(service.login("alice", "s3cret-password").await.is_err());
+    }
+}
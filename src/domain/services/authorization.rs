@@ -0,0 +1,433 @@
+//! Authorization domain service
+//!
+//! Decides whether a user may perform an action. Global actions depend only on the
+//! user's role. Project actions depend on the user's effective role in the
+//! project: administrators act as maintainers everywhere, members use their
+//! membership role, and non-members of public and internal projects are
+//! viewers. Private projects, and any write access, require membership.
+//!
+//! Requests made with an API token are further limited to the token's scopes
+//! and, when set, its project.
+
+use crate::domain::entities::{
+    api_token::TokenScope,
+    membership::{ProjectMember, ProjectRole},
+    project::{Project, ProjectVisibility},
+    user::UserRole,
+};
+use crate::domain::value_objects::{project_id::ProjectId, user_id::UserId};
+use crate::domain::repositories::membership::MembershipRepository;
+use crate::domain::services::auth::AuthenticatedUser;
+use std::fmt;
+use std::sync::Arc;
+
+/// Actions that can be authorized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Create a project
+    CreateProject,
+    /// Read a project, its pipelines, builds and events
+    ReadProject,
+    /// Update a project
+    UpdateProject,
+    /// Delete a project
+    DeleteProject,
+    /// Add, change and remove project members
+    ManageMembers,
+    /// Create, update, delete, enable and disable pipelines
+    ManagePipelines,
+    /// List, set and delete project and pipeline secrets
+    ManageSecrets,
+    /// Trigger and retry builds
+    TriggerBuild,
+    /// Cancel builds
+    CancelBuild,
+    /// List and read agents
+    ReadAgents,
+    /// Register and manage agents
+    ManageAgents,
+    /// Read the global event log
+    ReadEvents,
+    /// Replay events into handlers
+    ReplayEvents,
+    /// Manage users
+    ManageUsers,
+    /// Create, list and revoke one's own API tokens
+    ManageTokens,
+    /// Read artifact and cache storage usage
+    ReadStorage,
+    /// Download artifacts of a project's builds
+    DownloadArtifacts,
+    /// Pin and unpin artifacts of a project's builds
+    ManageArtifacts,
+}
+
+impl Action {
+    /// Minimum project role needed for a project action, `None` for global actions
+    pub fn required_project_role(self) -> Option<ProjectRole> {
+        match self {
+            Action::ReadProject | Action::DownloadArtifacts => Some(ProjectRole::Viewer),
+            Action::TriggerBuild | Action::CancelBuild => Some(ProjectRole::Developer),
+            Action::UpdateProject
+            | Action::DeleteProject
+            | Action::ManageMembers
+            | Action::ManagePipelines
+            | Action::ManageSecrets
+            | Action::ManageArtifacts => Some(ProjectRole::Maintainer),
+            _ => None,
+        }
+    }
+    
+    /// Token scope needed for an action, `None` when API tokens may not perform it
+    pub fn required_scope(self) -> Option<TokenScope> {
+        match self {
+            Action::ReadProject => Some(TokenScope::ProjectsRead),
+            Action::CreateProject
+            | Action::UpdateProject
+            | Action::DeleteProject
+            | Action::ManageMembers => Some(TokenScope::ProjectsWrite),
+            Action::ManagePipelines => Some(TokenScope::PipelinesWrite),
+            Action::TriggerBuild => Some(TokenScope::BuildsTrigger),
+            Action::CancelBuild => Some(TokenScope::BuildsCancel),
+            Action::ReadAgents => Some(TokenScope::AgentsRead),
+            Action::ManageAgents => Some(TokenScope::AgentsWrite),
+            Action::ReadEvents => Some(TokenScope::EventsRead),
+            Action::ReadStorage | Action::DownloadArtifacts => Some(TokenScope::ArtifactsRead),
+            Action::ManageArtifacts => Some(TokenScope::ArtifactsWrite),
+            Action::ManageSecrets
+            | Action::ReplayEvents
+            | Action::ManageUsers
+            | Action::ManageTokens => None,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::CreateProject => "create projects",
+            Action::ReadProject => "read this project",
+            Action::UpdateProject => "update this project",
+            Action::DeleteProject => "delete this project",
+            Action::ManageMembers => "manage members of this project",
+            Action::ManagePipelines => "manage pipelines of this project",
+            Action::ManageSecrets => "manage secrets of this project",
+            Action::TriggerBuild => "trigger builds of this project",
+            Action::CancelBuild => "cancel builds of this project",
+            Action::ReadAgents => "read agents",
+            Action::ManageAgents => "manage agents",
+            Action::ReadEvents => "read the event log",
+            Action::ReplayEvents => "replay events",
+            Action::ManageUsers => "manage users",
+            Action::ManageTokens => "manage API tokens",
+            Action::ReadStorage => "read storage usage",
+            Action::DownloadArtifacts => "download artifacts of this project",
+            Action::ManageArtifacts => "manage artifacts of this project",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Check whether a user role allows a global action
+pub fn allows_global(role: &UserRole, action: Action) -> bool {
+    match action {
+        Action::ReadAgents | Action::ManageTokens => true,
+        Action::CreateProject => matches!(role, UserRole::Admin | UserRole::Developer),
+        _ => *role == UserRole::Admin,
+    }
+}
+
+/// Get the highest project role a user role may hold, whatever the membership
+pub fn role_ceiling(role: &UserRole) -> ProjectRole {
+    match role {
+        UserRole::Admin | UserRole::Developer | UserRole::Service => ProjectRole::Maintainer,
+        UserRole::Viewer => ProjectRole::Viewer,
+    }
+}
+
+/// Get a user's effective role in a project, `None` when the project is hidden from them
+///
+/// A membership grants its role up to the ceiling of the user's global role,
+/// so global viewers stay read-only in the projects they are members of.
+pub fn effective_project_role(
+    role: &UserRole,
+    visibility: &ProjectVisibility,
+    membership: Option<ProjectRole>,
+) -> Option<ProjectRole> {
+    if *role == UserRole::Admin {
+        return Some(ProjectRole::Maintainer);
+    }
+    if let Some(membership) = membership {
+        return Some(membership.min(role_ceiling(role)));
+    }
+    
+    match visibility {
+        ProjectVisibility::Private => None,
+        ProjectVisibility::Public | ProjectVisibility::Internal => Some(ProjectRole::Viewer),
+    }
+}
+
+/// Authorization service
+pub struct AuthorizationService {
+    memberships: Arc<dyn MembershipRepository>,
+}
+
+impl AuthorizationService {
+    /// Create a new authorization service
+    pub fn new(memberships: Arc<dyn MembershipRepository>) -> Self {
+        Self { memberships }
+    }
+    
+    /// Authorize a global action
+    pub fn authorize(&self, user: &AuthenticatedUser, action: Action) -> crate::Result<()> {
+        if action.required_project_role().is_some() {
+            return Err(crate::Error::internal(format!("{action:?} needs a project")));
+        }
+        
+        if !allows_global(&user.role, action) {
+            return Err(deny(action));
+        }
+        
+        check_token(user, action, None)
+    }
+    
+    /// Authorize an action on a project
+    pub async fn authorize_project(
+        &self,
+        user: &AuthenticatedUser,
+        action: Action,
+        project: &Project,
+    ) -> crate::Result<()> {
+        let Some(required) = action.required_project_role() else {
+            return self.authorize(user, action);
+        };
+        
+        match self.project_role(user, project).await? {
+            Some(role) if role >= required => check_token(user, action, Some(project.id())),
+            _ => Err(deny(action)),
+        }
+    }
+    
+    /// Check whether an action on a project is allowed, without failing on denial
+    pub async fn is_allowed(
+        &self,
+        user: &AuthenticatedUser,
+        action: Action,
+        project: &Project,
+    ) -> crate::Result<bool> {
+        match self.authorize_project(user, action, project).await {
+            Ok(()) => Ok(true),
+            Err(crate::Error::Authorization(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Get a user's effective role in a project
+    pub async fn project_role(
+        &self,
+        user: &AuthenticatedUser,
+        project: &Project,
+    ) -> crate::Result<Option<ProjectRole>> {
+        let membership = if user.role == UserRole::Admin {
+            None
+        } else {
+            self.memberships
+                .find(project.id(), &user.user_id)
+                .await?
+                .map(|m| m.role())
+        };
+        
+        Ok(effective_project_role(&user.role, project.visibility(), membership))
+    }
+    
+    /// Add a member to a project or change their role
+    pub async fn set_member(
+        &self,
+        project_id: &ProjectId,
+        user_id: &UserId,
+        role: ProjectRole,
+    ) -> crate::Result<ProjectMember> {
+        let member = match self.memberships.find(project_id, user_id).await? {
+            Some(mut member) => {
+                member.set_role(role);
+                member
+            }
+            None => ProjectMember::new(project_id.clone(), user_id.clone(), role),
+        };
+        
+        self.memberships.save(&member).await?;
+        
+        Ok(member)
+    }
+    
+    /// Remove a member from a project
+    pub async fn remove_member(&self, project_id: &ProjectId, user_id: &UserId) -> crate::Result<()> {
+        if self.memberships.find(project_id, user_id).await?.is_none() {
+            return Err(crate::Error::not_found("Project member not found"));
+        }
+        
+        self.memberships.delete(project_id, user_id).await
+    }
+    
+    /// Remove all members from a project
+    pub async fn remove_all_members(&self, project_id: &ProjectId) -> crate::Result<()> {
+        self.memberships.delete_by_project(project_id).await
+    }
+    
+    /// Get the members of a project
+    pub async fn list_members(&self, project_id: &ProjectId) -> crate::Result<Vec<ProjectMember>> {
+        self.memberships.find_by_project(project_id).await
+    }
+}
+
+/// Limit a request made with an API token to the token's scopes and project
+fn check_token(
+    user: &AuthenticatedUser,
+    action: Action,
+    project_id: Option<&ProjectId>,
+) -> crate::Result<()> {
+    let Some(grant) = &user.api_token else {
+        return Ok(());
+    };
+    
+    let in_scope = action
+        .required_scope()
+        .is_some_and(|scope| grant.scopes.contains(&scope));
+    let in_project = match &grant.project_id {
+        Some(restricted) => project_id == Some(restricted),
+        None => true,
+    };
+    
+    if in_scope && in_project {
+        Ok(())
+    } else {
+        Err(crate::Error::authorization(format!("API token does not allow you to {action}")))
+    }
+}
+
+fn deny(action: Action) -> crate::Error {
+    crate::Error::authorization(format!("Not allowed to {action}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::auth::TokenGrant;
+    use crate::domain::value_objects::api_token_id::ApiTokenId;
+    use crate::infrastructure::repositories::in_memory::InMemoryMembershipRepository;
+
+    fn user(role: UserRole) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user_id: UserId::new(),
+            role,
+            api_token: None,
+        }
+    }
+
+    fn project(visibility: ProjectVisibility) -> Project {
+        let mut project = Project::new(
+            "test".to_string(),
+            "https://repo.git".to_string(),
+            "main".to_string(),
+        );
+        project.set_visibility(visibility);
+        project
+    }
+
+    #[test]
+    fn test_global_actions() {
+        assert!(allows_global(&UserRole::Admin, Action::ManageAgents));
+        assert!(!allows_global(&UserRole::Developer, Action::ManageAgents));
+        assert!(allows_global(&UserRole::Developer, Action::CreateProject));
+        assert!(!allows_global(&UserRole::Viewer, Action::CreateProject));
+        assert!(allows_global(&UserRole::Viewer, Action::ReadAgents));
+    }
+
+    #[test]
+    fn test_effective_project_role() {
+        use ProjectVisibility::*;
+
+        assert_eq!(effective_project_role(&UserRole::Viewer, &Internal, None), Some(ProjectRole::Viewer));
+        assert_eq!(effective_project_role(&UserRole::Developer, &Public, None), Some(ProjectRole::Viewer));
+        assert_eq!(effective_project_role(&UserRole::Service, &Internal, None), Some(ProjectRole::Viewer));
+        assert_eq!(effective_project_role(&UserRole::Developer, &Private, None), None);
+        assert_eq!(
+            effective_project_role(&UserRole::Developer, &Private, Some(ProjectRole::Maintainer)),
+            Some(ProjectRole::Maintainer)
+        );
+        assert_eq!(
+            effective_project_role(&UserRole::Service, &Private, Some(ProjectRole::Developer)),
+            Some(ProjectRole::Developer)
+        );
+
+        // Memberships never raise a user above their global role
+        assert_eq!(
+            effective_project_role(&UserRole::Viewer, &Private, Some(ProjectRole::Maintainer)),
+            Some(ProjectRole::Viewer)
+        );
+        assert_eq!(effective_project_role(&UserRole::Admin, &Private, None), Some(ProjectRole::Maintainer));
+    }
+
+    #[tokio::test]
+    async fn test_authorize_project() {
+        let service = AuthorizationService::new(Arc::new(InMemoryMembershipRepository::new()));
+        let viewer = user(UserRole::Viewer);
+        let developer = user(UserRole::Developer);
+        let internal = project(ProjectVisibility::Internal);
+        let private = project(ProjectVisibility::Private);
+
+        assert!(service.authorize_project(&viewer, Action::ReadProject, &internal).await.is_ok());
+        assert!(matches!(
+            service.authorize_project(&viewer, Action::TriggerBuild, &internal).await,
+            Err(crate::Error::Authorization(_))
+        ));
+        assert!(service.authorize_project(&developer, Action::ReadProject, &private).await.is_err());
+
+        service.set_member(private.id(), &developer.user_id, ProjectRole::Developer).await.unwrap();
+        assert!(service.authorize_project(&developer, Action::TriggerBuild, &private).await.is_ok());
+        assert!(service.authorize_project(&developer, Action::ManagePipelines, &private).await.is_err());
+        assert!(service.authorize_project(&developer, Action::ManageArtifacts, &private).await.is_err());
+
+        service.remove_member(private.id(), &developer.user_id).await.unwrap();
+        assert!(service.authorize_project(&developer, Action::ReadProject, &private).await.is_err());
+
+        // A membership lets a global viewer read, but nothing more
+        service.set_member(private.id(), &viewer.user_id, ProjectRole::Maintainer).await.unwrap();
+        assert!(service.authorize_project(&viewer, Action::ReadProject, &private).await.is_ok());
+        assert!(service.authorize_project(&viewer, Action::TriggerBuild, &private).await.is_err());
+        assert!(service.authorize_project(&viewer, Action::DeleteProject, &private).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_api_token_limits() {
+        let service = AuthorizationService::new(Arc::new(InMemoryMembershipRepository::new()));
+        let internal = project(ProjectVisibility::Internal);
+        let other = project(ProjectVisibility::Internal);
+        let mut bot = user(UserRole::Service);
+        bot.api_token = Some(TokenGrant {
+            token_id: ApiTokenId::new(),
+            scopes: vec![TokenScope::BuildsTrigger],
+            project_id: Some(internal.id().clone()),
+        });
+
+        // Write access needs a membership even on internal projects
+        assert!(!service.is_allowed(&bot, Action::TriggerBuild, &internal).await.unwrap());
+        service.set_member(internal.id(), &bot.user_id, ProjectRole::Developer).await.unwrap();
+        service.set_member(other.id(), &bot.user_id, ProjectRole::Developer).await.unwrap();
+        assert!(service.authorize_project(&bot, Action::TriggerBuild, &internal).await.is_ok());
+        assert!(!service.is_allowed(&bot, Action::ReadProject, &internal).await.unwrap());
+        assert!(!service.is_allowed(&bot, Action::TriggerBuild, &other).await.unwrap());
+        assert!(service.authorize(&bot, Action::ReadAgents).is_err());
+        assert!(service.authorize(&bot, Action::ManageTokens).is_err());
+
+        // Scopes never grant more than the user's role
+        let mut viewer = user(UserRole::Viewer);
+        viewer.api_token = Some(TokenGrant {
+            token_id: ApiTokenId::new(),
+            scopes: vec![TokenScope::BuildsTrigger, TokenScope::AgentsWrite],
+            project_id: None,
+        });
+        assert!(!service.is_allowed(&viewer, Action::TriggerBuild, &internal).await.unwrap());
+        assert!(service.authorize(&viewer, Action::ManageAgents).is_err());
+    }
+}
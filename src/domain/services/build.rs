@@ -1,20 +1,30 @@
 //! Build domain service
 
-use crate::domain::entities::build::{Build, BuildTrigger};
+use crate::domain::entities::{
+    build::{Build, BuildTrigger},
+    pipeline::Pipeline,
+};
 use crate::domain::value_objects::{
     build_id::BuildId,
     pipeline_id::PipelineId,
     project_id::ProjectId,
     agent_id::AgentId,
+    build_status::BuildStatus,
+    scm_event::{POLL_SOURCE, ScmEvent, ScmEventKind},
 };
-use crate::domain::repositories::build::BuildRepository;
+use crate::domain::repositories::build::{BuildRepository, BuildQueryOptions};
+use crate::domain::repositories::commit::CommitRepository;
 use crate::domain::events::EventPublisher;
 use std::sync::Arc;
 
+/// Most commits listed as the changes of a build
+const MAX_CHANGES: usize = 100;
+
 /// Build service
 pub struct BuildService {
     repository: Arc<dyn BuildRepository>,
     event_publisher: Arc<dyn EventPublisher>,
+    commits: Option<Arc<dyn CommitRepository>>,
 }
 
 impl BuildService {
@@ -26,9 +36,26 @@ impl BuildService {
         Self {
             repository,
             event_publisher,
+            commits: None,
         }
     }
     
+    /// Read commit details of new builds from project repositories
+    pub fn with_commit_repository(mut self, commits: Arc<dyn CommitRepository>) -> Self {
+        self.commits = Some(commits);
+        self
+    }
+    
+    /// Resolve a branch of a project to its head commit SHA
+    ///
+    /// Only branches are resolved, even when a tag has the same name.
+    pub async fn resolve_branch(&self, project_id: &ProjectId, branch: &str) -> crate::Result<String> {
+        let commits = self.commits.as_ref().ok_or_else(|| {
+            crate::Error::validation("A commit SHA is required to build without repository access")
+        })?;
+        Ok(commits.resolve(project_id, &format!("refs/heads/{branch}")).await?.sha)
+    }
+    
     /// Create a new build
     pub async fn create_build(
         &self,
@@ -42,7 +69,7 @@ impl BuildService {
         let number = self.repository.next_build_number(&pipeline_id).await?;
         
         // Create build
-        let mut build = Build::new(
+        let build = Build::new(
             pipeline_id,
             project_id,
             number,
@@ -51,6 +78,42 @@ impl BuildService {
             trigger,
         );
         
+        self.save_new_build(build).await
+    }
+    
+    /// Create a build of a pipeline for a source control event
+    pub async fn create_event_build(
+        &self,
+        pipeline: &Pipeline,
+        event: &ScmEvent,
+    ) -> crate::Result<Build> {
+        let number = self.repository.next_build_number(pipeline.id()).await?;
+        
+        let trigger = match &event.kind {
+            ScmEventKind::PullRequest { number, .. } => BuildTrigger::PullRequest { pr_number: *number },
+            _ if event.source == POLL_SOURCE => BuildTrigger::Poll,
+            ScmEventKind::Push { .. } | ScmEventKind::Tag { .. } => BuildTrigger::Webhook {
+                source: event.source.clone(),
+            },
+        };
+        let mut build = Build::new(
+            pipeline.id().clone(),
+            pipeline.project_id().clone(),
+            number,
+            event.commit_sha.clone(),
+            event.ref_name().to_string(),
+            trigger,
+        );
+        build.set_tag(matches!(event.kind, ScmEventKind::Tag { .. }));
+        // Polling only sees commit IDs
+        if !event.commit_author.is_empty() {
+            build.set_commit_details(event.commit_message.clone(), event.commit_author.clone());
+        }
+        
+        self.save_new_build(build).await
+    }
+    
+    async fn save_new_build(&self, mut build: Build) -> crate::Result<Build> {
         // Save build
         self.repository.save(&build).await?;
         
@@ -58,9 +121,34 @@ impl BuildService {
         let events = build.take_events();
         self.event_publisher.publish_batch(events).await?;
         
+        // Reading commits can mean cloning the repository first, so it is left
+        // to the background instead of holding up webhooks and API requests.
+        // Missing commit details do not stop a build.
+        if let Some(commits) = &self.commits {
+            let repository = self.repository.clone();
+            let commits = commits.clone();
+            let build_id = build.id().clone();
+            tokio::spawn(async move {
+                if let Err(e) = describe_commits(repository.as_ref(), commits.as_ref(), &build_id).await {
+                    tracing::warn!("Could not read commits of build {}: {}", build_id, e);
+                }
+            });
+        }
+        
         Ok(build)
     }
     
+    /// Record the built commit of a build and the commits since the last
+    /// successful build of the same branch
+    ///
+    /// New builds are described in the background; this describes one now.
+    pub async fn describe_commits(&self, build_id: &BuildId) -> crate::Result<()> {
+        match &self.commits {
+            Some(commits) => describe_commits(self.repository.as_ref(), commits.as_ref(), build_id).await,
+            None => Ok(()),
+        }
+    }
+    
     /// Start a build
     pub async fn start_build(
         &self,
@@ -137,6 +225,28 @@ impl BuildService {
         Ok(())
     }
     
+    /// Retry a finished build as a new build of the same commit
+    pub async fn retry_build(&self, build_id: &BuildId) -> crate::Result<Build> {
+        let build = self.get_build(build_id).await?;
+        
+        if !build.status().is_terminal() {
+            return Err(crate::Error::conflict("Only finished builds can be retried"));
+        }
+        
+        let number = self.repository.next_build_number(build.pipeline_id()).await?;
+        let mut retried = Build::new(
+            build.pipeline_id().clone(),
+            build.project_id().clone(),
+            number,
+            build.commit_sha().to_string(),
+            build.branch().to_string(),
+            build.trigger().clone(),
+        );
+        retried.set_tag(build.is_tag());
+        
+        self.save_new_build(retried).await
+    }
+    
     /// Get a build by ID
     pub async fn get_build(&self, build_id: &BuildId) -> crate::Result<Build> {
         self.repository
@@ -150,9 +260,50 @@ impl BuildService {
         self.repository.find_by_pipeline(pipeline_id).await
     }
     
+    /// Find builds matching the query options
+    pub async fn list_builds(&self, options: BuildQueryOptions) -> crate::Result<Vec<Build>> {
+        self.repository.query(options).await
+    }
+    
     /// Get running builds
     pub async fn get_running_builds(&self) -> crate::Result<Vec<Build>> {
         self.repository.find_running().await
     }
 }
 
+/// Record the built commit of a build and the commits since the last
+/// successful build of the same branch
+async fn describe_commits(
+    repository: &dyn BuildRepository,
+    commits: &dyn CommitRepository,
+    build_id: &BuildId,
+) -> crate::Result<()> {
+    let build = repository
+        .find_by_id(build_id)
+        .await?
+        .ok_or_else(|| crate::Error::not_found("Build not found"))?;
+    
+    let commit = commits.resolve(build.project_id(), build.commit_sha()).await?;
+    let last_success = repository.query(BuildQueryOptions {
+        pipeline_id: Some(build.pipeline_id().clone()),
+        status: Some(BuildStatus::Success),
+        branch: Some(build.branch().to_string()),
+        limit: Some(1),
+        sort_by: Some("created_at".to_string()),
+        sort_desc: true,
+        ..Default::default()
+    }).await?;
+    let since = last_success.first().map(Build::commit_sha);
+    let changes = commits
+        .history(build.project_id(), &commit.sha, since, MAX_CHANGES)
+        .await?;
+    
+    // The build may have moved on while its commits were read
+    let mut build = repository
+        .find_by_id(build_id)
+        .await?
+        .ok_or_else(|| crate::Error::not_found("Build not found"))?;
+    build.set_commit(&commit);
+    build.set_changes(changes);
+    repository.update(&build).await
+}
@@ -0,0 +1,334 @@
+//! Event log domain service
+
+use crate::domain::events::{DomainEvent, EventHandler};
+use crate::domain::repositories::{
+    build::BuildRepository,
+    event::{EventQueryOptions, EventRepository, StoredEvent},
+    pipeline::PipelineRepository,
+};
+use crate::domain::value_objects::project_id::ProjectId;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Number of events replayed at once when no limit is given
+pub const DEFAULT_REPLAY_LIMIT: usize = 1000;
+
+/// Largest number of events replayed at once
+pub const MAX_REPLAY_LIMIT: usize = 10_000;
+
+/// How far a replay got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayProgress {
+    /// Number of events delivered to the handler
+    pub delivered: usize,
+    /// Sequence number of the last event replayed, delivered or skipped;
+    /// `None` when no event was left to replay
+    pub last_sequence: Option<u64>,
+}
+
+/// Get the number of events to replay at once for a requested limit
+pub fn replay_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_REPLAY_LIMIT).clamp(1, MAX_REPLAY_LIMIT)
+}
+
+/// Event log service recording every published event
+///
+/// Subscribe it to the event bus to record events; its handler is interested in
+/// every event type.
+pub struct EventLogService {
+    repository: Arc<dyn EventRepository>,
+    projects: Option<ProjectLookup>,
+}
+
+/// Repositories finding the project of events that do not carry it
+struct ProjectLookup {
+    pipelines: Arc<dyn PipelineRepository>,
+    builds: Arc<dyn BuildRepository>,
+}
+
+impl EventLogService {
+    /// Create a new event log service
+    pub fn new(repository: Arc<dyn EventRepository>) -> Self {
+        Self {
+            repository,
+            projects: None,
+        }
+    }
+
+    /// Record the project of build and pipeline events, looking it up from
+    /// their build or pipeline
+    pub fn with_project_lookup(
+        mut self,
+        pipelines: Arc<dyn PipelineRepository>,
+        builds: Arc<dyn BuildRepository>,
+    ) -> Self {
+        self.projects = Some(ProjectLookup { pipelines, builds });
+        self
+    }
+
+    /// Record an event under the project it concerns
+    pub async fn record(&self, event: &DomainEvent) -> crate::Result<StoredEvent> {
+        let project_id = match self.project_of(event).await {
+            Ok(project_id) => project_id,
+            Err(e) => {
+                // Losing the event would be worse than losing its project
+                tracing::warn!("Could not find the project of {} event: {}", event.event_type(), e);
+                None
+            }
+        };
+        self.repository.append(event, project_id).await
+    }
+
+    /// Find the project an event concerns
+    async fn project_of(&self, event: &DomainEvent) -> crate::Result<Option<ProjectId>> {
+        if let Some(project_id) = event.project_id() {
+            return Ok(Some(project_id.clone()));
+        }
+        let Some(projects) = &self.projects else {
+            return Ok(None);
+        };
+
+        Ok(match event {
+            DomainEvent::BuildStarted { build_id, .. }
+            | DomainEvent::BuildCompleted { build_id, .. }
+            | DomainEvent::BuildCancelled { build_id, .. } => projects
+                .builds
+                .find_by_id(build_id)
+                .await?
+                .map(|build| build.project_id().clone()),
+            DomainEvent::PipelineConfigUpdated { pipeline_id, .. }
+            | DomainEvent::PipelineEnabled { pipeline_id, .. }
+            | DomainEvent::PipelineDisabled { pipeline_id, .. } => projects
+                .pipelines
+                .find_by_id(pipeline_id)
+                .await?
+                .map(|pipeline| pipeline.project_id().clone()),
+            _ => None,
+        })
+    }
+
+    /// List recorded events
+    pub async fn list_events(&self, options: EventQueryOptions) -> crate::Result<Vec<StoredEvent>> {
+        self.repository.query(options).await
+    }
+
+    /// Re-deliver a page of recorded events to a handler in log order
+    ///
+    /// Only events the handler is interested in are delivered. Use this to rebuild a
+    /// read model from scratch or to catch a handler up from a point in time. At most
+    /// `limit` events are replayed, [`DEFAULT_REPLAY_LIMIT`] when unset; continue with
+    /// `after_sequence` set to the returned `last_sequence` until it is `None`. Stops at
+    /// the first handler error, which names the sequence to resume after.
+    pub async fn replay(
+        &self,
+        options: EventQueryOptions,
+        handler: &dyn EventHandler,
+    ) -> crate::Result<ReplayProgress> {
+        let interests = handler.interested_in();
+        let options = EventQueryOptions {
+            limit: Some(replay_limit(options.limit)),
+            ..options
+        };
+        let events = self.repository.query(options).await?;
+        let mut progress = ReplayProgress::default();
+
+        for stored in events {
+            if interests.iter().any(|i| *i == "*" || *i == stored.event_type) {
+                if let Err(e) = handler.handle(&stored.event).await {
+                    let resume = progress
+                        .last_sequence
+                        .map_or_else(|| "the start".to_string(), |sequence| format!("sequence {sequence}"));
+                    return Err(e.context(format!(
+                        "Replay stopped at event {} after delivering {} event(s); resume after {resume}",
+                        stored.sequence, progress.delivered
+                    )));
+                }
+                progress.delivered += 1;
+            }
+            progress.last_sequence = Some(stored.sequence);
+        }
+
+        Ok(progress)
+    }
+}
+
+#[async_trait]
+impl EventHandler for EventLogService {
+    async fn handle(&self, event: &DomainEvent) -> crate::Result<()> {
+        self.record(event).await?;
+        Ok(())
+    }
+
+    fn interested_in(&self) -> Vec<&str> {
+        vec!["*"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::in_memory::{
+        InMemoryBuildRepository,
+        InMemoryEventRepository,
+        InMemoryPipelineRepository,
+    };
+    use crate::domain::entities::{build::{Build, BuildTrigger}, pipeline::Pipeline};
+    use crate::domain::value_objects::{
+        build_id::BuildId,
+        build_status::BuildStatus,
+        pipeline_config::PipelineConfig,
+        pipeline_id::PipelineId,
+    };
+    use chrono::{Duration, Utc};
+    use tokio::sync::Mutex;
+
+    struct BuildCounter {
+        completed: Mutex<u32>,
+    }
+
+    #[async_trait]
+    impl EventHandler for BuildCounter {
+        async fn handle(&self, _event: &DomainEvent) -> crate::Result<()> {
+            *self.completed.lock().await += 1;
+            Ok(())
+        }
+
+        fn interested_in(&self) -> Vec<&str> {
+            vec!["build.completed"]
+        }
+    }
+
+    struct FailingHandler;
+
+    #[async_trait]
+    impl EventHandler for FailingHandler {
+        async fn handle(&self, _event: &DomainEvent) -> crate::Result<()> {
+            Err(crate::Error::internal("read model unavailable"))
+        }
+
+        fn interested_in(&self) -> Vec<&str> {
+            vec!["build.cancelled"]
+        }
+    }
+
+    fn create_service() -> EventLogService {
+        EventLogService::new(Arc::new(InMemoryEventRepository::new()))
+    }
+
+    #[tokio::test]
+    async fn test_record_and_filter() {
+        let service = create_service();
+        let build_id = BuildId::new();
+
+        service.record(&DomainEvent::BuildCancelled {
+            build_id: build_id.clone(),
+            cancelled_at: Utc::now(),
+        }).await.unwrap();
+        service.record(&DomainEvent::PipelineEnabled {
+            pipeline_id: PipelineId::new(),
+            enabled_at: Utc::now(),
+        }).await.unwrap();
+
+        let events = service.list_events(EventQueryOptions {
+            aggregate_type: Some("build".to_string()),
+            aggregate_id: Some(build_id.to_string()),
+            ..Default::default()
+        }).await.unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].sequence, 1);
+        assert_eq!(events[0].event_type, "build.cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_project() {
+        let pipelines = Arc::new(InMemoryPipelineRepository::new());
+        let builds = Arc::new(InMemoryBuildRepository::new());
+        let service = create_service().with_project_lookup(pipelines.clone(), builds.clone());
+
+        let project_id = ProjectId::new();
+        let pipeline = Pipeline::new(project_id.clone(), "ci".to_string(), PipelineConfig::new(vec![], vec![]));
+        pipelines.save(&pipeline).await.unwrap();
+        let build = Build::new(
+            pipeline.id().clone(),
+            project_id.clone(),
+            1,
+            "a".repeat(40),
+            "main".to_string(),
+            BuildTrigger::Push,
+        );
+        builds.save(&build).await.unwrap();
+
+        service.record(&DomainEvent::PipelineEnabled {
+            pipeline_id: pipeline.id().clone(),
+            enabled_at: Utc::now(),
+        }).await.unwrap();
+        service.record(&DomainEvent::BuildCompleted {
+            build_id: build.id().clone(),
+            status: BuildStatus::Success,
+            completed_at: Utc::now(),
+        }).await.unwrap();
+        // Events of unknown builds are still recorded, without a project
+        let unknown = service.record(&DomainEvent::BuildCancelled {
+            build_id: BuildId::new(),
+            cancelled_at: Utc::now(),
+        }).await.unwrap();
+        assert_eq!(unknown.project_id, None);
+
+        let events = service.list_events(EventQueryOptions {
+            project_id: Some(project_id),
+            ..Default::default()
+        }).await.unwrap();
+        let types: Vec<_> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, ["pipeline.enabled", "build.completed"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_from_point_in_time() {
+        let service = create_service();
+        let now = Utc::now();
+
+        for offset in [3, 2, 1] {
+            service.record(&DomainEvent::BuildCompleted {
+                build_id: BuildId::new(),
+                status: BuildStatus::Success,
+                completed_at: now - Duration::hours(offset),
+            }).await.unwrap();
+        }
+        service.record(&DomainEvent::BuildCancelled {
+            build_id: BuildId::new(),
+            cancelled_at: now,
+        }).await.unwrap();
+
+        let counter = BuildCounter { completed: Mutex::new(0) };
+        let progress = service.replay(
+            EventQueryOptions {
+                since: Some(now - Duration::minutes(150)),
+                ..Default::default()
+            },
+            &counter,
+        ).await.unwrap();
+
+        assert_eq!(progress, ReplayProgress { delivered: 2, last_sequence: Some(4) });
+        assert_eq!(*counter.completed.lock().await, 2);
+
+        // Replays go page by page
+        let counter = BuildCounter { completed: Mutex::new(0) };
+        let mut options = EventQueryOptions { limit: Some(2), ..Default::default() };
+        let first = service.replay(options.clone(), &counter).await.unwrap();
+        assert_eq!(first, ReplayProgress { delivered: 2, last_sequence: Some(2) });
+        options.after_sequence = first.last_sequence;
+        let second = service.replay(options.clone(), &counter).await.unwrap();
+        assert_eq!(second, ReplayProgress { delivered: 1, last_sequence: Some(4) });
+        options.after_sequence = second.last_sequence;
+        assert_eq!(service.replay(options, &counter).await.unwrap(), ReplayProgress::default());
+        assert_eq!(*counter.completed.lock().await, 3);
+
+        // A failing handler reports where to resume
+        let error = service.replay(EventQueryOptions::default(), &FailingHandler).await.unwrap_err();
+        assert!(
+            error.to_string().contains("Replay stopped at event 4 after delivering 0 event(s); resume after sequence 3"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Masking of secret values in job logs
+//!
+//! Every secret is masked in its raw form, base64 (standard and URL-safe
+//! alphabets, at any alignment within larger encoded output) and URL-encoded
+//! forms, and multi-line secrets line by line. All forms are matched in a
+//! single pass with Aho-Corasick. Because a value can be
+//! split across chunks, the masker holds back the last `longest - 1` bytes of
+//! the stream until more output or the end of the log arrives.
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+
+/// Replacement for masked values
+pub const MASK: &str = "***";
+
+/// Shortest secret line or shifted base64 encoding that is masked on its own
+const MIN_MASKED_LEN: usize = 4;
+
+/// Characters left alone by URL encoding (RFC 3986 unreserved characters)
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Streaming filter replacing secret values in log output with `***`
+pub struct LogMasker {
+    matcher: Option<AhoCorasick>,
+    longest: usize,
+    pending: String,
+}
+
+impl LogMasker {
+    /// Create a masker for a set of secret values
+    pub fn new<S: AsRef<str>>(secrets: &[S]) -> Self {
+        let mut patterns: Vec<String> = secrets
+            .iter()
+            .flat_map(|secret| masked_forms(secret.as_ref()))
+            .collect();
+        patterns.sort();
+        patterns.dedup();
+
+        let longest = patterns.iter().map(String::len).max().unwrap_or(0);
+        let matcher = if patterns.is_empty() {
+            None
+        } else {
+            Some(
+                AhoCorasick::builder()
+                    .match_kind(MatchKind::LeftmostLongest)
+                    .build(&patterns)
+                    .expect("secret patterns are valid"),
+            )
+        };
+
+        Self {
+            matcher,
+            longest,
+            pending: String::new(),
+        }
+    }
+
+    /// Mask a chunk of output, returning the text that is safe to store
+    ///
+    /// The returned text may lag behind the input; call `finish` at the end of
+    /// the stream to get the rest.
+    pub fn mask(&mut self, chunk: &str) -> String {
+        let Some(matcher) = &self.matcher else {
+            return chunk.to_string();
+        };
+
+        self.pending.push_str(chunk);
+        let buffer = std::mem::take(&mut self.pending);
+
+        // A match starting before `safe` fits completely in the buffer, so more
+        // output cannot change it
+        let mut safe = buffer.len().saturating_sub(self.longest - 1);
+        while !buffer.is_char_boundary(safe) {
+            safe -= 1;
+        }
+
+        let mut masked = String::with_capacity(buffer.len());
+        let mut position = 0;
+        for found in matcher.find_iter(&buffer) {
+            if found.start() >= safe {
+                break;
+            }
+            masked.push_str(&buffer[position..found.start()]);
+            masked.push_str(MASK);
+            position = found.end();
+        }
+
+        let end = position.max(safe);
+        masked.push_str(&buffer[position..end]);
+        self.pending = buffer[end..].to_string();
+
+        masked
+    }
+
+    /// Mask and return whatever output is still held back
+    pub fn finish(&mut self) -> String {
+        let buffer = std::mem::take(&mut self.pending);
+        match &self.matcher {
+            Some(matcher) => matcher.replace_all(&buffer, &vec![MASK; matcher.patterns_len()]),
+            None => buffer,
+        }
+    }
+}
+
+/// Mask a complete text in one go
+pub fn mask_all<S: AsRef<str>>(secrets: &[S], text: &str) -> String {
+    let mut masker = LogMasker::new(secrets);
+    let mut output = masker.mask(text);
+    output.push_str(&masker.finish());
+    output
+}
+
+fn masked_forms(secret: &str) -> Vec<String> {
+    let mut values = vec![secret.to_string()];
+    if secret.contains('\n') {
+        values.extend(
+            secret
+                .lines()
+                .map(str::trim)
+                // Lines such as `{` would mask unrelated output
+                .filter(|line| line.len() >= MIN_MASKED_LEN && line.chars().any(char::is_alphanumeric))
+                .map(str::to_string),
+        );
+    }
+
+    let mut forms = Vec::new();
+    for value in values.iter().filter(|v| !v.is_empty()) {
+        let url_encoded = utf8_percent_encode(value, URL_ENCODE_SET).to_string();
+        forms.push(url_encoded.replace("%20", "+"));
+        forms.push(url_encoded);
+        forms.extend(base64_forms(&STANDARD_NO_PAD, value));
+        forms.extend(base64_forms(&URL_SAFE_NO_PAD, value));
+        forms.push(value.clone());
+    }
+    forms
+}
+
+/// Base64 encodings of a value, standalone and inside larger encoded output
+///
+/// Inside a larger text the value can start at any of the three byte
+/// alignments. Characters that also encode bits of the surrounding bytes are
+/// dropped from both ends of those forms.
+fn base64_forms(engine: &impl Engine, value: &str) -> Vec<String> {
+    let mut forms = vec![engine.encode(value)];
+    for (offset, partial) in [(0, 0), (1, 2), (2, 3)] {
+        let mut bytes = vec![0; offset];
+        bytes.extend_from_slice(value.as_bytes());
+        let encoded = engine.encode(&bytes);
+        let end = if bytes.len() % 3 == 0 { encoded.len() } else { encoded.len() - 1 };
+        if end >= partial + MIN_MASKED_LEN {
+            forms.push(encoded[partial..end].to_string());
+        }
+    }
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_masks_all_forms() {
+        let secret = "p@ss word/+?";
+        let log = format!(
+            "raw={secret} b64={} url={} form={}\n",
+            STANDARD_NO_PAD.encode(secret),
+            utf8_percent_encode(secret, URL_ENCODE_SET),
+            utf8_percent_encode(secret, URL_ENCODE_SET).to_string().replace("%20", "+"),
+        );
+
+        assert_eq!(mask_all(&[secret], &log), "raw=*** b64=*** url=*** form=***\n");
+    }
+
+    #[test]
+    fn test_masks_values_split_across_chunks() {
+        let mut masker = LogMasker::new(&["hunter2"]);
+
+        let mut output = masker.mask("password: hun");
+        output.push_str(&masker.mask("ter"));
+        output.push_str(&masker.mask("2\ndone hunt"));
+        output.push_str(&masker.finish());
+
+        assert_eq!(output, "password: ***\ndone hunt");
+    }
+
+    #[test]
+    fn test_prefers_longest_secret() {
+        let masker_output = mask_all(&["abc", "abcdef"], "xabcdefx abcx");
+        assert_eq!(masker_output, "x***x ***x");
+    }
+
+    #[test]
+    fn test_masks_multiline_secrets_by_line() {
+        let key = "-----BEGIN KEY-----\nMIIEvQIBADANBgkq\n-----END KEY-----";
+        assert_eq!(mask_all(&[key], "line: MIIEvQIBADANBgkq\n"), "line: ***\n");
+    }
+
+    #[test]
+    fn test_masks_secrets_inside_base64_output() {
+        let secret = "ghp_token123";
+        for prefix in ["", "u", "user:"] {
+            for suffix in ["", "!", "\nmore"] {
+                let encoded = STANDARD_NO_PAD.encode(format!("{prefix}{secret}{suffix}"));
+                assert!(mask_all(&[secret], &encoded).contains(MASK), "{prefix}{secret}{suffix}");
+            }
+        }
+
+        let unrelated = STANDARD_NO_PAD.encode("user:other-token");
+        assert_eq!(mask_all(&[secret], &unrelated), unrelated);
+    }
+
+    #[test]
+    fn test_skips_trivial_lines_of_multiline_secrets() {
+        let config = "{\n  \"key\": \"s3cr3t-value\"\n}\n";
+        let log = "{\n\n  \"key\": \"s3cr3t-value\"\n}\n";
+        assert_eq!(mask_all(&[config], log), "{\n\n  ***\n}\n");
+    }
+
+    #[test]
+    fn test_without_secrets() {
+        let mut masker = LogMasker::new::<&str>(&[]);
+        assert_eq!(masker.mask("unchanged"), "unchanged");
+        assert_eq!(masker.finish(), "");
+    }
+
+    proptest! {
+        #[test]
+        fn chunking_does_not_change_output(
+            text in "[a-zé ]{0,60}(hunter2|pässwörd)?[a-z ]{0,20}",
+            splits in proptest::collection::vec(0usize..80, 0..6),
+        ) {
+            let secrets = ["hunter2", "pässwörd"];
+            let mut masker = LogMasker::new(&secrets);
+            let mut output = String::new();
+            let mut rest = text.as_str();
+            for split in splits {
+                let mut at = split.min(rest.len());
+                while !rest.is_char_boundary(at) {
+                    at -= 1;
+                }
+                let (chunk, tail) = rest.split_at(at);
+                output.push_str(&masker.mask(chunk));
+                rest = tail;
+            }
+            output.push_str(&masker.mask(rest));
+            output.push_str(&masker.finish());
+
+            prop_assert_eq!(output, mask_all(&secrets, &text));
+            prop_assert!(!mask_all(&secrets, &text).contains("hunter2"));
+        }
+    }
+}
@@ -3,4 +3,12 @@
 pub mod pipeline;
 pub mod build;
 pub mod agent;
+pub mod project;
+pub mod auth;
+pub mod authorization;
+pub mod api_token;
+pub mod secret;
+pub mod log_masking;
+pub mod webhook;
+pub mod event_log;
 
@@ -137,6 +137,11 @@ impl PipelineService {
             .ok_or_else(|| crate::Error::not_found("Pipeline not found"))
     }
     
+    /// Get all pipelines
+    pub async fn list_pipelines(&self) -> crate::Result<Vec<Pipeline>> {
+        self.repository.find_all().await
+    }
+    
     /// Get all pipelines for a project
     pub async fn get_project_pipelines(&self, project_id: &ProjectId) -> crate::Result<Vec<Pipeline>> {
         self.repository.find_by_project(project_id).await
@@ -0,0 +1,193 @@
+//! Project domain service
+
+use crate::domain::entities::project::{Project, ProjectVisibility};
+use crate::domain::value_objects::project_id::ProjectId;
+use crate::domain::repositories::project::ProjectRepository;
+use crate::domain::events::EventPublisher;
+use std::sync::Arc;
+
+/// Project service
+pub struct ProjectService {
+    repository: Arc<dyn ProjectRepository>,
+    event_publisher: Arc<dyn EventPublisher>,
+}
+
+impl ProjectService {
+    /// Create a new project service
+    pub fn new(
+        repository: Arc<dyn ProjectRepository>,
+        event_publisher: Arc<dyn EventPublisher>,
+    ) -> Self {
+        Self {
+            repository,
+            event_publisher,
+        }
+    }
+    
+    /// Create a new project
+    pub async fn create_project(
+        &self,
+        name: String,
+        repository_url: String,
+        default_branch: String,
+        description: Option<String>,
+        visibility: Option<ProjectVisibility>,
+    ) -> crate::Result<Project> {
+        // Check if project name already exists
+        if self.repository.name_exists(&name).await? {
+            return Err(crate::Error::conflict("Project name already exists"));
+        }
+        
+        // Create project
+        let mut project = Project::new(name, repository_url, default_branch);
+        project.set_description(description);
+        if let Some(visibility) = visibility {
+            project.set_visibility(visibility);
+        }
+        
+        // Validate project
+        project.validate()?;
+        
+        // Save project
+        self.repository.save(&project).await?;
+        
+        // Publish events
+        let events = project.take_events();
+        self.event_publisher.publish_batch(events).await?;
+        
+        Ok(project)
+    }
+    
+    /// Update a project; fields left as `None` are unchanged
+    pub async fn update_project(
+        &self,
+        project_id: &ProjectId,
+        name: Option<String>,
+        repository_url: Option<String>,
+        default_branch: Option<String>,
+        description: Option<String>,
+        visibility: Option<ProjectVisibility>,
+    ) -> crate::Result<Project> {
+        let mut project = self.get_project(project_id).await?;
+        
+        if let Some(name) = name {
+            if name != project.name() && self.repository.name_exists(&name).await? {
+                return Err(crate::Error::conflict("Project name already exists"));
+            }
+            project.rename(name);
+        }
+        if let Some(repository_url) = repository_url {
+            project.set_repository_url(repository_url);
+        }
+        if let Some(default_branch) = default_branch {
+            project.set_default_branch(default_branch);
+        }
+        if description.is_some() {
+            project.set_description(description);
+        }
+        if let Some(visibility) = visibility {
+            project.set_visibility(visibility);
+        }
+        
+        project.validate()?;
+        
+        self.repository.update(&project).await?;
+        
+        Ok(project)
+    }
+    
+    /// Delete a project
+    pub async fn delete_project(&self, project_id: &ProjectId) -> crate::Result<()> {
+        // Check if project exists
+        if !self.repository.exists(project_id).await? {
+            return Err(crate::Error::not_found("Project not found"));
+        }
+        
+        // Delete project
+        self.repository.delete(project_id).await?;
+        
+        Ok(())
+    }
+    
+    /// Get a project by ID
+    pub async fn get_project(&self, project_id: &ProjectId) -> crate::Result<Project> {
+        self.repository
+            .find_by_id(project_id)
+            .await?
+            .ok_or_else(|| crate::Error::not_found("Project not found"))
+    }
+    
+    /// Get all projects
+    pub async fn list_projects(&self) -> crate::Result<Vec<Project>> {
+        self.repository.find_all().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::events::InMemoryEventPublisher;
+    use crate::infrastructure::repositories::in_memory::InMemoryProjectRepository;
+
+    fn create_service() -> (ProjectService, Arc<InMemoryEventPublisher>) {
+        let event_publisher = Arc::new(InMemoryEventPublisher::new());
+        let service = ProjectService::new(
+            Arc::new(InMemoryProjectRepository::new()),
+            event_publisher.clone(),
+        );
+        (service, event_publisher)
+    }
+
+    async fn create_project(service: &ProjectService, name: &str) -> crate::Result<Project> {
+        service.create_project(
+            name.to_string(),
+            "https://github.com/user/repo.git".to_string(),
+            "main".to_string(),
+            None,
+            None,
+        ).await
+    }
+
+    #[tokio::test]
+    async fn test_create_project() {
+        let (service, event_publisher) = create_service();
+        
+        let project = create_project(&service, "test-project").await.unwrap();
+        
+        assert_eq!(service.get_project(project.id()).await.unwrap().name(), "test-project");
+        assert_eq!(event_publisher.get_events().await.len(), 1);
+        assert!(matches!(
+            create_project(&service, "test-project").await,
+            Err(crate::Error::Conflict(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_project() {
+        let (service, _) = create_service();
+        let project = create_project(&service, "first").await.unwrap();
+        create_project(&service, "second").await.unwrap();
+        
+        let updated = service.update_project(
+            project.id(),
+            None,
+            None,
+            Some("develop".to_string()),
+            Some("Renamed".to_string()),
+            Some(ProjectVisibility::Internal),
+        ).await.unwrap();
+        assert_eq!(updated.default_branch(), "develop");
+        assert_eq!(updated.description(), Some("Renamed"));
+        assert_eq!(updated.visibility(), &ProjectVisibility::Internal);
+        
+        let renamed = service.update_project(
+            project.id(),
+            Some("second".to_string()),
+            None,
+            None,
+            None,
+            None,
+        ).await;
+        assert!(matches!(renamed, Err(crate::Error::Conflict(_))));
+    }
+}
@@ -0,0 +1,405 @@
+//! Secret domain service
+//!
+//! Secrets are encrypted with AES-256-GCM under a key derived with HKDF-SHA256
+//! from `security.secrets_key` (or the JWT secret). The scope and name of a
+//! secret are bound to its ciphertext as associated data, so a stored value
+//! cannot be moved to another secret. Decrypted values only ever leave this
+//! service inside a `JobEnvironment`.
+
+use crate::config::SecurityConfig;
+use crate::domain::entities::{
+    pipeline::Pipeline,
+    secret::{Secret, SecretScope},
+};
+use crate::domain::repositories::secret::SecretRepository;
+use crate::domain::services::log_masking::LogMasker;
+use crate::domain::value_objects::pipeline_config::{self, expand_expressions, secret_reference};
+use crate::domain::value_objects::project_id::ProjectId;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+const NONCE_LEN: usize = 12;
+const KEY_INFO: &[u8] = b"ferrous-ci secrets v1";
+const WEBHOOK_SECRET_NAME: &str = "WEBHOOK_SECRET";
+const REPOSITORY_TOKEN_NAME: &str = "HTTPS_TOKEN";
+
+/// Environment of a job at execution time
+///
+/// Contains decrypted secret values, so it cannot be serialized and its debug
+/// output hides variable values.
+pub struct JobEnvironment {
+    variables: HashMap<String, String>,
+    secret_values: Vec<String>,
+}
+
+impl JobEnvironment {
+    /// Get the environment variables
+    pub fn variables(&self) -> &HashMap<String, String> {
+        &self.variables
+    }
+
+    /// Get the values of the secrets referenced by the environment
+    pub fn secret_values(&self) -> &[String] {
+        &self.secret_values
+    }
+
+    /// Create a masker hiding the secret values in the job's logs
+    pub fn log_masker(&self) -> LogMasker {
+        LogMasker::new(&self.secret_values)
+    }
+
+    /// Take the environment variables
+    pub fn into_variables(self) -> HashMap<String, String> {
+        self.variables
+    }
+}
+
+impl fmt::Debug for JobEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.variables.keys().collect();
+        names.sort();
+
+        f.debug_struct("JobEnvironment")
+            .field("variables", &names)
+            .field("secrets", &self.secret_values.len())
+            .finish()
+    }
+}
+
+/// Secret service
+pub struct SecretService {
+    repository: Arc<dyn SecretRepository>,
+    cipher: Aes256Gcm,
+}
+
+impl SecretService {
+    /// Create a new secret service
+    pub fn new(repository: Arc<dyn SecretRepository>, config: &SecurityConfig) -> Self {
+        let key_material = config.secrets_key.as_deref().unwrap_or(&config.jwt_secret);
+
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, key_material.as_bytes())
+            .expand(KEY_INFO, &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+
+        Self {
+            repository,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    /// Create a secret or replace its value
+    pub async fn set_secret(
+        &self,
+        scope: SecretScope,
+        name: String,
+        value: &str,
+    ) -> crate::Result<Secret> {
+        let encrypted = self.encrypt(&scope, &name, value)?;
+
+        let secret = match self.repository.find(&scope, &name).await? {
+            Some(mut secret) => {
+                secret.set_encrypted_value(encrypted);
+                secret
+            }
+            None => Secret::new(scope, name, encrypted)?,
+        };
+
+        self.repository.save(&secret).await?;
+
+        Ok(secret)
+    }
+
+    /// Delete a secret
+    pub async fn delete_secret(&self, scope: &SecretScope, name: &str) -> crate::Result<()> {
+        if self.repository.find(scope, name).await?.is_none() {
+            return Err(crate::Error::not_found("Secret not found"));
+        }
+
+        self.repository.delete(scope, name).await
+    }
+
+    /// Delete all secrets of a scope
+    pub async fn delete_all(&self, scope: &SecretScope) -> crate::Result<()> {
+        self.repository.delete_by_scope(scope).await
+    }
+
+    /// List the secrets of a scope, without their values
+    pub async fn list_secrets(&self, scope: &SecretScope) -> crate::Result<Vec<Secret>> {
+        self.repository.find_by_scope(scope).await
+    }
+
+    /// Set the secret used to verify a project's incoming webhooks
+    pub async fn set_webhook_secret(&self, project_id: &ProjectId, value: &str) -> crate::Result<()> {
+        if value.is_empty() {
+            return Err(crate::Error::validation("Webhook secret cannot be empty"));
+        }
+
+        let scope = SecretScope::Webhook(project_id.clone());
+        self.set_secret(scope, WEBHOOK_SECRET_NAME.to_string(), value).await?;
+        Ok(())
+    }
+
+    /// Get the secret used to verify a project's incoming webhooks
+    pub async fn webhook_secret(&self, project_id: &ProjectId) -> crate::Result<Option<String>> {
+        let scope = SecretScope::Webhook(project_id.clone());
+        match self.repository.find(&scope, WEBHOOK_SECRET_NAME).await? {
+            Some(secret) => Ok(Some(self.decrypt(&secret)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Set the token authenticating HTTPS fetches of a project's repository
+    pub async fn set_repository_token(&self, project_id: &ProjectId, value: &str) -> crate::Result<()> {
+        if value.is_empty() {
+            return Err(crate::Error::validation("Repository token cannot be empty"));
+        }
+
+        let scope = SecretScope::Repository(project_id.clone());
+        self.set_secret(scope, REPOSITORY_TOKEN_NAME.to_string(), value).await?;
+        Ok(())
+    }
+
+    /// Remove the token of a project's repository
+    pub async fn delete_repository_token(&self, project_id: &ProjectId) -> crate::Result<()> {
+        self.delete_secret(&SecretScope::Repository(project_id.clone()), REPOSITORY_TOKEN_NAME).await
+    }
+
+    /// Get the token authenticating HTTPS fetches of a project's repository
+    pub async fn repository_token(&self, project_id: &ProjectId) -> crate::Result<Option<String>> {
+        let scope = SecretScope::Repository(project_id.clone());
+        match self.repository.find(&scope, REPOSITORY_TOKEN_NAME).await? {
+            Some(secret) => Ok(Some(self.decrypt(&secret)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Build the environment of a job, expanding secret references
+    ///
+    /// Variables are taken from the pipeline, the pipeline configuration and the
+    /// job, later ones overriding earlier ones. Pipeline secrets override project
+    /// secrets with the same name. Referencing an unknown secret is an error.
+    pub async fn job_environment(
+        &self,
+        pipeline: &Pipeline,
+        job: &pipeline_config::Job,
+    ) -> crate::Result<JobEnvironment> {
+        let mut variables = pipeline.environment().clone();
+        variables.extend(pipeline.config().environment.clone());
+        variables.extend(job.environment.clone());
+
+        // Find the referenced secrets first so nothing is decrypted needlessly
+        let mut referenced = Vec::new();
+        for value in variables.values() {
+            expand_expressions(value, |expression| {
+                if let Some(name) = secret_reference(expression) {
+                    referenced.push(name.to_string());
+                }
+                Ok(None)
+            })?;
+        }
+        if referenced.is_empty() {
+            return Ok(JobEnvironment { variables, secret_values: Vec::new() });
+        }
+
+        let secrets = self.pipeline_secrets(pipeline).await?;
+        let mut secret_values = Vec::new();
+        for name in referenced {
+            let value = secrets
+                .get(&name)
+                .ok_or_else(|| crate::Error::validation(format!("Unknown secret: {name}")))?;
+            if !secret_values.contains(value) {
+                secret_values.push(value.clone());
+            }
+        }
+
+        for value in variables.values_mut() {
+            *value = expand_expressions(value, |expression| {
+                Ok(secret_reference(expression).and_then(|name| secrets.get(name).cloned()))
+            })?;
+        }
+
+        Ok(JobEnvironment { variables, secret_values })
+    }
+
+    async fn pipeline_secrets(&self, pipeline: &Pipeline) -> crate::Result<HashMap<String, String>> {
+        let mut values = HashMap::new();
+        for scope in [
+            SecretScope::Project(pipeline.project_id().clone()),
+            SecretScope::Pipeline(pipeline.id().clone()),
+        ] {
+            for secret in self.repository.find_by_scope(&scope).await? {
+                let value = self.decrypt(&secret)?;
+                values.insert(secret.name().to_string(), value);
+            }
+        }
+        Ok(values)
+    }
+
+    fn encrypt(&self, scope: &SecretScope, name: &str, value: &str) -> crate::Result<Vec<u8>> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let aad = associated_data(scope, name);
+
+        let ciphertext = self.cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: value.as_bytes(), aad: &aad })
+            .map_err(|_| crate::Error::internal("Failed to encrypt secret"))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(ciphertext);
+        Ok(encrypted)
+    }
+
+    fn decrypt(&self, secret: &Secret) -> crate::Result<String> {
+        let failed = || {
+            crate::Error::internal(format!(
+                "Failed to decrypt secret {}; has security.secrets_key changed?",
+                secret.name()
+            ))
+        };
+
+        let encrypted = secret.encrypted_value();
+        if encrypted.len() < NONCE_LEN {
+            return Err(failed());
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+        let aad = associated_data(secret.scope(), secret.name());
+
+        let plaintext = self.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: &aad })
+            .map_err(|_| failed())?;
+        String::from_utf8(plaintext).map_err(|_| failed())
+    }
+}
+
+fn associated_data(scope: &SecretScope, name: &str) -> Vec<u8> {
+    format!("{scope}/{name}").into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::domain::value_objects::pipeline_config::{PipelineConfig, Stage, Trigger};
+    use crate::infrastructure::repositories::in_memory::InMemorySecretRepository;
+
+    fn create_service(repository: Arc<InMemorySecretRepository>, key: &str) -> SecretService {
+        let mut config = Config::default().security;
+        config.secrets_key = Some(key.to_string());
+        SecretService::new(repository, &config)
+    }
+
+    fn create_pipeline(environment: &[(&str, &str)]) -> (Pipeline, pipeline_config::Job) {
+        let mut job = pipeline_config::Job::new("deploy".to_string());
+        job.add_command("./deploy.sh".to_string());
+        for (key, value) in environment {
+            job.environment.insert(key.to_string(), value.to_string());
+        }
+
+        let config = PipelineConfig::new(
+            vec![Stage::new("deploy".to_string(), vec![job.clone()])],
+            vec![Trigger::Manual],
+        );
+        (Pipeline::new(ProjectId::new(), "deploy".to_string(), config), job)
+    }
+
+    #[tokio::test]
+    async fn test_job_environment() {
+        let service = create_service(Arc::new(InMemorySecretRepository::new()), "test-key");
+        let (pipeline, job) = create_pipeline(&[
+            ("REGISTRY", "registry.example.com"),
+            ("REGISTRY_AUTH", "ci:${{ secrets.REGISTRY_PASSWORD }}"),
+            ("DEPLOY_KEY", "${{ secrets.DEPLOY_KEY }}"),
+        ]);
+
+        let project = SecretScope::Project(pipeline.project_id().clone());
+        service.set_secret(project.clone(), "REGISTRY_PASSWORD".to_string(), "hunter2").await.unwrap();
+        service.set_secret(project, "DEPLOY_KEY".to_string(), "project-key").await.unwrap();
+        service.set_secret(
+            SecretScope::Pipeline(pipeline.id().clone()),
+            "DEPLOY_KEY".to_string(),
+            "pipeline-key",
+        ).await.unwrap();
+
+        let environment = service.job_environment(&pipeline, &job).await.unwrap();
+        let variables = environment.variables();
+        assert_eq!(variables["REGISTRY"], "registry.example.com");
+        assert_eq!(variables["REGISTRY_AUTH"], "ci:hunter2");
+        assert_eq!(variables["DEPLOY_KEY"], "pipeline-key");
+        assert_eq!(environment.secret_values().len(), 2);
+        assert!(!format!("{environment:?}").contains("hunter2"));
+
+        let mut masker = environment.log_masker();
+        let mut logs = masker.mask("login ci:hunt");
+        logs.push_str(&masker.mask("er2 with pipeline-key\n"));
+        logs.push_str(&masker.finish());
+        assert_eq!(logs, "login ci:*** with ***\n");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_secret() {
+        let service = create_service(Arc::new(InMemorySecretRepository::new()), "test-key");
+        let (pipeline, job) = create_pipeline(&[("TOKEN", "${{ secrets.MISSING }}")]);
+
+        assert!(matches!(
+            service.job_environment(&pipeline, &job).await,
+            Err(crate::Error::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_secrets_are_encrypted() {
+        let repository = Arc::new(InMemorySecretRepository::new());
+        let service = create_service(repository.clone(), "test-key");
+        let (pipeline, job) = create_pipeline(&[("TOKEN", "${{ secrets.TOKEN }}")]);
+        let scope = SecretScope::Project(pipeline.project_id().clone());
+
+        let secret = service.set_secret(scope, "TOKEN".to_string(), "plaintext-value").await.unwrap();
+        let stored = String::from_utf8_lossy(secret.encrypted_value());
+        assert!(!stored.contains("plaintext-value"));
+
+        // A different key cannot decrypt the stored value
+        let other = create_service(repository, "other-key");
+        assert!(other.job_environment(&pipeline, &job).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_secret() {
+        let service = create_service(Arc::new(InMemorySecretRepository::new()), "test-key");
+        let (pipeline, job) = create_pipeline(&[("TOKEN", "${{ secrets.WEBHOOK_SECRET }}")]);
+        let project_id = pipeline.project_id();
+
+        assert!(service.webhook_secret(project_id).await.unwrap().is_none());
+        service.set_webhook_secret(project_id, "webhook-key").await.unwrap();
+        assert_eq!(service.webhook_secret(project_id).await.unwrap().as_deref(), Some("webhook-key"));
+
+        // Webhook secrets are not visible to jobs or in the project's secrets
+        assert!(service.job_environment(&pipeline, &job).await.is_err());
+        let project = SecretScope::Project(project_id.clone());
+        assert!(service.list_secrets(&project).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repository_token() {
+        let service = create_service(Arc::new(InMemorySecretRepository::new()), "test-key");
+        let (pipeline, job) = create_pipeline(&[("TOKEN", "${{ secrets.HTTPS_TOKEN }}")]);
+        let project_id = pipeline.project_id();
+
+        assert!(service.repository_token(project_id).await.unwrap().is_none());
+        assert!(service.set_repository_token(project_id, "").await.is_err());
+        service.set_repository_token(project_id, "ghp_token").await.unwrap();
+        assert_eq!(service.repository_token(project_id).await.unwrap().as_deref(), Some("ghp_token"));
+
+        // Repository tokens are not visible to jobs or in the project's secrets
+        assert!(service.job_environment(&pipeline, &job).await.is_err());
+        let project = SecretScope::Project(project_id.clone());
+        assert!(service.list_secrets(&project).await.unwrap().is_empty());
+
+        service.delete_repository_token(project_id).await.unwrap();
+        assert!(service.repository_token(project_id).await.unwrap().is_none());
+    }
+}
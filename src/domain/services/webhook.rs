@@ -0,0 +1,235 @@
+//! Webhook domain service
+//!
+//! Turns source control events into builds of the pipelines they trigger.
+//! Verifying and parsing forge payloads is left to the receivers.
+
+use crate::domain::entities::{build::Build, pipeline::Pipeline, project::Project};
+use crate::domain::repositories::commit::CommitRepository;
+use crate::domain::services::{build::BuildService, pipeline::PipelineService};
+use crate::domain::value_objects::pipeline_config::Trigger;
+use crate::domain::value_objects::scm_event::{ScmEvent, ScmEventKind};
+use std::sync::Arc;
+
+/// Webhook service
+pub struct WebhookService {
+    pipeline_service: Arc<PipelineService>,
+    build_service: Arc<BuildService>,
+    commits: Option<Arc<dyn CommitRepository>>,
+}
+
+impl WebhookService {
+    /// Create a new webhook service
+    pub fn new(pipeline_service: Arc<PipelineService>, build_service: Arc<BuildService>) -> Self {
+        Self {
+            pipeline_service,
+            build_service,
+            commits: None,
+        }
+    }
+
+    /// Find the files changed by events whose payload does not list them in
+    /// project repositories
+    pub fn with_commit_repository(mut self, commits: Arc<dyn CommitRepository>) -> Self {
+        self.commits = Some(commits);
+        self
+    }
+
+    /// Create a build for every enabled pipeline of the project the event triggers
+    pub async fn handle_event(&self, project: &Project, event: &ScmEvent) -> crate::Result<Vec<Build>> {
+        if matches!(event.kind, ScmEventKind::PullRequest { .. }) && !project.settings().pr_builds_enabled {
+            return Ok(Vec::new());
+        }
+
+        let mut pipelines = self.pipeline_service.get_project_pipelines(project.id()).await?;
+        pipelines.retain(Pipeline::is_enabled);
+
+        // Path filters need the changed files, which payloads of pull requests
+        // and large pushes leave out
+        let mut event = event.clone();
+        let filters_paths = pipelines
+            .iter()
+            .any(|pipeline| pipeline.config().triggers.iter().any(Trigger::filters_paths));
+        if event.changed_files.is_none() && filters_paths {
+            event.changed_files = self.find_changed_files(project, &event).await;
+        }
+
+        let mut builds = Vec::new();
+        for pipeline in pipelines {
+            if pipeline.config().triggers.iter().any(|trigger| trigger.matches(&event)) {
+                builds.push(self.build_service.create_event_build(&pipeline, &event).await?);
+            }
+        }
+
+        tracing::info!(
+            "{} {} event for {} triggered {} build(s)",
+            event.source,
+            event.kind,
+            project.name(),
+            builds.len()
+        );
+        Ok(builds)
+    }
+
+    /// Find the files an event changed in the project repository
+    ///
+    /// Returns `None` when they cannot be found, so path filters let the event
+    /// through rather than miss a build.
+    async fn find_changed_files(&self, project: &Project, event: &ScmEvent) -> Option<Vec<String>> {
+        let commits = self.commits.as_ref()?;
+        let result = match (&event.kind, &event.before_sha) {
+            (ScmEventKind::Push { .. }, Some(before)) => {
+                commits.changed_files(project.id(), before, &event.commit_sha).await
+            }
+            (ScmEventKind::PullRequest { target_branch, .. }, _) => {
+                commits
+                    .pull_request_changed_files(project.id(), target_branch, &event.commit_sha)
+                    .await
+            }
+            // New branches have nothing to compare with
+            _ => return None,
+        };
+
+        match result {
+            Ok(files) => Some(files),
+            Err(e) => {
+                tracing::warn!(
+                    "Could not find the files changed by {} {} event for {}: {}",
+                    event.source,
+                    event.kind,
+                    project.name(),
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EventsConfig;
+    use crate::domain::value_objects::{commit_info::CommitInfo, pipeline_config::{Job, PipelineConfig, Stage}, project_id::ProjectId};
+    use async_trait::async_trait;
+    use crate::infrastructure::events::EventBus;
+    use crate::infrastructure::repositories::in_memory::{
+        InMemoryBuildRepository,
+        InMemoryPipelineRepository,
+    };
+
+    fn push(branch: &str) -> ScmEvent {
+        ScmEvent {
+            source: "github".to_string(),
+            kind: ScmEventKind::Push { branch: branch.to_string() },
+            commit_sha: "abc123".to_string(),
+            commit_message: "Fix tests".to_string(),
+            commit_author: "octocat".to_string(),
+            before_sha: None,
+            changed_files: None,
+        }
+    }
+
+    /// Commit repository listing the same changed files for every event
+    struct ChangedFiles(Vec<&'static str>);
+
+    #[async_trait]
+    impl CommitRepository for ChangedFiles {
+        async fn resolve(&self, _: &ProjectId, revision: &str) -> crate::Result<CommitInfo> {
+            Err(crate::Error::not_found(format!("Revision {revision} not found")))
+        }
+
+        async fn history(&self, _: &ProjectId, _: &str, _: Option<&str>, _: usize) -> crate::Result<Vec<CommitInfo>> {
+            Ok(Vec::new())
+        }
+
+        async fn changed_files(&self, _: &ProjectId, _: &str, _: &str) -> crate::Result<Vec<String>> {
+            Ok(self.0.iter().map(|file| file.to_string()).collect())
+        }
+
+        async fn pull_request_changed_files(&self, _: &ProjectId, _: &str, _: &str) -> crate::Result<Vec<String>> {
+            Ok(self.0.iter().map(|file| file.to_string()).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_event() {
+        let events = Arc::new(EventBus::new(EventsConfig::default()));
+        let pipelines = Arc::new(PipelineService::new(
+            Arc::new(InMemoryPipelineRepository::new()),
+            events.clone(),
+        ));
+        let builds = Arc::new(BuildService::new(Arc::new(InMemoryBuildRepository::new()), events));
+        let service = WebhookService::new(pipelines.clone(), builds);
+
+        let project = Project::new(
+            "webhooks".to_string(),
+            "https://github.com/test/webhooks.git".to_string(),
+            "main".to_string(),
+        );
+        let mut job = Job::new("test".to_string());
+        job.add_command("cargo test".to_string());
+        for (name, branches) in [("main", vec!["main".to_string()]), ("all", vec![])] {
+            let config = PipelineConfig::new(
+                vec![Stage::new("test".to_string(), vec![job.clone()])],
+                vec![Trigger::Push { branches, paths: vec![], paths_ignore: vec![] }],
+            );
+            pipelines.create_pipeline(project.id().clone(), name.to_string(), config).await.unwrap();
+        }
+
+        let created = service.handle_event(&project, &push("main")).await.unwrap();
+        assert_eq!(created.len(), 2);
+        assert_eq!(created[0].commit_sha(), "abc123");
+        assert_eq!(created[0].commit_author(), Some("octocat"));
+        assert_eq!(created[0].commit_message(), Some("Fix tests"));
+
+        assert_eq!(service.handle_event(&project, &push("feature")).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_changed_files_are_found_for_path_filters() {
+        let events = Arc::new(EventBus::new(EventsConfig::default()));
+        let pipelines = Arc::new(PipelineService::new(
+            Arc::new(InMemoryPipelineRepository::new()),
+            events.clone(),
+        ));
+        let builds = Arc::new(BuildService::new(Arc::new(InMemoryBuildRepository::new()), events));
+        let service = WebhookService::new(pipelines.clone(), builds)
+            .with_commit_repository(Arc::new(ChangedFiles(vec!["docs/guide.md"])));
+
+        let project = Project::new(
+            "monorepo".to_string(),
+            "https://github.com/test/monorepo.git".to_string(),
+            "main".to_string(),
+        );
+        let mut job = Job::new("test".to_string());
+        job.add_command("cargo test".to_string());
+        for (name, paths) in [("api", "services/api/**"), ("docs", "docs/**")] {
+            let config = PipelineConfig::new(
+                vec![Stage::new("test".to_string(), vec![job.clone()])],
+                vec![
+                    Trigger::Push { branches: vec![], paths: vec![paths.to_string()], paths_ignore: vec![] },
+                    Trigger::PullRequest { branches: vec![], paths: vec![paths.to_string()], paths_ignore: vec![] },
+                ],
+            );
+            pipelines.create_pipeline(project.id().clone(), name.to_string(), config).await.unwrap();
+        }
+
+        // Pushes are compared with the commit before them
+        let pushed = ScmEvent { before_sha: Some("fff000".to_string()), ..push("main") };
+        let created = service.handle_event(&project, &pushed).await.unwrap();
+        assert_eq!(created.len(), 1);
+
+        let pull_request = ScmEvent {
+            kind: ScmEventKind::PullRequest {
+                number: 7,
+                source_branch: "feature".to_string(),
+                target_branch: "main".to_string(),
+            },
+            ..push("feature")
+        };
+        assert_eq!(service.handle_event(&project, &pull_request).await.unwrap().len(), 1);
+
+        // New branches have nothing to compare with, so every pipeline builds
+        assert_eq!(service.handle_event(&project, &push("feature")).await.unwrap().len(), 2);
+    }
+}
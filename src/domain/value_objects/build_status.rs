@@ -77,6 +77,21 @@ impl fmt::Display for BuildStatus {
     }
 }
 
+impl std::str::FromStr for BuildStatus {
+    type Err = crate::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pending" => Ok(BuildStatus::Pending),
+            "running" => Ok(BuildStatus::Running),
+            "success" => Ok(BuildStatus::Success),
+            "failed" => Ok(BuildStatus::Failed),
+            "cancelled" => Ok(BuildStatus::Cancelled),
+            _ => Err(crate::Error::validation(format!("Unknown build status: {s}"))),
+        }
+    }
+}
+
 impl Default for BuildStatus {
     fn default() -> Self {
         BuildStatus::Pending
@@ -109,6 +124,8 @@ mod tests {
     fn test_build_status_display() {
         assert_eq!(BuildStatus::Success.to_string(), "Success");
         assert_eq!(BuildStatus::Failed.to_string(), "Failed");
+        assert_eq!("running".parse::<BuildStatus>().unwrap(), BuildStatus::Running);
+        assert!("unknown".parse::<BuildStatus>().is_err());
     }
 
     #[test]
@@ -0,0 +1,49 @@
+//! Commit Info value object - Details of a Git commit shown with builds
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Details of a Git commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitInfo {
+    /// Commit SHA
+    pub sha: String,
+    
+    /// Full commit message
+    pub message: String,
+    
+    /// Author, as `Name <email>`
+    pub author: String,
+    
+    /// Committer, as `Name <email>`
+    pub committer: String,
+    
+    /// Commit time
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CommitInfo {
+    /// Get the first line of the message
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let commit = CommitInfo {
+            sha: "abc123".to_string(),
+            message: "Fix tests\n\nThe clock was off by one.".to_string(),
+            author: "Alice <alice@example.com>".to_string(),
+            committer: "Bob <bob@example.com>".to_string(),
+            timestamp: Utc::now(),
+        };
+        
+        assert_eq!(commit.summary(), "Fix tests");
+        assert_eq!(CommitInfo { message: String::new(), ..commit }.summary(), "");
+    }
+}
@@ -5,10 +5,14 @@ pub mod pipeline_id;
 pub mod project_id;
 pub mod agent_id;
 pub mod user_id;
+pub mod api_token_id;
 pub mod job_id;
 pub mod stage_id;
 pub mod artifact_id;
 pub mod workspace_id;
 pub mod build_status;
 pub mod pipeline_config;
+pub mod ref_pattern;
+pub mod scm_event;
+pub mod commit_info;
 
@@ -1,7 +1,10 @@
 //! Pipeline Configuration value object
 
+use crate::domain::entities::secret;
+use crate::domain::value_objects::ref_pattern::{self, RefMatcher, RefPattern};
+use crate::domain::value_objects::scm_event::{POLL_SOURCE, ScmEvent, ScmEventKind};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Pipeline Configuration value object
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -15,11 +18,15 @@ pub struct PipelineConfig {
     /// Pipeline triggers
     pub triggers: Vec<Trigger>,
     
-    /// Global environment variables
+    /// Global environment variables; values may reference `${{ secrets.NAME }}`
     pub environment: HashMap<String, String>,
     
     /// Notification settings
     pub notifications: Option<NotificationConfig>,
+    
+    /// How the repository is checked out for jobs
+    #[serde(default)]
+    pub checkout: CheckoutConfig,
 }
 
 /// Pipeline stage
@@ -50,7 +57,7 @@ pub struct Job {
     /// Commands to execute
     pub commands: Vec<String>,
     
-    /// Environment variables
+    /// Environment variables; values may reference `${{ secrets.NAME }}`
     pub environment: HashMap<String, String>,
     
     /// Working directory
@@ -71,8 +78,51 @@ pub struct Job {
     /// Dependencies on other jobs
     pub needs: Vec<String>,
     
+    /// Jobs whose artifacts are downloaded into the workspace before the
+    /// commands run; each must be needed by this job or run in an earlier
+    /// stage
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    
     /// Conditions for running this job
     pub when: Option<WhenCondition>,
+    
+    /// Checkout settings replacing the pipeline's, e.g. to get the full
+    /// history for `git describe`
+    pub checkout: Option<CheckoutConfig>,
+}
+
+/// Repository checkout settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckoutConfig {
+    /// Number of commits of history to fetch; `None` fetches all of it
+    #[serde(default)]
+    pub depth: Option<u32>,
+    
+    /// Only check out these paths; empty checks out everything
+    #[serde(default)]
+    pub sparse_paths: Vec<String>,
+    
+    /// Which submodules to check out
+    #[serde(default)]
+    pub submodules: SubmoduleMode,
+    
+    /// Fetch all tags, not only the ones pointing into the fetched history
+    #[serde(default = "default_fetch_tags")]
+    pub fetch_tags: bool,
+}
+
+/// Submodule handling during checkout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmoduleMode {
+    /// Leave submodules empty
+    #[default]
+    None,
+    /// Check out the submodules of the repository
+    Init,
+    /// Check out submodules and their own submodules
+    Recursive,
 }
 
 /// Pipeline trigger
@@ -81,13 +131,27 @@ pub struct Job {
 pub enum Trigger {
     /// Trigger on push
     Push {
-        /// Branches to trigger on
+        /// Branch patterns to trigger on
         branches: Vec<String>,
+        /// Only trigger when a changed file matches one of these patterns;
+        /// patterns without a slash match file names at any depth
+        #[serde(default)]
+        paths: Vec<String>,
+        /// Do not trigger when every changed file matches one of these patterns
+        #[serde(default)]
+        paths_ignore: Vec<String>,
     },
     /// Trigger on pull request
     PullRequest {
-        /// Branches to trigger on
+        /// Target branch patterns to trigger on
         branches: Vec<String>,
+        /// Only trigger when a changed file matches one of these patterns;
+        /// patterns without a slash match file names at any depth
+        #[serde(default)]
+        paths: Vec<String>,
+        /// Do not trigger when every changed file matches one of these patterns
+        #[serde(default)]
+        paths_ignore: Vec<String>,
     },
     /// Scheduled trigger
     Schedule {
@@ -101,12 +165,26 @@ pub enum Trigger {
         /// Tag patterns
         patterns: Vec<String>,
     },
+    /// Poll the repository for new commits, for forges without webhooks
+    Poll {
+        /// Seconds between polls
+        interval: u64,
+        /// Branch patterns to build; empty builds every branch
+        #[serde(default)]
+        branches: Vec<String>,
+        /// Tag patterns to build; tags are only built when listed
+        #[serde(default)]
+        tags: Vec<String>,
+    },
 }
 
+/// Shortest allowed interval between polls of a repository, in seconds
+pub const MIN_POLL_INTERVAL: u64 = 60;
+
 /// Condition for running a stage or job
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WhenCondition {
-    /// Branch condition
+    /// Branch pattern condition
     pub branch: Option<String>,
     
     /// Event type condition
@@ -119,13 +197,15 @@ pub struct WhenCondition {
 /// Artifact configuration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ArtifactConfig {
-    /// Paths to include
+    /// Paths to include, as globs relative to the workspace; a directory
+    /// includes everything in it
     pub paths: Vec<String>,
     
     /// Paths to exclude
+    #[serde(default)]
     pub exclude: Vec<String>,
     
-    /// Artifact name
+    /// Artifact name, by default the job name or the name of the only file
     pub name: Option<String>,
     
     /// Expiration time in days
@@ -135,14 +215,34 @@ pub struct ArtifactConfig {
 /// Cache configuration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CacheConfig {
-    /// Cache key
+    /// Cache key; `${{ hashFiles('Cargo.lock') }}` is replaced with a hash of
+    /// the matching files
     pub key: String,
     
-    /// Paths to cache
+    /// Key prefixes to restore the newest cache of when no cache has the key
+    #[serde(default)]
+    pub restore_keys: Vec<String>,
+    
+    /// Paths to cache, relative to the workspace; directories are cached
+    /// with their contents
     pub paths: Vec<String>,
     
-    /// Cache policy (pull, push, pull-push)
-    pub policy: Option<String>,
+    /// Whether the cache is restored, saved or both
+    #[serde(default)]
+    pub policy: CachePolicy,
+}
+
+/// When a job's cache is restored and saved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CachePolicy {
+    /// Only restore the cache before the job
+    Pull,
+    /// Only save the cache after the job
+    Push,
+    /// Restore the cache before the job and save it after
+    #[default]
+    PullPush,
 }
 
 /// Notification configuration
@@ -180,6 +280,7 @@ impl PipelineConfig {
             triggers,
             environment: HashMap::new(),
             notifications: None,
+            checkout: CheckoutConfig::default(),
         }
     }
     
@@ -199,14 +300,69 @@ impl PipelineConfig {
             stage.validate()?;
         }
         
+        self.validate_dependencies()?;
+        
         // Validate triggers
         if self.triggers.is_empty() {
             return Err(crate::Error::validation("Pipeline must have at least one trigger"));
         }
         
+        for trigger in &self.triggers {
+            trigger.validate()?;
+        }
+        
+        validate_secret_references(&self.environment)?;
+        
+        self.checkout.validate()?;
+        
+        Ok(())
+    }
+    
+    /// Check that jobs only depend on the artifacts of jobs that run before
+    /// them and save artifacts
+    fn validate_dependencies(&self) -> crate::Result<()> {
+        let jobs: HashMap<&str, &Job> = self
+            .stages
+            .iter()
+            .flat_map(|stage| &stage.jobs)
+            .map(|job| (job.name.as_str(), job))
+            .collect();
+        let mut earlier = HashSet::new();
+        
+        for stage in &self.stages {
+            for job in &stage.jobs {
+                for dependency in &job.dependencies {
+                    let Some(upstream) = jobs.get(dependency.as_str()) else {
+                        return Err(crate::Error::validation(format!(
+                            "Job '{}' depends on unknown job '{dependency}'",
+                            job.name
+                        )));
+                    };
+                    if !earlier.contains(dependency.as_str()) && !job.needs.contains(dependency) {
+                        return Err(crate::Error::validation(format!(
+                            "Job '{}' depends on '{dependency}', which must be in its needs or an earlier stage",
+                            job.name
+                        )));
+                    }
+                    if upstream.artifacts.is_none() {
+                        return Err(crate::Error::validation(format!(
+                            "Job '{}' depends on '{dependency}', which saves no artifacts",
+                            job.name
+                        )));
+                    }
+                }
+            }
+            earlier.extend(stage.jobs.iter().map(|job| job.name.as_str()));
+        }
+        
         Ok(())
     }
     
+    /// Get the checkout settings of a job
+    pub fn checkout_for<'a>(&'a self, job: &'a Job) -> &'a CheckoutConfig {
+        job.checkout.as_ref().unwrap_or(&self.checkout)
+    }
+    
     /// Add a stage
     pub fn add_stage(&mut self, stage: Stage) {
         self.stages.push(stage);
@@ -244,6 +400,10 @@ impl Stage {
             return Err(crate::Error::validation("Stage must have at least one job"));
         }
         
+        if let Some(when) = &self.when {
+            when.validate()?;
+        }
+        
         for job in &self.jobs {
             job.validate()?;
         }
@@ -266,7 +426,9 @@ impl Job {
             artifacts: None,
             cache: None,
             needs: Vec::new(),
+            dependencies: Vec::new(),
             when: None,
+            checkout: None,
         }
     }
     
@@ -282,6 +444,24 @@ impl Job {
             ));
         }
         
+        if let Some(when) = &self.when {
+            when.validate()?;
+        }
+        
+        validate_secret_references(&self.environment)?;
+        
+        if let Some(checkout) = &self.checkout {
+            checkout.validate()?;
+        }
+        
+        if let Some(artifacts) = &self.artifacts {
+            artifacts.validate()?;
+        }
+        
+        if let Some(cache) = &self.cache {
+            cache.validate()?;
+        }
+        
         Ok(())
     }
     
@@ -296,6 +476,318 @@ impl Job {
     }
 }
 
+impl ArtifactConfig {
+    /// Validate the artifact settings
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.paths.is_empty() {
+            return Err(crate::Error::validation("Artifacts must list at least one path"));
+        }
+        
+        validate_workspace_patterns(&self.paths, "Artifact")?;
+        validate_workspace_patterns(&self.exclude, "Artifact")?;
+        
+        if let Some(name) = &self.name {
+            if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+                return Err(crate::Error::validation(format!("Invalid artifact name: '{name}'")));
+            }
+        }
+        
+        if self.expire_in == Some(0) {
+            return Err(crate::Error::validation("Artifacts must be kept for at least a day"));
+        }
+        
+        Ok(())
+    }
+}
+
+impl CacheConfig {
+    /// Validate the cache settings
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.paths.is_empty() {
+            return Err(crate::Error::validation("Caches must list at least one path"));
+        }
+        validate_workspace_patterns(&self.paths, "Cache")?;
+        
+        validate_cache_key(&self.key)?;
+        for prefix in &self.restore_keys {
+            validate_cache_key(prefix)?;
+        }
+        
+        Ok(())
+    }
+}
+
+impl CachePolicy {
+    /// Check if the cache is restored before the job
+    pub fn pulls(self) -> bool {
+        matches!(self, CachePolicy::Pull | CachePolicy::PullPush)
+    }
+    
+    /// Check if the cache is saved after the job
+    pub fn pushes(self) -> bool {
+        matches!(self, CachePolicy::Push | CachePolicy::PullPush)
+    }
+}
+
+/// Check that path patterns are valid and stay inside the workspace
+fn validate_workspace_patterns(patterns: &[String], kind: &str) -> crate::Result<()> {
+    for pattern in patterns {
+        if pattern.starts_with('/') || pattern.split('/').any(|segment| segment == "..") {
+            return Err(crate::Error::validation(format!(
+                "{kind} path '{pattern}' must stay inside the workspace"
+            )));
+        }
+        RefPattern::parse(pattern)?;
+    }
+    Ok(())
+}
+
+/// Check that a cache key only uses `hashFiles` expressions and makes a
+/// valid file name
+fn validate_cache_key(key: &str) -> crate::Result<()> {
+    let expanded = expand_expressions(key, |expression| {
+        let Some(patterns) = hash_files_patterns(expression)? else {
+            return Err(crate::Error::validation(format!(
+                "Unsupported expression in cache key: '{expression}'"
+            )));
+        };
+        validate_workspace_patterns(&patterns, "hashFiles")?;
+        Ok(Some("0".to_string()))
+    })?;
+    
+    if expanded.is_empty() || expanded == "." || expanded == ".." || expanded.contains(['/', '\\']) {
+        return Err(crate::Error::validation(format!("Invalid cache key: '{key}'")));
+    }
+    Ok(())
+}
+
+impl CheckoutConfig {
+    /// Validate the checkout settings
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.depth == Some(0) {
+            return Err(crate::Error::validation("Checkout depth must be at least 1"));
+        }
+        
+        for path in &self.sparse_paths {
+            if path.is_empty() || path.split('/').any(|segment| segment == "..") {
+                return Err(crate::Error::validation(format!("Invalid sparse checkout path: '{path}'")));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check if only part of the history is fetched
+    pub fn is_shallow(&self) -> bool {
+        self.depth.is_some()
+    }
+}
+
+impl Default for CheckoutConfig {
+    fn default() -> Self {
+        Self {
+            depth: None,
+            sparse_paths: Vec::new(),
+            submodules: SubmoduleMode::None,
+            fetch_tags: default_fetch_tags(),
+        }
+    }
+}
+
+fn default_fetch_tags() -> bool {
+    true
+}
+
+impl Trigger {
+    /// Validate the branch and tag patterns of the trigger
+    pub fn validate(&self) -> crate::Result<()> {
+        match self {
+            Trigger::Push { branches, paths, paths_ignore }
+            | Trigger::PullRequest { branches, paths, paths_ignore } => {
+                ref_pattern::validate_patterns(branches)?;
+                ref_pattern::validate_patterns(paths)?;
+                ref_pattern::validate_patterns(paths_ignore)
+            }
+            Trigger::Tag { patterns } => ref_pattern::validate_patterns(patterns),
+            Trigger::Poll { interval, branches, tags } => {
+                if *interval < MIN_POLL_INTERVAL {
+                    return Err(crate::Error::validation(format!(
+                        "Poll interval must be at least {MIN_POLL_INTERVAL} seconds"
+                    )));
+                }
+                ref_pattern::validate_patterns(branches)?;
+                ref_pattern::validate_patterns(tags)
+            }
+            Trigger::Schedule { .. } | Trigger::Manual => Ok(()),
+        }
+    }
+    
+    /// Check if the trigger filters events by the files they change
+    pub fn filters_paths(&self) -> bool {
+        match self {
+            Trigger::Push { paths, paths_ignore, .. } | Trigger::PullRequest { paths, paths_ignore, .. } => {
+                !paths.is_empty() || !paths_ignore.is_empty()
+            }
+            _ => false,
+        }
+    }
+    
+    /// Check whether a source control event fires this trigger
+    ///
+    /// An empty branch or tag list matches every branch or tag. Pull request
+    /// triggers match on the target branch. Path filters are only applied when
+    /// the event knows which files changed. Poll triggers only match events
+    /// found by polling.
+    pub fn matches(&self, event: &ScmEvent) -> bool {
+        let changed_files = event.changed_files.as_deref();
+        match (self, &event.kind) {
+            (Trigger::Push { branches, paths, paths_ignore }, ScmEventKind::Push { branch }) => {
+                matches_any(branches, branch) && matches_paths(paths, paths_ignore, changed_files)
+            }
+            (
+                Trigger::PullRequest { branches, paths, paths_ignore },
+                ScmEventKind::PullRequest { target_branch, .. },
+            ) => {
+                matches_any(branches, target_branch) && matches_paths(paths, paths_ignore, changed_files)
+            }
+            (Trigger::Tag { patterns }, ScmEventKind::Tag { name }) => matches_any(patterns, name),
+            (Trigger::Poll { branches, .. }, ScmEventKind::Push { branch }) if event.source == POLL_SOURCE => {
+                matches_any(branches, branch)
+            }
+            (Trigger::Poll { tags, .. }, ScmEventKind::Tag { name }) if event.source == POLL_SOURCE => {
+                !tags.is_empty() && matches_any(tags, name)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn matches_any(patterns: &[String], name: &str) -> bool {
+    patterns.is_empty() || RefMatcher::new(patterns).is_ok_and(|matcher| matcher.matches(name))
+}
+
+/// Apply `paths` and `paths_ignore` filters to the changed files of an event
+fn matches_paths(paths: &[String], paths_ignore: &[String], changed_files: Option<&[String]>) -> bool {
+    let Some(changed_files) = changed_files else {
+        return true;
+    };
+    
+    let any_relevant = paths.is_empty()
+        || path_matcher(paths).is_ok_and(|matcher| changed_files.iter().any(|file| matcher.matches(file)));
+    let all_ignored = !paths_ignore.is_empty()
+        && path_matcher(paths_ignore).is_ok_and(|matcher| changed_files.iter().all(|file| matcher.matches(file)));
+    
+    any_relevant && !all_ignored
+}
+
+/// Parse path patterns; as in `.gitignore`, a pattern without a slash matches
+/// a file name at any depth
+fn path_matcher(patterns: &[String]) -> crate::Result<RefMatcher> {
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|pattern| {
+            let (negation, body) = match pattern.strip_prefix('!') {
+                Some(body) => ("!", body),
+                None => ("", pattern.as_str()),
+            };
+            if body.contains('/') {
+                pattern.clone()
+            } else {
+                format!("{negation}**/{body}")
+            }
+        })
+        .collect();
+    RefMatcher::new(&patterns)
+}
+
+impl WhenCondition {
+    /// Validate the branch pattern
+    pub fn validate(&self) -> crate::Result<()> {
+        match &self.branch {
+            Some(branch) => ref_pattern::RefPattern::parse(branch).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+    
+    /// Check whether the branch condition holds for a branch
+    pub fn matches_branch(&self, branch: &str) -> bool {
+        self.branch
+            .as_ref()
+            .is_none_or(|pattern| RefMatcher::new(&[pattern]).is_ok_and(|matcher| matcher.matches(branch)))
+    }
+}
+
+/// Expand `${{ expression }}` placeholders in a value
+///
+/// `resolve` receives the trimmed expression and returns its replacement, or
+/// `None` to leave the placeholder as it is.
+pub fn expand_expressions(
+    value: &str,
+    mut resolve: impl FnMut(&str) -> crate::Result<Option<String>>,
+) -> crate::Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    
+    while let Some(start) = rest.find("${{") {
+        let inner = &rest[start + 3..];
+        let end = inner
+            .find("}}")
+            .ok_or_else(|| crate::Error::validation("Unterminated ${{ expression"))?;
+        
+        expanded.push_str(&rest[..start]);
+        match resolve(inner[..end].trim())? {
+            Some(replacement) => expanded.push_str(&replacement),
+            None => expanded.push_str(&rest[start..start + 3 + end + 2]),
+        }
+        rest = &inner[end + 2..];
+    }
+    
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Get the patterns of a `hashFiles('a', 'b')` expression, or `None` for
+/// other expressions
+pub fn hash_files_patterns(expression: &str) -> crate::Result<Option<Vec<String>>> {
+    let Some(arguments) = expression.strip_prefix("hashFiles(") else {
+        return Ok(None);
+    };
+    let invalid = || crate::Error::validation(format!("Invalid hashFiles expression: '{expression}'"));
+    
+    let arguments = arguments.strip_suffix(')').ok_or_else(invalid)?;
+    let patterns = arguments
+        .split(',')
+        .map(|argument| {
+            let argument = argument.trim();
+            argument
+                .strip_prefix('\'')
+                .and_then(|argument| argument.strip_suffix('\''))
+                .or_else(|| argument.strip_prefix('"').and_then(|argument| argument.strip_suffix('"')))
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_string)
+                .ok_or_else(invalid)
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+    Ok(Some(patterns))
+}
+
+/// Get the secret name of a `secrets.NAME` expression
+pub fn secret_reference(expression: &str) -> Option<&str> {
+    expression.strip_prefix("secrets.").map(str::trim)
+}
+
+fn validate_secret_references(environment: &HashMap<String, String>) -> crate::Result<()> {
+    for value in environment.values() {
+        expand_expressions(value, |expression| {
+            if let Some(name) = secret_reference(expression) {
+                secret::validate_name(name)?;
+            }
+            Ok(None)
+        })?;
+    }
+    Ok(())
+}
+
 impl Default for PipelineConfig {
     fn default() -> Self {
         Self {
@@ -304,6 +796,7 @@ impl Default for PipelineConfig {
             triggers: Vec::new(),
             environment: HashMap::new(),
             notifications: None,
+            checkout: CheckoutConfig::default(),
         }
     }
 }
@@ -321,6 +814,8 @@ mod tests {
             )],
             vec![Trigger::Push {
                 branches: vec!["main".to_string()],
+                paths: vec![],
+                paths_ignore: vec![],
             }],
         );
         
@@ -365,6 +860,21 @@ mod tests {
         assert!(job2.validate().is_ok());
     }
 
+    #[test]
+    fn test_expand_expressions() {
+        let expanded = expand_expressions("user:${{ secrets.PASSWORD }}@${{ env.HOST }}", |expression| {
+            Ok(secret_reference(expression).map(|name| format!("<{name}>")))
+        }).unwrap();
+        assert_eq!(expanded, "user:<PASSWORD>@${{ env.HOST }}");
+        
+        assert!(expand_expressions("${{ secrets.PASSWORD", |_| Ok(None)).is_err());
+        
+        let mut job = Job::new("deploy".to_string());
+        job.add_command("./deploy.sh".to_string());
+        job.environment.insert("KEY".to_string(), "${{ secrets.deploy-key }}".to_string());
+        assert!(job.validate().is_err());
+    }
+
     #[test]
     fn test_stage_validation() {
         let mut stage = Stage::new("build".to_string(), vec![]);
@@ -379,5 +889,214 @@ mod tests {
         
         assert!(stage.validate().is_ok());
     }
-}
 
+    fn push_trigger(branches: &[&str], paths: &[&str], paths_ignore: &[&str]) -> Trigger {
+        let strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+        Trigger::Push {
+            branches: strings(branches),
+            paths: strings(paths),
+            paths_ignore: strings(paths_ignore),
+        }
+    }
+    
+    #[test]
+    fn test_trigger_matching() {
+        let event = |kind| ScmEvent {
+            source: "github".to_string(),
+            kind,
+            commit_sha: "abc123".to_string(),
+            commit_message: "Change".to_string(),
+            commit_author: "octocat".to_string(),
+            before_sha: None,
+            changed_files: None,
+        };
+        let push = event(ScmEventKind::Push { branch: "main".to_string() });
+        let pull_request = event(ScmEventKind::PullRequest {
+            number: 1,
+            source_branch: "feature".to_string(),
+            target_branch: "main".to_string(),
+        });
+        let tag = event(ScmEventKind::Tag { name: "v1.0.0".to_string() });
+        
+        let main = push_trigger(&["main"], &[], &[]);
+        assert!(main.matches(&push));
+        assert!(!main.matches(&pull_request));
+        assert!(!push_trigger(&["develop"], &[], &[]).matches(&push));
+        assert!(!push_trigger(&["**", "!main"], &[], &[]).matches(&push));
+        let pull_request_trigger = Trigger::PullRequest {
+            branches: vec!["main".to_string()],
+            paths: vec![],
+            paths_ignore: vec![],
+        };
+        assert!(pull_request_trigger.matches(&pull_request));
+        assert!(Trigger::Tag { patterns: vec![] }.matches(&tag));
+        assert!(Trigger::Tag { patterns: vec!["v*.*.*".to_string()] }.matches(&tag));
+        assert!(!Trigger::Manual.matches(&push));
+        
+        let poll = Trigger::Poll { interval: 300, branches: vec!["main".to_string()], tags: vec![] };
+        assert!(!poll.matches(&push));
+        let polled = ScmEvent { source: POLL_SOURCE.to_string(), ..push.clone() };
+        assert!(poll.matches(&polled));
+        assert!(!poll.matches(&ScmEvent { source: POLL_SOURCE.to_string(), ..tag.clone() }));
+        assert!(poll.validate().is_ok());
+        assert!(Trigger::Poll { interval: 5, branches: vec![], tags: vec![] }.validate().is_err());
+    }
+    
+    #[test]
+    fn test_invalid_patterns_are_rejected() {
+        let mut job = Job::new("compile".to_string());
+        job.add_command("cargo build".to_string());
+        let mut config = PipelineConfig::new(
+            vec![Stage::new("build".to_string(), vec![job])],
+            vec![push_trigger(&["/(/"], &[], &[])],
+        );
+        assert!(config.validate().is_err());
+        
+        config.triggers = vec![Trigger::Manual];
+        config.stages[0].when = Some(WhenCondition {
+            branch: Some("release/**".to_string()),
+            event: None,
+            status: None,
+        });
+        assert!(config.validate().is_ok());
+        assert!(config.stages[0].when.as_ref().unwrap().matches_branch("release/1.0"));
+        assert!(!config.stages[0].when.as_ref().unwrap().matches_branch("main"));
+    }
+    
+    #[test]
+    fn test_path_filters() {
+        let push = |files: Option<&[&str]>| ScmEvent {
+            source: "github".to_string(),
+            kind: ScmEventKind::Push { branch: "main".to_string() },
+            commit_sha: "abc123".to_string(),
+            commit_message: "Change".to_string(),
+            commit_author: "octocat".to_string(),
+            before_sha: Some("fff000".to_string()),
+            changed_files: files.map(|files| files.iter().map(|f| f.to_string()).collect()),
+        };
+        let api = push_trigger(&["main"], &["services/api/**", "Cargo.lock"], &[]);
+        let code = push_trigger(&["main"], &[], &["*.md", "docs/**"]);
+        
+        assert!(api.matches(&push(Some(&["services/api/src/main.rs", "README.md"]))));
+        assert!(api.matches(&push(Some(&["Cargo.lock"]))));
+        assert!(!api.matches(&push(Some(&["services/web/index.html"]))));
+        assert!(!api.matches(&push(Some(&[]))));
+        
+        assert!(code.matches(&push(Some(&["docs/guide.md", "src/lib.rs"]))));
+        assert!(!code.matches(&push(Some(&["docs/guide.md", "services/api/README.md"]))));
+        assert!(!code.matches(&push(Some(&["README.md", "CHANGELOG.md"]))));
+        
+        // Patterns without a slash match at any depth, others from the root
+        assert!(api.matches(&push(Some(&["services/web/Cargo.lock"]))));
+        assert!(!api.matches(&push(Some(&["vendor/services/api/lib.rs"]))));
+        assert!(api.filters_paths());
+        assert!(!push_trigger(&["main"], &[], &[]).filters_paths());
+        
+        // Without a list of changed files nothing is filtered out
+        assert!(api.matches(&push(None)));
+        assert!(code.matches(&push(None)));
+        
+        assert!(push_trigger(&["main"], &["/[/"], &[]).validate().is_err());
+    }
+    
+    #[test]
+    fn test_checkout_config() {
+        let mut job = Job::new("compile".to_string());
+        job.add_command("cargo build".to_string());
+        let mut config = PipelineConfig::new(
+            vec![Stage::new("build".to_string(), vec![job])],
+            vec![Trigger::Manual],
+        );
+        config.checkout = serde_yaml::from_str("depth: 1\nsparse_paths: [services/api]\nsubmodules: recursive").unwrap();
+        assert!(config.checkout.fetch_tags);
+        assert_eq!(config.checkout.submodules, SubmoduleMode::Recursive);
+        assert!(config.validate().is_ok());
+        
+        // Jobs needing history replace the pipeline's shallow checkout
+        let job = &config.stages[0].jobs[0];
+        assert!(config.checkout_for(job).is_shallow());
+        let mut describe = job.clone();
+        describe.checkout = Some(CheckoutConfig::default());
+        assert!(!config.checkout_for(&describe).is_shallow());
+        
+        config.checkout.depth = Some(0);
+        assert!(config.validate().is_err());
+        config.checkout.depth = None;
+        config.checkout.sparse_paths = vec!["../outside".to_string()];
+        assert!(config.validate().is_err());
+    }
+    
+    #[test]
+    fn test_artifact_config() {
+        let artifacts: ArtifactConfig = serde_yaml::from_str("paths: [\"dist/**\"]\nexpire_in: 7").unwrap();
+        assert!(artifacts.exclude.is_empty());
+        assert!(artifacts.validate().is_ok());
+        
+        for path in ["../secrets", "/etc/passwd", "dist/../../up"] {
+            let invalid = ArtifactConfig { paths: vec![path.to_string()], ..artifacts.clone() };
+            assert!(invalid.validate().is_err(), "{path}");
+        }
+        assert!(ArtifactConfig { paths: vec![], ..artifacts.clone() }.validate().is_err());
+        assert!(ArtifactConfig { name: Some("a/b".to_string()), ..artifacts.clone() }.validate().is_err());
+        assert!(ArtifactConfig { expire_in: Some(0), ..artifacts }.validate().is_err());
+    }
+    
+    #[test]
+    fn test_artifact_dependencies() {
+        let job = |name: &str| {
+            let mut job = Job::new(name.to_string());
+            job.add_command("make".to_string());
+            job
+        };
+        let mut compile = job("compile");
+        compile.artifacts = Some(serde_yaml::from_str("paths: [dist]").unwrap());
+        let mut package = job("package");
+        package.dependencies = vec!["compile".to_string()];
+        let mut config = PipelineConfig::new(
+            vec![
+                Stage::new("build".to_string(), vec![compile, job("lint")]),
+                Stage::new("release".to_string(), vec![package]),
+            ],
+            vec![Trigger::Manual],
+        );
+        assert!(config.validate().is_ok());
+        
+        // Jobs of the same stage must be needed
+        let mut test = job("test");
+        test.dependencies = vec!["compile".to_string()];
+        config.stages[0].jobs.push(test);
+        assert!(config.validate().is_err());
+        config.stages[0].jobs[2].needs = vec!["compile".to_string()];
+        assert!(config.validate().is_ok());
+        
+        for dependency in ["lint", "missing", "package"] {
+            config.stages[1].jobs[0].dependencies = vec![dependency.to_string()];
+            assert!(config.validate().is_err(), "{dependency}");
+        }
+    }
+    
+    #[test]
+    fn test_cache_config() {
+        let cache: CacheConfig = serde_yaml::from_str(
+            "key: cargo-${{ hashFiles('Cargo.lock', \"**/Cargo.toml\") }}\nrestore_keys: [cargo-]\npaths: [target]",
+        )
+        .unwrap();
+        assert_eq!(cache.policy, CachePolicy::PullPush);
+        assert!(cache.validate().is_ok());
+        assert_eq!(
+            hash_files_patterns("hashFiles('Cargo.lock', \"**/Cargo.toml\")").unwrap(),
+            Some(vec!["Cargo.lock".to_string(), "**/Cargo.toml".to_string()])
+        );
+        assert_eq!(hash_files_patterns("secrets.TOKEN").unwrap(), None);
+        
+        let pull: CacheConfig = serde_yaml::from_str("key: deps\npaths: [node_modules]\npolicy: pull").unwrap();
+        assert!(pull.policy.pulls() && !pull.policy.pushes());
+        assert!(CachePolicy::Push.pushes() && !CachePolicy::Push.pulls());
+        
+        for key in ["", "a/b", "${{ secrets.TOKEN }}", "${{ hashFiles() }}", "${{ hashFiles('../x') }}", "${{ hashFiles('a'"] {
+            assert!(CacheConfig { key: key.to_string(), ..cache.clone() }.validate().is_err(), "{key}");
+        }
+        assert!(CacheConfig { paths: vec![], ..cache.clone() }.validate().is_err());
+        assert!(CacheConfig { paths: vec!["/root".to_string()], ..cache }.validate().is_err());
+    }
+}
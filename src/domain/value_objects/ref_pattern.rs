@@ -0,0 +1,246 @@
+//! Ref Pattern value object - Branch and tag name patterns
+//!
+//! Patterns are gitignore-style globs:
+//!
+//! - `*` matches any characters except `/`, `**` also matches across `/`
+//! - `?` matches one character except `/`
+//! - `[abc]`, `[a-z]` and `[!abc]` match one character of a set
+//! - `\` escapes the next character
+//!
+//! A pattern between slashes, like `/^release-\d+$/`, is a regular expression
+//! instead. A leading `!` turns any pattern into an exclusion.
+
+use regex::Regex;
+
+/// A single branch or tag name pattern
+#[derive(Debug, Clone)]
+pub struct RefPattern {
+    pattern: String,
+    negated: bool,
+    regex: Regex,
+}
+
+impl RefPattern {
+    /// Parse a pattern
+    pub fn parse(pattern: &str) -> crate::Result<Self> {
+        let (negated, body) = match pattern.strip_prefix('!') {
+            Some(body) => (true, body),
+            None => (false, pattern),
+        };
+        if body.is_empty() {
+            return Err(crate::Error::validation(format!("Empty ref pattern: '{pattern}'")));
+        }
+
+        let expression = match body.strip_prefix('/').and_then(|b| b.strip_suffix('/')) {
+            Some(expression) if !expression.is_empty() => expression.to_string(),
+            _ => glob_to_regex(body),
+        };
+        let regex = Regex::new(&expression).map_err(|e| {
+            crate::Error::validation(format!("Invalid ref pattern '{pattern}': {e}"))
+        })?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            negated,
+            regex,
+        })
+    }
+
+    /// Get the pattern as written
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Check if this is an exclusion (`!pattern`)
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Check if a name matches the pattern, ignoring negation
+    pub fn is_match(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+}
+
+/// An ordered list of patterns deciding whether a ref is selected
+///
+/// As in `.gitignore`, the last pattern matching a name decides: inclusions
+/// select it and exclusions deselect it. A list made only of exclusions selects
+/// everything they do not exclude; an empty list selects nothing.
+#[derive(Debug, Clone)]
+pub struct RefMatcher {
+    patterns: Vec<RefPattern>,
+}
+
+impl RefMatcher {
+    /// Parse a list of patterns
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> crate::Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| RefPattern::parse(pattern.as_ref()))
+            .collect::<crate::Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Check if a branch or tag name is selected
+    pub fn matches(&self, name: &str) -> bool {
+        let only_exclusions = !self.patterns.is_empty() && self.patterns.iter().all(RefPattern::is_negated);
+
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.is_match(name))
+            .map_or(only_exclusions, |pattern| !pattern.is_negated())
+    }
+}
+
+/// Check that a list of patterns is valid
+pub fn validate_patterns<S: AsRef<str>>(patterns: &[S]) -> crate::Result<()> {
+    RefMatcher::new(patterns).map(|_| ())
+}
+
+/// Translate a glob into an anchored regular expression
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::from("^");
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                let at_segment_start = i == 1 || chars[i - 2] == '/';
+                if at_segment_start && chars.get(i + 1) == Some(&'/') {
+                    // `**/` also matches no directories at all
+                    regex.push_str("(?:.*/)?");
+                    i += 1;
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match class_end(&chars, i) {
+                Some(end) => {
+                    regex.push('[');
+                    let mut start = i + 1;
+                    if matches!(chars[start], '!' | '^') {
+                        regex.push('^');
+                        start += 1;
+                    }
+                    for &c in &chars[start..end] {
+                        if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                            regex.push('\\');
+                        }
+                        regex.push(c);
+                    }
+                    regex.push(']');
+                    i = end;
+                }
+                None => regex.push_str(r"\["),
+            },
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                regex.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    regex.push('$');
+    regex
+}
+
+/// Find the `]` closing the character class opened at `start`
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if matches!(chars.get(i), Some('!' | '^')) {
+        i += 1;
+    }
+    // A `]` right after the opening bracket is part of the set
+    if chars.get(i) == Some(&']') {
+        i += 1;
+    }
+    (i..chars.len()).find(|&j| chars[j] == ']')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn matches(patterns: &[&str], name: &str) -> bool {
+        RefMatcher::new(patterns).unwrap().matches(name)
+    }
+
+    #[test]
+    fn test_globs() {
+        assert!(matches(&["main"], "main"));
+        assert!(!matches(&["main"], "main2"));
+        assert!(matches(&["release/*"], "release/1.0"));
+        assert!(!matches(&["release/*"], "release/1.0/hotfix"));
+        assert!(matches(&["release/**"], "release/1.0/hotfix"));
+        assert!(matches(&["**/hotfix"], "hotfix"));
+        assert!(matches(&["**/hotfix"], "release/1.0/hotfix"));
+        assert!(matches(&["v*.*.*"], "v1.20.3"));
+        assert!(!matches(&["v*.*.*"], "v1.20"));
+        assert!(matches(&["v?"], "v1"));
+        assert!(matches(&["v[0-9]*"], "v2-beta"));
+        assert!(!matches(&["v[!0-9]*"], "v2-beta"));
+        assert!(matches(&[r"feature\*"], "feature*"));
+        assert!(!matches(&[r"feature\*"], "feature-x"));
+        assert!(matches(&["a[b"], "a[b"));
+    }
+
+    #[test]
+    fn test_regex_patterns() {
+        assert!(matches(&[r"/^release-\d+$/"], "release-42"));
+        assert!(!matches(&[r"/^release-\d+$/"], "release-x"));
+        assert!(matches(&["/hotfix/"], "team/hotfix-1"));
+        assert!(RefMatcher::new(&["/(unclosed/"]).is_err());
+        // Found by proptest: a glob between slashes is a regex, not a glob
+        assert!(RefMatcher::new(&["/?/"]).is_err());
+    }
+
+    #[test]
+    fn test_negation() {
+        assert!(matches(&["**", "!wip/*"], "feature/x"));
+        assert!(!matches(&["**", "!wip/*"], "wip/x"));
+        assert!(matches(&["!wip/*"], "main"));
+        assert!(!matches(&["!wip/*"], "wip/x"));
+        // The last matching pattern wins
+        assert!(matches(&["!wip/*", "wip/keep"], "wip/keep"));
+        assert!(!matches(&[] as &[&str], "main"));
+        assert!(RefPattern::parse("!").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn escaped_names_match_themselves(name in "[a-zA-Z0-9/._*?\\[\\]!-]{1,20}") {
+            let escaped: String = name.chars().flat_map(|c| ['\\', c]).collect();
+            prop_assert!(RefPattern::parse(&escaped).unwrap().is_match(&name));
+        }
+
+        #[test]
+        fn double_star_matches_everything(name in "[a-z0-9/._-]{0,30}") {
+            prop_assert!(RefPattern::parse("**").unwrap().is_match(&name));
+        }
+
+        #[test]
+        fn single_star_stays_in_one_segment(segment in "[a-z0-9._-]{0,10}", rest in "[a-z0-9._-]{0,10}") {
+            let pattern = RefPattern::parse("release/*").unwrap();
+            let single = format!("release/{segment}");
+            let nested = format!("release/{segment}/{rest}");
+            prop_assert!(pattern.is_match(&single));
+            prop_assert!(!pattern.is_match(&nested));
+        }
+
+        #[test]
+        fn negation_inverts_a_single_pattern(glob in "[a-z*?][a-z*?/]{0,7}", name in "[a-z/]{0,12}") {
+            let included = matches(&[glob.as_str()], &name);
+            let excluded = matches(&[format!("!{glob}").as_str()], &name);
+            prop_assert_eq!(included, !excluded);
+        }
+    }
+}
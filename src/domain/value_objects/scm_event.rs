@@ -0,0 +1,105 @@
+//! SCM Event value object - A source control event that can trigger builds
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Source of events found by polling a repository
+pub const POLL_SOURCE: &str = "poll";
+
+/// Kind of source control event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScmEventKind {
+    /// Commits pushed to a branch
+    Push {
+        /// Branch name, without `refs/heads/`
+        branch: String,
+    },
+    /// Pull request opened or updated
+    PullRequest {
+        /// Pull request number
+        number: u32,
+        /// Branch the changes come from
+        source_branch: String,
+        /// Branch the changes are proposed for
+        target_branch: String,
+    },
+    /// Tag pushed
+    Tag {
+        /// Tag name, without `refs/tags/`
+        name: String,
+    },
+}
+
+impl fmt::Display for ScmEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScmEventKind::Push { .. } => write!(f, "push"),
+            ScmEventKind::PullRequest { .. } => write!(f, "pull_request"),
+            ScmEventKind::Tag { .. } => write!(f, "tag"),
+        }
+    }
+}
+
+/// Source control event, independent of the forge that sent it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScmEvent {
+    /// Forge the event came from, e.g. `github`
+    pub source: String,
+    
+    /// What happened
+    pub kind: ScmEventKind,
+    
+    /// Commit to build
+    pub commit_sha: String,
+    
+    /// Commit message, or pull request title
+    pub commit_message: String,
+    
+    /// Commit author
+    pub commit_author: String,
+    
+    /// Commit the ref pointed to before a push, if it existed
+    pub before_sha: Option<String>,
+    
+    /// Paths of the files changed by the event, or `None` when the forge did
+    /// not list them
+    pub changed_files: Option<Vec<String>>,
+}
+
+impl ScmEvent {
+    /// Get the ref a build of this event runs on
+    ///
+    /// This is the pushed branch or tag, or the source branch of a pull request.
+    pub fn ref_name(&self) -> &str {
+        match &self.kind {
+            ScmEventKind::Push { branch } => branch,
+            ScmEventKind::PullRequest { source_branch, .. } => source_branch,
+            ScmEventKind::Tag { name } => name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_ref_name() {
+        let event = ScmEvent {
+            source: "github".to_string(),
+            kind: ScmEventKind::PullRequest {
+                number: 7,
+                source_branch: "feature".to_string(),
+                target_branch: "main".to_string(),
+            },
+            commit_sha: "abc123".to_string(),
+            commit_message: "Add feature".to_string(),
+            commit_author: "octocat".to_string(),
+            before_sha: None,
+            changed_files: None,
+        };
+        
+        assert_eq!(event.ref_name(), "feature");
+        assert_eq!(event.kind.to_string(), "pull_request");
+    }
+}
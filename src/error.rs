@@ -307,6 +307,17 @@ impl From<git2::Error> for Error {
     }
 }
 
+/// Convert from redis::RedisError
+impl From<redis::RedisError> for Error {
+    fn from(err: redis::RedisError) -> Self {
+        if err.is_io_error() || err.is_connection_dropped() || err.is_timeout() {
+            Error::Network(err.to_string())
+        } else {
+            Error::ExternalService(err.to_string())
+        }
+    }
+}
+
 /// Convert from serde_json::Error
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
@@ -0,0 +1,569 @@
+//! In-process event bus dispatching domain events to subscribed handlers
+//!
+//! Every subscribed handler gets its own set of bounded queues. Events are routed
+//! to a queue by their aggregate id, so events of one aggregate are always handled
+//! in publish order while different aggregates are processed concurrently.
+//!
+//! Handlers are told apart by name, so each subscribed handler needs a unique
+//! one. Events a handler keeps failing on are kept as dead letters, up to
+//! `max_dead_letters` of the latest.
+
+use crate::config::EventsConfig;
+use crate::domain::events::{DomainEvent, EventHandler, EventPublisher};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+/// An event a handler failed to process after all delivery attempts
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    /// Name of the handler that failed
+    pub handler: String,
+
+    /// The undelivered event
+    pub event: DomainEvent,
+
+    /// Error returned by the last attempt
+    pub error: String,
+
+    /// Number of delivery attempts
+    pub attempts: u32,
+
+    /// Time of the last failed attempt
+    pub failed_at: DateTime<Utc>,
+}
+
+/// A registered handler and the queues feeding it
+struct Subscription {
+    handler: String,
+    interests: Vec<String>,
+    queues: Vec<mpsc::Sender<DomainEvent>>,
+}
+
+impl Subscription {
+    fn is_interested(&self, event_type: &str) -> bool {
+        self.interests.iter().any(|i| i == "*" || i == event_type)
+    }
+
+    fn queue_for(&self, aggregate_id: &str) -> &mpsc::Sender<DomainEvent> {
+        let mut hasher = DefaultHasher::new();
+        aggregate_id.hash(&mut hasher);
+        // The modulo keeps the value below `queues.len()`, so it fits in usize
+        #[allow(clippy::cast_possible_truncation)]
+        let index = (hasher.finish() % self.queues.len() as u64) as usize;
+        &self.queues[index]
+    }
+}
+
+/// Event bus publishing domain events to subscribed handlers
+pub struct EventBus {
+    config: EventsConfig,
+    subscriptions: RwLock<Vec<Subscription>>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+    dead_letters: Arc<Mutex<VecDeque<DeadLetter>>>,
+}
+
+impl EventBus {
+    /// Create a new event bus
+    pub fn new(config: EventsConfig) -> Self {
+        Self {
+            config,
+            subscriptions: RwLock::new(Vec::new()),
+            workers: Mutex::new(Vec::new()),
+            dead_letters: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Subscribe a handler to the event types it is interested in
+    ///
+    /// Fails if a handler with the same name is already subscribed.
+    pub async fn subscribe(&self, handler: Arc<dyn EventHandler>) -> crate::Result<()> {
+        // Held until the subscription is added so concurrent subscriptions
+        // cannot both take a name
+        let mut subscriptions = self.subscriptions.write().await;
+        if subscriptions.iter().any(|s| s.handler == handler.name()) {
+            return Err(crate::Error::conflict(format!(
+                "Event handler {} is already subscribed",
+                handler.name()
+            )));
+        }
+
+        let partitions = self.config.partitions.max(1);
+        let capacity = self.config.queue_capacity.max(1);
+        let mut queues = Vec::with_capacity(partitions);
+        let mut workers = self.workers.lock().await;
+
+        for _ in 0..partitions {
+            let (sender, receiver) = mpsc::channel(capacity);
+            queues.push(sender);
+            workers.push(tokio::spawn(run_worker(
+                handler.clone(),
+                receiver,
+                self.config.clone(),
+                self.dead_letters.clone(),
+            )));
+        }
+
+        let interests = handler
+            .interested_in()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        tracing::debug!("Subscribed event handler {}", handler.name());
+
+        subscriptions.push(Subscription {
+            handler: handler.name().to_string(),
+            interests,
+            queues,
+        });
+        Ok(())
+    }
+
+    /// Get the names of all subscribed handlers
+    pub async fn handler_names(&self) -> Vec<String> {
+        self.subscriptions
+            .read()
+            .await
+            .iter()
+            .map(|s| s.handler.clone())
+            .collect()
+    }
+
+    /// Deliver events to a single subscribed handler, skipping events it is not
+    /// interested in, and return the number of events queued
+    pub async fn redeliver(&self, handler: &str, events: Vec<DomainEvent>) -> crate::Result<usize> {
+        let queues: Vec<_> = {
+            let subscriptions = self.subscriptions.read().await;
+            let subscription = subscriptions
+                .iter()
+                .find(|s| s.handler == handler)
+                .ok_or_else(|| crate::Error::not_found(format!("Event handler {handler} not found")))?;
+
+            events
+                .into_iter()
+                .filter(|e| subscription.is_interested(e.event_type()))
+                .map(|e| (subscription.queue_for(&e.aggregate_id()).clone(), e))
+                .collect()
+        };
+
+        let count = queues.len();
+        for (queue, event) in queues {
+            queue
+                .send(event)
+                .await
+                .map_err(|_| crate::Error::internal("Event bus is shut down"))?;
+        }
+
+        Ok(count)
+    }
+
+    /// Get all dead-lettered events
+    pub async fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.lock().await.iter().cloned().collect()
+    }
+
+    /// Remove and return all dead-lettered events
+    pub async fn take_dead_letters(&self) -> Vec<DeadLetter> {
+        std::mem::take(&mut *self.dead_letters.lock().await).into()
+    }
+
+    /// Stop accepting events and wait until all queued events are handled
+    pub async fn shutdown(&self) {
+        self.subscriptions.write().await.clear();
+
+        let workers = std::mem::take(&mut *self.workers.lock().await);
+        for worker in workers {
+            if let Err(e) = worker.await {
+                tracing::error!("Event handler worker panicked: {}", e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for EventBus {
+    async fn publish(&self, event: DomainEvent) -> crate::Result<()> {
+        // Collect the target queues first so the lock is not held while a full queue
+        // applies backpressure
+        let aggregate_id = event.aggregate_id();
+        let queues: Vec<_> = self
+            .subscriptions
+            .read()
+            .await
+            .iter()
+            .filter(|s| s.is_interested(event.event_type()))
+            .map(|s| s.queue_for(&aggregate_id).clone())
+            .collect();
+
+        for queue in queues {
+            queue
+                .send(event.clone())
+                .await
+                .map_err(|_| crate::Error::internal("Event bus is shut down"))?;
+        }
+
+        Ok(())
+    }
+
+    async fn publish_batch(&self, events: Vec<DomainEvent>) -> crate::Result<()> {
+        for event in events {
+            self.publish(event).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Handle queued events one at a time until the queue is closed
+async fn run_worker(
+    handler: Arc<dyn EventHandler>,
+    mut receiver: mpsc::Receiver<DomainEvent>,
+    config: EventsConfig,
+    dead_letters: Arc<Mutex<VecDeque<DeadLetter>>>,
+) {
+    while let Some(event) = receiver.recv().await {
+        if let Some(dead_letter) = deliver(handler.as_ref(), event, &config).await {
+            let mut dead_letters = dead_letters.lock().await;
+            if dead_letters.len() >= config.max_dead_letters.max(1) {
+                if let Some(dropped) = dead_letters.pop_front() {
+                    tracing::warn!(
+                        "Dropping the dead letter of {} for {} {}; it can be replayed from the event log",
+                        dropped.handler,
+                        dropped.event.event_type(),
+                        dropped.event.aggregate_id()
+                    );
+                }
+            }
+            dead_letters.push_back(dead_letter);
+        }
+    }
+}
+
+/// Deliver an event, retrying with exponential backoff up to
+/// `max_retry_backoff_ms`, and return a dead letter if every attempt failed
+async fn deliver(
+    handler: &dyn EventHandler,
+    event: DomainEvent,
+    config: &EventsConfig,
+) -> Option<DeadLetter> {
+    let max_attempts = config.max_attempts.max(1);
+    let max_backoff = Duration::from_millis(config.max_retry_backoff_ms);
+    let mut backoff = Duration::from_millis(config.retry_backoff_ms).min(max_backoff);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+
+        let error = match handler.handle(&event).await {
+            Ok(()) => return None,
+            Err(e) => e,
+        };
+
+        if attempts >= max_attempts {
+            tracing::error!(
+                "Handler {} failed on {} after {} attempts: {}",
+                handler.name(),
+                event.event_type(),
+                attempts,
+                error
+            );
+
+            return Some(DeadLetter {
+                handler: handler.name().to_string(),
+                event,
+                error: error.to_string(),
+                attempts,
+                failed_at: Utc::now(),
+            });
+        }
+
+        tracing::warn!(
+            "Handler {} failed on {} (attempt {}/{}): {}",
+            handler.name(),
+            event.event_type(),
+            attempts,
+            max_attempts,
+            error
+        );
+
+        tokio::time::sleep(backoff).await;
+        backoff = backoff.saturating_mul(2).min(max_backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{
+        agent_id::AgentId,
+        build_id::BuildId,
+        build_status::BuildStatus,
+        pipeline_id::PipelineId,
+    };
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct RecordingHandler {
+        name: &'static str,
+        interests: Vec<&'static str>,
+        events: Mutex<Vec<DomainEvent>>,
+        delay_ms: u64,
+    }
+
+    impl RecordingHandler {
+        fn new(name: &'static str, interests: Vec<&'static str>) -> Self {
+            Self {
+                name,
+                interests,
+                events: Mutex::new(Vec::new()),
+                delay_ms: 0,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl EventHandler for RecordingHandler {
+        async fn handle(&self, event: &DomainEvent) -> crate::Result<()> {
+            if self.delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            }
+            self.events.lock().await.push(event.clone());
+            Ok(())
+        }
+
+        fn interested_in(&self) -> Vec<&str> {
+            self.interests.clone()
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    struct FailingHandler {
+        calls: AtomicU32,
+        failures: u32,
+    }
+
+    #[async_trait]
+    impl EventHandler for FailingHandler {
+        async fn handle(&self, _event: &DomainEvent) -> crate::Result<()> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call <= self.failures {
+                return Err(crate::Error::network("Webhook unreachable"));
+            }
+            Ok(())
+        }
+
+        fn interested_in(&self) -> Vec<&str> {
+            vec!["*"]
+        }
+    }
+
+    fn test_config() -> EventsConfig {
+        EventsConfig {
+            retry_backoff_ms: 1,
+            ..EventsConfig::default()
+        }
+    }
+
+    fn build_started(build_id: &BuildId) -> DomainEvent {
+        DomainEvent::BuildStarted {
+            build_id: build_id.clone(),
+            agent_id: AgentId::new(),
+            started_at: Utc::now(),
+        }
+    }
+
+    fn build_completed(build_id: &BuildId) -> DomainEvent {
+        DomainEvent::BuildCompleted {
+            build_id: build_id.clone(),
+            status: BuildStatus::Success,
+            completed_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivers_only_interesting_events() {
+        let bus = EventBus::new(test_config());
+        let builds = Arc::new(RecordingHandler::new("builds", vec!["build.started"]));
+        let everything = Arc::new(RecordingHandler::new("everything", vec!["*"]));
+        bus.subscribe(builds.clone()).await.unwrap();
+        bus.subscribe(everything.clone()).await.unwrap();
+
+        bus.publish(build_started(&BuildId::new())).await.unwrap();
+        bus.publish(DomainEvent::PipelineEnabled {
+            pipeline_id: PipelineId::new(),
+            enabled_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+        bus.shutdown().await;
+
+        assert_eq!(builds.events.lock().await.len(), 1);
+        assert_eq!(everything.events.lock().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_succeeds() {
+        let bus = EventBus::new(test_config());
+        let handler = Arc::new(FailingHandler {
+            calls: AtomicU32::new(0),
+            failures: 2,
+        });
+        bus.subscribe(handler.clone()).await.unwrap();
+
+        bus.publish(build_started(&BuildId::new())).await.unwrap();
+        bus.shutdown().await;
+
+        assert_eq!(handler.calls.load(Ordering::SeqCst), 3);
+        assert!(bus.dead_letters().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_after_max_attempts() {
+        let bus = EventBus::new(test_config());
+        let handler = Arc::new(FailingHandler {
+            calls: AtomicU32::new(0),
+            failures: u32::MAX,
+        });
+        bus.subscribe(handler.clone()).await.unwrap();
+
+        bus.publish(build_started(&BuildId::new())).await.unwrap();
+        bus.shutdown().await;
+
+        let dead_letters = bus.take_dead_letters().await;
+        assert_eq!(dead_letters.len(), 1);
+        assert!(dead_letters[0].handler.ends_with("FailingHandler"));
+        assert_eq!(dead_letters[0].attempts, 3);
+        assert!(bus.dead_letters().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_preserves_order_per_aggregate() {
+        let bus = EventBus::new(EventsConfig {
+            queue_capacity: 2,
+            ..test_config()
+        });
+        let handler = Arc::new(RecordingHandler {
+            delay_ms: 1,
+            ..RecordingHandler::new("slow", vec!["*"])
+        });
+        bus.subscribe(handler.clone()).await.unwrap();
+
+        let build_ids: Vec<BuildId> = (0..5).map(|_| BuildId::new()).collect();
+        for build_id in &build_ids {
+            bus.publish(build_started(build_id)).await.unwrap();
+        }
+        for build_id in &build_ids {
+            bus.publish(build_completed(build_id)).await.unwrap();
+        }
+        bus.shutdown().await;
+
+        let events = handler.events.lock().await;
+        assert_eq!(events.len(), 10);
+        for build_id in &build_ids {
+            let types: Vec<&str> = events
+                .iter()
+                .filter(|e| e.aggregate_id() == build_id.to_string())
+                .map(DomainEvent::event_type)
+                .collect();
+            assert_eq!(types, vec!["build.started", "build.completed"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redeliver_to_single_handler() {
+        let bus = EventBus::new(test_config());
+        let first = Arc::new(RecordingHandler::new("first", vec!["build.started"]));
+        let second = Arc::new(RecordingHandler::new("second", vec!["*"]));
+        bus.subscribe(first.clone()).await.unwrap();
+        bus.subscribe(second.clone()).await.unwrap();
+
+        let build_id = BuildId::new();
+        assert_eq!(bus.handler_names().await, ["first", "second"]);
+        let queued = bus
+            .redeliver("first", vec![build_started(&build_id), build_completed(&build_id)])
+            .await
+            .unwrap();
+        assert!(bus.redeliver("unknown", vec![]).await.is_err());
+        bus.shutdown().await;
+
+        assert_eq!(queued, 1);
+        assert_eq!(first.events.lock().await.len(), 1);
+        assert!(second.events.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_after_shutdown_is_ignored() {
+        let bus = EventBus::new(test_config());
+        let handler = Arc::new(RecordingHandler::new("late", vec!["*"]));
+        bus.subscribe(handler.clone()).await.unwrap();
+        bus.shutdown().await;
+
+        assert!(bus.publish(build_started(&BuildId::new())).await.is_ok());
+        assert!(handler.events.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_duplicate_handler_names() {
+        let bus = EventBus::new(test_config());
+        bus.subscribe(Arc::new(RecordingHandler::new("audit", vec!["*"]))).await.unwrap();
+
+        let duplicate = bus.subscribe(Arc::new(RecordingHandler::new("audit", vec!["build.started"]))).await;
+        assert!(matches!(duplicate, Err(crate::Error::Conflict(_))));
+        assert_eq!(bus.handler_names().await, ["audit"]);
+        bus.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_backoff_is_capped() {
+        // Doubling would overflow a Duration long before the last attempt
+        let bus = EventBus::new(EventsConfig {
+            max_attempts: 80,
+            retry_backoff_ms: u64::MAX / 4,
+            max_retry_backoff_ms: 1,
+            ..test_config()
+        });
+        let handler = Arc::new(FailingHandler {
+            calls: AtomicU32::new(0),
+            failures: 79,
+        });
+        bus.subscribe(handler.clone()).await.unwrap();
+
+        bus.publish(build_started(&BuildId::new())).await.unwrap();
+        bus.shutdown().await;
+
+        assert_eq!(handler.calls.load(Ordering::SeqCst), 80);
+        assert!(bus.dead_letters().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_keeps_latest_dead_letters() {
+        let bus = EventBus::new(EventsConfig {
+            max_attempts: 1,
+            max_dead_letters: 2,
+            partitions: 1,
+            ..test_config()
+        });
+        let handler = Arc::new(FailingHandler {
+            calls: AtomicU32::new(0),
+            failures: u32::MAX,
+        });
+        bus.subscribe(handler.clone()).await.unwrap();
+
+        let build_ids: Vec<BuildId> = (0..3).map(|_| BuildId::new()).collect();
+        for build_id in &build_ids {
+            bus.publish(build_started(build_id)).await.unwrap();
+        }
+        bus.shutdown().await;
+
+        let kept: Vec<String> = bus.dead_letters().await.iter().map(|d| d.event.aggregate_id()).collect();
+        assert_eq!(kept, [build_ids[1].to_string(), build_ids[2].to_string()]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Publisher forwarding events to several publishers
+
+use crate::domain::events::{DomainEvent, EventPublisher};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Event publisher forwarding every event to a primary publisher, then to
+/// secondary ones on a best-effort basis
+///
+/// Only the primary's result is returned: by the time an event is published
+/// the change it describes is already saved, so a failing secondary, e.g. an
+/// unreachable Redis stream, is logged instead of failing the operation.
+pub struct FanoutEventPublisher {
+    primary: Arc<dyn EventPublisher>,
+    secondaries: Vec<Arc<dyn EventPublisher>>,
+}
+
+impl FanoutEventPublisher {
+    /// Create a new fan-out publisher
+    pub fn new(primary: Arc<dyn EventPublisher>, secondaries: Vec<Arc<dyn EventPublisher>>) -> Self {
+        Self { primary, secondaries }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for FanoutEventPublisher {
+    async fn publish(&self, event: DomainEvent) -> crate::Result<()> {
+        self.primary.publish(event.clone()).await?;
+        for publisher in &self.secondaries {
+            if let Err(e) = publisher.publish(event.clone()).await {
+                tracing::warn!("Secondary event publisher failed: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn publish_batch(&self, events: Vec<DomainEvent>) -> crate::Result<()> {
+        self.primary.publish_batch(events.clone()).await?;
+        for publisher in &self.secondaries {
+            if let Err(e) = publisher.publish_batch(events.clone()).await {
+                tracing::warn!("Secondary event publisher failed: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::events::InMemoryEventPublisher;
+    use crate::domain::value_objects::project_id::ProjectId;
+    use chrono::Utc;
+
+    struct FailingPublisher;
+
+    #[async_trait]
+    impl EventPublisher for FailingPublisher {
+        async fn publish(&self, _event: DomainEvent) -> crate::Result<()> {
+            Err(crate::Error::internal("unreachable"))
+        }
+
+        async fn publish_batch(&self, _events: Vec<DomainEvent>) -> crate::Result<()> {
+            Err(crate::Error::internal("unreachable"))
+        }
+    }
+
+    fn project_created() -> DomainEvent {
+        DomainEvent::ProjectCreated {
+            project_id: ProjectId::new(),
+            name: "web".to_string(),
+            repository_url: "https://git.example.com/web.git".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forwards_to_all_publishers() {
+        let first = Arc::new(InMemoryEventPublisher::new());
+        let second = Arc::new(InMemoryEventPublisher::new());
+        let fanout = FanoutEventPublisher::new(first.clone(), vec![second.clone()]);
+
+        fanout.publish(project_created()).await.unwrap();
+
+        assert_eq!(first.get_events().await.len(), 1);
+        assert_eq!(second.get_events().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failing_secondary_is_ignored() {
+        let primary = Arc::new(InMemoryEventPublisher::new());
+        let last = Arc::new(InMemoryEventPublisher::new());
+        let fanout = FanoutEventPublisher::new(primary.clone(), vec![Arc::new(FailingPublisher), last.clone()]);
+
+        fanout.publish(project_created()).await.unwrap();
+        fanout.publish_batch(vec![project_created(), project_created()]).await.unwrap();
+        assert_eq!(primary.get_events().await.len(), 3);
+        assert_eq!(last.get_events().await.len(), 3);
+
+        let failing = FanoutEventPublisher::new(Arc::new(FailingPublisher), vec![last.clone()]);
+        assert!(failing.publish(project_created()).await.is_err());
+        assert_eq!(last.get_events().await.len(), 3);
+    }
+}
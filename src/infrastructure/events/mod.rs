@@ -0,0 +1,9 @@
+//! Event delivery infrastructure
+
+pub mod bus;
+pub mod fanout;
+pub mod redis_stream;
+
+pub use bus::{DeadLetter, EventBus};
+pub use fanout::FanoutEventPublisher;
+pub use redis_stream::{RedisStreamPublisher, RedisStreamSubscriber};
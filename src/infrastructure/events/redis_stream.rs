@@ -0,0 +1,336 @@
+//! Redis Streams event backend
+//!
+//! Every server replica appends the events it publishes to a shared stream and reads
+//! the stream through its own consumer group, so each replica sees every event. Entries
+//! are tagged with the publishing replica's id; a replica skips its own entries because
+//! they were already delivered to its local event bus.
+
+use crate::config::RedisStreamConfig;
+use crate::domain::events::{DomainEvent, EventPublisher};
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::streams::{StreamId, StreamMaxlen, StreamReadOptions, StreamReadReply};
+use redis::AsyncCommands;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+const FIELD_TYPE: &str = "type";
+const FIELD_AGGREGATE_ID: &str = "aggregate_id";
+const FIELD_ORIGIN: &str = "origin";
+const FIELD_PAYLOAD: &str = "payload";
+
+/// Resolve the replica id from the configuration or the host name
+///
+/// The id names the replica's consumer, so it must stay the same across
+/// restarts: entries read but not acknowledged before a restart are only
+/// retried by a consumer with the same name.
+fn instance_id(config: &RedisStreamConfig) -> crate::Result<String> {
+    config
+        .instance_id
+        .clone()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| {
+            crate::Error::config("Set events.redis.instance_id: the host name is unknown")
+        })
+}
+
+/// Open a connection that reconnects automatically after failures
+async fn connect(config: &RedisStreamConfig) -> crate::Result<ConnectionManager> {
+    let client = redis::Client::open(config.url.as_str())
+        .map_err(|e| crate::Error::config(format!("Invalid Redis URL: {e}")))?;
+
+    Ok(client.get_connection_manager().await?)
+}
+
+/// Build the stream entry fields for an event
+fn encode_event(event: &DomainEvent, origin: &str) -> crate::Result<Vec<(&'static str, String)>> {
+    Ok(vec![
+        (FIELD_TYPE, event.event_type().to_string()),
+        (FIELD_AGGREGATE_ID, event.aggregate_id()),
+        (FIELD_ORIGIN, origin.to_string()),
+        (FIELD_PAYLOAD, serde_json::to_string(event)?),
+    ])
+}
+
+/// Decode the event carried by a stream entry
+fn decode_event(entry: &StreamId) -> crate::Result<DomainEvent> {
+    let payload: String = entry.get(FIELD_PAYLOAD).ok_or_else(|| {
+        crate::Error::serialization(format!("Stream entry {} has no payload", entry.id))
+    })?;
+
+    Ok(serde_json::from_str(&payload)?)
+}
+
+/// Event publisher appending events to a Redis stream
+pub struct RedisStreamPublisher {
+    connection: ConnectionManager,
+    stream: String,
+    origin: String,
+    max_len: Option<StreamMaxlen>,
+}
+
+impl RedisStreamPublisher {
+    /// Connect to Redis
+    pub async fn connect(config: &RedisStreamConfig) -> crate::Result<Self> {
+        let max_len = match (config.max_len, config.exact_trim) {
+            (0, _) => None,
+            (len, true) => Some(StreamMaxlen::Equals(len)),
+            (len, false) => Some(StreamMaxlen::Approx(len)),
+        };
+
+        Ok(Self {
+            connection: connect(config).await?,
+            stream: config.stream.clone(),
+            origin: instance_id(config)?,
+            max_len,
+        })
+    }
+
+    /// Get the replica id entries are tagged with
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    fn add_command(&self, event: &DomainEvent) -> crate::Result<redis::Cmd> {
+        let fields = encode_event(event, &self.origin)?;
+
+        Ok(match self.max_len {
+            Some(max_len) => redis::Cmd::xadd_maxlen(&self.stream, max_len, "*", &fields),
+            None => redis::Cmd::xadd(&self.stream, "*", &fields),
+        })
+    }
+}
+
+#[async_trait]
+impl EventPublisher for RedisStreamPublisher {
+    async fn publish(&self, event: DomainEvent) -> crate::Result<()> {
+        let command = self.add_command(&event)?;
+        let mut connection = self.connection.clone();
+        let _: String = command.query_async(&mut connection).await?;
+        Ok(())
+    }
+
+    async fn publish_batch(&self, events: Vec<DomainEvent>) -> crate::Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut pipeline = redis::pipe();
+        pipeline.atomic();
+        for event in &events {
+            pipeline.add_command(self.add_command(event)?).ignore();
+        }
+
+        let mut connection = self.connection.clone();
+        let () = pipeline.query_async(&mut connection).await?;
+        Ok(())
+    }
+}
+
+/// Consumer-group reader feeding stream events from other replicas into a local publisher
+pub struct RedisStreamSubscriber {
+    connection: ConnectionManager,
+    config: RedisStreamConfig,
+    group: String,
+    consumer: String,
+    sink: Arc<dyn EventPublisher>,
+    read_pending: bool,
+}
+
+impl RedisStreamSubscriber {
+    /// Connect to Redis and create the replica's consumer group if needed
+    pub async fn connect(
+        config: &RedisStreamConfig,
+        sink: Arc<dyn EventPublisher>,
+    ) -> crate::Result<Self> {
+        let consumer = instance_id(config)?;
+
+        let mut subscriber = Self {
+            connection: connect(config).await?,
+            config: config.clone(),
+            group: format!("ferrous-{consumer}"),
+            consumer,
+            sink,
+            read_pending: true,
+        };
+        subscriber.ensure_group().await?;
+
+        Ok(subscriber)
+    }
+
+    /// Get the consumer group name
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    /// Create the consumer group, starting at new entries
+    async fn ensure_group(&mut self) -> crate::Result<()> {
+        let result: redis::RedisResult<()> = self
+            .connection
+            .xgroup_create_mkstream(&self.config.stream, &self.group, "$")
+            .await;
+
+        match result {
+            Err(e) if e.code() != Some("BUSYGROUP") => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read and deliver one batch of entries, returning the number of events delivered
+    ///
+    /// Entries that were read but not acknowledged before (for example because the
+    /// process stopped or delivery failed) are retried before new entries are read.
+    pub async fn poll(&mut self) -> crate::Result<usize> {
+        let mut options = StreamReadOptions::default()
+            .group(&self.group, &self.consumer)
+            .count(self.config.batch_size.max(1));
+
+        let start = if self.read_pending {
+            "0"
+        } else {
+            // Saturate instead of truncating absurd block times on 32-bit targets
+            options = options.block(usize::try_from(self.config.block_ms).unwrap_or(usize::MAX));
+            ">"
+        };
+
+        let reply: Option<StreamReadReply> = self
+            .connection
+            .xread_options(&[&self.config.stream], &[start], &options)
+            .await?;
+
+        let entries: Vec<StreamId> = reply
+            .map(|r| r.keys.into_iter().flat_map(|k| k.ids).collect())
+            .unwrap_or_default();
+
+        if self.read_pending && entries.is_empty() {
+            self.read_pending = false;
+        }
+
+        let mut delivered = 0;
+        for entry in entries {
+            if self.deliver(&entry).await? {
+                delivered += 1;
+            }
+
+            let _: usize = self
+                .connection
+                .xack(&self.config.stream, &self.group, &[&entry.id])
+                .await?;
+        }
+
+        Ok(delivered)
+    }
+
+    /// Deliver an entry to the sink unless it was published by this replica
+    async fn deliver(&self, entry: &StreamId) -> crate::Result<bool> {
+        let origin: Option<String> = entry.get(FIELD_ORIGIN);
+        if origin.as_deref() == Some(self.consumer.as_str()) {
+            return Ok(false);
+        }
+
+        match decode_event(entry) {
+            Ok(event) => {
+                self.sink.publish(event).await?;
+                Ok(true)
+            }
+            Err(e) => {
+                // A malformed entry would block the group forever; drop it
+                tracing::error!("Dropping undecodable stream entry {}: {}", entry.id, e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Read until `shutdown` becomes true, recovering from connection failures
+    pub async fn run(mut self, mut shutdown: watch::Receiver<bool>) {
+        let reconnect_delay = Duration::from_millis(self.config.reconnect_delay_ms);
+
+        while !*shutdown.borrow() {
+            let result = tokio::select! {
+                result = self.poll() => result,
+                _ = shutdown.changed() => break,
+            };
+
+            if let Err(e) = result {
+                tracing::warn!("Redis stream read failed, retrying: {}", e);
+                self.read_pending = true;
+
+                tokio::select! {
+                    () = tokio::time::sleep(reconnect_delay) => {}
+                    _ = shutdown.changed() => break,
+                }
+
+                // The stream or group may be gone after a Redis restart
+                if let Err(e) = self.ensure_group().await {
+                    tracing::warn!("Could not recreate consumer group: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Run the subscriber on a background task
+    pub fn spawn(self, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(shutdown))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::pipeline_id::PipelineId;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn to_entry(fields: Vec<(&'static str, String)>) -> StreamId {
+        StreamId {
+            id: "1-0".to_string(),
+            map: fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), redis::Value::BulkString(v.into_bytes())))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let event = DomainEvent::PipelineEnabled {
+            pipeline_id: PipelineId::new(),
+            enabled_at: Utc::now(),
+        };
+
+        let fields = encode_event(&event, "replica-a").unwrap();
+        let entry = to_entry(fields);
+
+        assert_eq!(entry.get::<String>(FIELD_TYPE).unwrap(), "pipeline.enabled");
+        assert_eq!(entry.get::<String>(FIELD_ORIGIN).unwrap(), "replica-a");
+        assert_eq!(entry.get::<String>(FIELD_AGGREGATE_ID).unwrap(), event.aggregate_id());
+        assert_eq!(decode_event(&entry).unwrap(), event);
+    }
+
+    #[test]
+    fn test_decode_without_payload() {
+        let entry = to_entry(vec![(FIELD_TYPE, "build.started".to_string())]);
+        assert!(decode_event(&entry).is_err());
+    }
+
+    #[test]
+    fn test_instance_id_from_config() {
+        let config = RedisStreamConfig {
+            url: "redis://127.0.0.1/".to_string(),
+            stream: "events".to_string(),
+            instance_id: Some("replica-b".to_string()),
+            max_len: 0,
+            exact_trim: false,
+            batch_size: 10,
+            block_ms: 10,
+            reconnect_delay_ms: 10,
+        };
+
+        assert_eq!(instance_id(&config).unwrap(), "replica-b");
+    }
+}
@@ -0,0 +1,548 @@
+//! Artifact collection from job workspaces
+//!
+//! The files selected by a job's artifact settings are uploaded as they are
+//! when there is only one, and as a `.tar.zst` archive otherwise. Archives
+//! are streamed to the store while they are written, and unpacked while they
+//! are read when downstream jobs download them. Downloads are staged in the
+//! workspace and only moved into place once their checksum is verified.
+
+use super::JobContext;
+use crate::domain::entities::artifact::{Artifact, ArtifactType};
+use crate::domain::value_objects::pipeline_config::ArtifactConfig;
+use crate::domain::value_objects::ref_pattern::RefPattern;
+use crate::infrastructure::storage::{
+    ArtifactStore, StoredArtifact, artifact_key, check_checksum, copy_hashed, validate_key,
+};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use tokio_util::io::SyncIoBridge;
+use uuid::Uuid;
+
+/// Buffer between archive writing and uploading
+const PIPE_SIZE: usize = 64 * 1024;
+
+/// Collect and upload the artifact of a job
+///
+/// Returns `None` when no file matched.
+pub async fn collect(
+    store: &dyn ArtifactStore,
+    context: &JobContext,
+    job: &str,
+    config: &ArtifactConfig,
+) -> crate::Result<Option<Artifact>> {
+    config.validate()?;
+    let files = search(&context.workspace, &config.paths, &config.exclude).await?;
+
+    let (file_name, artifact_type, stored) = match files.as_slice() {
+        [] => {
+            tracing::warn!("No files of job {} matched artifact paths {:?}", job, config.paths);
+            return Ok(None);
+        }
+        [file] => {
+            let file_name = config
+                .name
+                .clone()
+                .unwrap_or_else(|| file.rsplit('/').next().unwrap_or(file).to_string());
+            let key = artifact_key(&context.project_id, &context.build_id, &format!("{job}/{file_name}"))?;
+            let contents = tokio::fs::File::open(context.workspace.join(file)).await?;
+            let stored = store.put(&key, Box::pin(contents), None).await?;
+            (file_name, ArtifactType::for_path(file), stored)
+        }
+        files => {
+            let file_name = format!("{}.tar.zst", config.name.as_deref().unwrap_or(job));
+            let key = artifact_key(&context.project_id, &context.build_id, &format!("{job}/{file_name}"))?;
+            let stored = upload_archive(store, &key, context.workspace.clone(), files.to_vec()).await?;
+            (file_name, archive_type(files), stored)
+        }
+    };
+
+    let mut artifact = Artifact::new(
+        context.build_id.clone(),
+        file_name,
+        stored.key,
+        stored.size,
+        stored.checksum,
+        artifact_type,
+    );
+    artifact.set_job(job.to_string());
+    artifact.set_files(files);
+    artifact.set_content_type(content_type(artifact.name()).to_string());
+    if let Some(days) = config.expire_in {
+        artifact.set_expiration(Utc::now() + Duration::days(days.into()));
+    }
+    Ok(Some(artifact))
+}
+
+/// Download an artifact into a workspace, restoring its files at the paths
+/// they were collected from
+///
+/// Fails if the downloaded contents do not match the artifact's checksum, in
+/// which case nothing is written to the workspace.
+pub async fn download(store: &dyn ArtifactStore, workspace: &Path, artifact: &Artifact) -> crate::Result<()> {
+    for file in artifact.files() {
+        validate_key(file)
+            .map_err(|_| crate::Error::validation(format!("Artifact file {file} is outside the workspace")))?;
+    }
+
+    let mut contents = store.get(artifact.path()).await?;
+    match artifact.files() {
+        [] => Err(crate::Error::validation(format!(
+            "Artifact {} does not list its files",
+            artifact.name()
+        ))),
+        [file] => {
+            write_verified(workspace, artifact.path(), artifact.checksum(), |staging| async move {
+                let path = staging.join(file);
+                tokio::fs::create_dir_all(path.parent().unwrap_or(&staging)).await?;
+                let mut output = tokio::fs::File::create(&path).await?;
+                let (_, checksum) = copy_hashed(&mut contents, &mut output, u64::MAX).await?;
+                Ok(checksum)
+            })
+            .await
+        }
+        _ => {
+            let reader = SyncIoBridge::new(contents);
+            write_verified(workspace, artifact.path(), artifact.checksum(), |staging| async move {
+                tokio::task::spawn_blocking(move || unpack_archive(&staging, reader))
+                    .await
+                    .map_err(|e| crate::Error::internal(format!("Artifact unpacking task failed: {e}")))?
+            })
+            .await
+        }
+    }
+}
+
+/// Write downloaded contents into a workspace once they match their checksum
+///
+/// `write` gets a staging directory inside the workspace and returns the
+/// checksum of what it wrote there. The files are only moved into place when
+/// the checksum matches; the staging directory is removed either way.
+pub(super) async fn write_verified<F, Fut>(workspace: &Path, key: &str, expected: &str, write: F) -> crate::Result<()>
+where
+    F: FnOnce(PathBuf) -> Fut,
+    Fut: Future<Output = crate::Result<String>>,
+{
+    let staging = workspace.join(format!(".ferrous-staging-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&staging).await?;
+
+    let result = async {
+        let checksum = write(staging.clone()).await?;
+        check_checksum(key, expected, &checksum)?;
+        move_into(&staging, workspace).await
+    }
+    .await;
+
+    if let Err(e) = tokio::fs::remove_dir_all(&staging).await {
+        tracing::warn!("Could not remove staging directory {}: {}", staging.display(), e);
+    }
+    result
+}
+
+/// Move the files of a staging directory to the same paths in a workspace
+async fn move_into(staging: &Path, workspace: &Path) -> crate::Result<()> {
+    let mut directories = vec![PathBuf::new()];
+    while let Some(directory) = directories.pop() {
+        let mut entries = tokio::fs::read_dir(staging.join(&directory)).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file = directory.join(entry.file_name());
+            if entry.file_type().await?.is_dir() {
+                directories.push(file);
+            } else {
+                tokio::fs::rename(entry.path(), output_path(workspace, &file).await?).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prepare the path a downloaded file is moved to, like `unpack_in` does
+/// for archive entries
+///
+/// Fails if its directory resolves outside the workspace. An existing file or
+/// symbolic link at the path is removed rather than written through.
+async fn output_path(workspace: &Path, file: &Path) -> crate::Result<PathBuf> {
+    let path = workspace.join(file);
+    let parent = path.parent().unwrap_or(workspace);
+    tokio::fs::create_dir_all(parent).await?;
+
+    let root = tokio::fs::canonicalize(workspace).await?;
+    if !tokio::fs::canonicalize(parent).await?.starts_with(&root) {
+        return Err(crate::Error::validation(format!(
+            "Artifact file {} is outside the workspace",
+            file.display()
+        )));
+    }
+
+    match tokio::fs::symlink_metadata(&path).await {
+        Ok(metadata) if !metadata.is_dir() => tokio::fs::remove_file(&path).await?,
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    Ok(path)
+}
+
+/// Find the files of a workspace matching path patterns on a blocking task
+pub(super) async fn search(workspace: &Path, paths: &[String], exclude: &[String]) -> crate::Result<Vec<String>> {
+    let workspace = workspace.to_path_buf();
+    let paths = paths.to_vec();
+    let exclude = exclude.to_vec();
+    tokio::task::spawn_blocking(move || find_files(&workspace, &paths, &exclude))
+        .await
+        .map_err(|e| crate::Error::internal(format!("Workspace search task failed: {e}")))?
+}
+
+/// Find the files of a workspace matching path patterns and none of the
+/// `exclude` patterns, as sorted paths relative to the workspace
+///
+/// Patterns matching a directory select the files in it. The `.git`
+/// directory is never selected. Selected symbolic links must point inside the
+/// workspace.
+pub fn find_files(workspace: &Path, paths: &[String], exclude: &[String]) -> crate::Result<Vec<String>> {
+    let include = parse_patterns(paths)?;
+    let exclude = parse_patterns(exclude)?;
+    let root = workspace.canonicalize()?;
+
+    let mut files = Vec::new();
+    let mut directories = vec![String::new()];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(root.join(&directory))? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let path = if directory.is_empty() { name } else { format!("{directory}/{name}") };
+            if path == ".git" {
+                continue;
+            }
+
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                directories.push(path);
+                continue;
+            }
+            if !selected(&include, &path) || selected(&exclude, &path) {
+                continue;
+            }
+            if file_type.is_symlink() && !root.join(&path).canonicalize().is_ok_and(|target| target.starts_with(&root)) {
+                return Err(crate::Error::validation(format!("{path} links outside the workspace")));
+            }
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn parse_patterns(patterns: &[String]) -> crate::Result<Vec<RefPattern>> {
+    patterns
+        .iter()
+        .map(|pattern| RefPattern::parse(pattern.trim_end_matches('/')))
+        .collect()
+}
+
+/// Check if a path or one of its directories matches a pattern
+fn selected(patterns: &[RefPattern], path: &str) -> bool {
+    let mut candidate = path;
+    loop {
+        if patterns.iter().any(|pattern| pattern.is_match(candidate)) {
+            return true;
+        }
+        match candidate.rsplit_once('/') {
+            Some((parent, _)) => candidate = parent,
+            None => return false,
+        }
+    }
+}
+
+/// Stream files into a `.tar.zst` archive uploaded under a key
+pub(super) async fn upload_archive(
+    store: &dyn ArtifactStore,
+    key: &str,
+    workspace: std::path::PathBuf,
+    files: Vec<String>,
+) -> crate::Result<StoredArtifact> {
+    let (reader, writer) = tokio::io::duplex(PIPE_SIZE);
+    let writer = SyncIoBridge::new(writer);
+    let writing = tokio::task::spawn_blocking(move || write_archive(&workspace, &files, writer));
+
+    let stored = store.put(key, Box::pin(reader), None).await;
+    let written = writing
+        .await
+        .map_err(|e| crate::Error::internal(format!("Artifact archive task failed: {e}")))?;
+
+    match (stored, written) {
+        (Ok(stored), Ok(())) => Ok(stored),
+        (Ok(_), Err(e)) => {
+            // The upload ended early with a truncated archive
+            if let Err(e) = store.delete(key).await {
+                tracing::warn!("Could not delete incomplete artifact {}: {}", key, e);
+            }
+            Err(e)
+        }
+        (Err(e), _) => Err(e),
+    }
+}
+
+/// Write files of a workspace as a zstd compressed tar archive
+pub fn write_archive<W: Write>(workspace: &Path, files: &[String], writer: W) -> crate::Result<()> {
+    let mut builder = tar::Builder::new(zstd::Encoder::new(writer, 0)?);
+    builder.follow_symlinks(false);
+    for file in files {
+        builder.append_path_with_name(workspace.join(file), file)?;
+    }
+    builder.into_inner()?.finish()?.flush()?;
+    Ok(())
+}
+
+/// Unpack a zstd compressed tar archive into a workspace, returning the hex
+/// encoded SHA256 of the compressed archive
+pub fn unpack_archive<R: Read>(workspace: &Path, reader: R) -> crate::Result<String> {
+    let mut reader = HashingReader {
+        inner: reader,
+        hasher: Sha256::new(),
+    };
+
+    let mut archive = tar::Archive::new(zstd::Decoder::new(&mut reader)?);
+    archive.set_overwrite(true);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.unpack_in(workspace)? {
+            return Err(crate::Error::validation(format!(
+                "Archive entry {} is outside the workspace",
+                entry.path()?.display()
+            )));
+        }
+    }
+    drop(archive);
+
+    // Hash whatever the decoder left unread
+    io::copy(&mut reader, &mut io::sink())?;
+    Ok(hex::encode(reader.hasher.finalize()))
+}
+
+/// Reader computing the SHA256 of what is read through it
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buffer)?;
+        self.hasher.update(&buffer[..read]);
+        Ok(read)
+    }
+}
+
+/// Get the type of an archive from the files in it
+///
+/// Files all of one type, e.g. test reports, give the archive their type.
+fn archive_type(files: &[String]) -> ArtifactType {
+    let mut types = files.iter().map(|file| ArtifactType::for_path(file));
+    let first = types.next().unwrap_or(ArtifactType::Archive);
+    if types.all(|artifact_type| artifact_type == first) {
+        first
+    } else {
+        ArtifactType::Archive
+    }
+}
+
+/// Guess the content type of an artifact from its file name
+fn content_type(file_name: &str) -> &'static str {
+    let extension = file_name.rsplit_once('.').map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("zst") => "application/zstd",
+        Some("zip") => "application/zip",
+        Some("gz" | "tgz") => "application/gzip",
+        Some("tar") => "application/x-tar",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("html" | "htm") => "text/html",
+        Some("txt" | "log" | "info") => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{build_id::BuildId, project_id::ProjectId};
+    use crate::infrastructure::storage::local::LocalArtifactStore;
+    use tokio::io::AsyncReadExt;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn config(paths: &[&str], exclude: &[&str]) -> ArtifactConfig {
+        ArtifactConfig {
+            paths: strings(paths),
+            exclude: strings(exclude),
+            name: None,
+            expire_in: None,
+        }
+    }
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [".git/config", "dist/app", "dist/lib/core.so", "dist/app.debug", "reports/TEST-unit.xml", "README.md"] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_find_files() {
+        let dir = workspace();
+
+        let find = |paths: &[&str], exclude: &[&str]| find_files(dir.path(), &strings(paths), &strings(exclude));
+
+        assert_eq!(find(&["dist/"], &["**/*.debug"]).unwrap(), ["dist/app", "dist/lib/core.so"]);
+        assert_eq!(find(&["**/*.xml"], &[]).unwrap(), ["reports/TEST-unit.xml"]);
+        assert_eq!(find(&["**"], &["dist"]).unwrap(), ["README.md", "reports/TEST-unit.xml"]);
+        assert!(find(&["missing/*"], &[]).unwrap().is_empty());
+
+        #[cfg(unix)]
+        {
+            let outside = tempfile::tempdir().unwrap();
+            std::os::unix::fs::symlink(outside.path(), dir.path().join("dist/escape")).unwrap();
+            assert!(find(&["dist"], &[]).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collect() {
+        let dir = workspace();
+        let storage = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(storage.path(), 1024 * 1024);
+        let context = JobContext {
+            project_id: ProjectId::new(),
+            build_id: BuildId::new(),
+            git_ref: "refs/heads/main".to_string(),
+            default_ref: "refs/heads/main".to_string(),
+            workspace: dir.path().to_path_buf(),
+        };
+
+        // A single file is uploaded as it is
+        let mut reports = config(&["reports"], &[]);
+        reports.expire_in = Some(7);
+        let artifact = collect(&store, &context, "test", &reports).await.unwrap().unwrap();
+        assert_eq!(artifact.name(), "TEST-unit.xml");
+        assert_eq!(artifact.job(), Some("test"));
+        assert_eq!(artifact.files(), ["reports/TEST-unit.xml"]);
+        assert_eq!(artifact.artifact_type(), &ArtifactType::TestResults);
+        assert_eq!(artifact.content_type(), "application/xml");
+        assert!(artifact.expires_at().is_some_and(|expires_at| expires_at > Utc::now() + Duration::days(6)));
+        assert_eq!(artifact.path(), format!("{}/{}/test/TEST-unit.xml", context.project_id, context.build_id));
+        store.verify(artifact.path(), artifact.checksum()).await.unwrap();
+
+        // Several files are archived
+        let mut dist = config(&["dist"], &[]);
+        dist.name = Some("binaries".to_string());
+        let artifact = collect(&store, &context, "build", &dist).await.unwrap().unwrap();
+        assert_eq!(artifact.name(), "binaries.tar.zst");
+        assert_eq!(artifact.artifact_type(), &ArtifactType::BuildOutput);
+        assert_eq!(artifact.content_type(), "application/zstd");
+        assert_eq!(artifact.expires_at(), None);
+
+        let mut compressed = Vec::new();
+        store.get(artifact.path()).await.unwrap().read_to_end(&mut compressed).await.unwrap();
+        assert_eq!(compressed.len() as u64, artifact.size());
+        let mut archive = tar::Archive::new(zstd::Decoder::new(compressed.as_slice()).unwrap());
+        let entries: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(entries, ["dist/app", "dist/app.debug", "dist/lib/core.so"]);
+
+        assert!(collect(&store, &context, "build", &config(&["missing"], &[])).await.unwrap().is_none());
+        assert!(collect(&store, &context, "build", &config(&["../*"], &[])).await.is_err());
+
+        // Archives over the size limit leave nothing behind
+        let small = LocalArtifactStore::new(storage.path(), 16);
+        assert!(collect(&small, &context, "small", &dist).await.is_err());
+        let key = artifact_key(&context.project_id, &context.build_id, "small/binaries.tar.zst").unwrap();
+        assert!(!small.exists(&key).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_download_leaves_nothing_on_checksum_mismatch() {
+        let dir = workspace();
+        let storage = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(storage.path(), 1024 * 1024);
+        let context = JobContext {
+            project_id: ProjectId::new(),
+            build_id: BuildId::new(),
+            git_ref: "refs/heads/main".to_string(),
+            default_ref: "refs/heads/main".to_string(),
+            workspace: dir.path().to_path_buf(),
+        };
+
+        for paths in [&["reports"][..], &["dist"][..]] {
+            let collected = collect(&store, &context, "test", &config(paths, &[])).await.unwrap().unwrap();
+            let mut tampered = Artifact::new(
+                context.build_id.clone(),
+                collected.name().to_string(),
+                collected.path().to_string(),
+                collected.size(),
+                "0".repeat(64),
+                collected.artifact_type().clone(),
+            );
+            tampered.set_files(collected.files().to_vec());
+
+            let target = tempfile::tempdir().unwrap();
+            let result = download(&store, target.path(), &tampered).await;
+            assert!(result.unwrap_err().to_string().contains("Checksum mismatch"));
+            assert_eq!(fs::read_dir(target.path()).unwrap().count(), 0);
+
+            download(&store, target.path(), &collected).await.unwrap();
+            let files: Vec<String> = fs::read_dir(target.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            assert_eq!(files, [paths[0]]);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_download_does_not_follow_symlinks() {
+        let dir = workspace();
+        let storage = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(storage.path(), 1024 * 1024);
+        let context = JobContext {
+            project_id: ProjectId::new(),
+            build_id: BuildId::new(),
+            git_ref: "refs/heads/main".to_string(),
+            default_ref: "refs/heads/main".to_string(),
+            workspace: dir.path().to_path_buf(),
+        };
+        let artifact = collect(&store, &context, "test", &config(&["reports"], &[])).await.unwrap().unwrap();
+
+        // A link in place of the file is replaced, leaving its target alone
+        let target = tempfile::tempdir().unwrap();
+        let other = workspace();
+        fs::write(target.path().join("secret"), "secret").unwrap();
+        fs::remove_file(other.path().join("reports/TEST-unit.xml")).unwrap();
+        std::os::unix::fs::symlink(target.path().join("secret"), other.path().join("reports/TEST-unit.xml")).unwrap();
+        download(&store, other.path(), &artifact).await.unwrap();
+        assert_eq!(fs::read_to_string(target.path().join("secret")).unwrap(), "secret");
+        assert!(!fs::symlink_metadata(other.path().join("reports/TEST-unit.xml")).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(other.path().join("reports/TEST-unit.xml")).unwrap(), "reports/TEST-unit.xml");
+
+        // Directories linking outside the workspace are refused
+        fs::remove_dir_all(other.path().join("reports")).unwrap();
+        std::os::unix::fs::symlink(target.path(), other.path().join("reports")).unwrap();
+        assert!(download(&store, other.path(), &artifact).await.is_err());
+        assert!(!target.path().join("TEST-unit.xml").exists());
+    }
+}
@@ -0,0 +1,321 @@
+//! Dependency caches of jobs
+//!
+//! Caches are `.tar.zst` archives of workspace paths, saved per project and
+//! ref under their key. Keys are templated, e.g. `cargo-${{ hashFiles('Cargo.lock') }}`,
+//! so a cache is only reused while the files it depends on are unchanged.
+//! Saved caches are never replaced; a changed cache gets a new key. Caches
+//! not restored for a while are evicted by the
+//! [`CacheSweeper`](crate::infrastructure::storage::retention::CacheSweeper).
+//!
+//! When no cache has the key, the newest cache whose key starts with one of
+//! the restore keys is restored instead, in the order they are listed.
+//!
+//! A build only saves caches for its own ref, and restores them from its own
+//! ref, falling back to the project's default branch. Builds of other
+//! branches or pull requests can thus never replace what the default branch
+//! restores.
+
+use super::JobContext;
+use super::artifacts::{search, unpack_archive, upload_archive, write_verified};
+use crate::domain::repositories::cache::{CacheEntry, CacheRepository};
+use crate::domain::value_objects::pipeline_config::{self, CacheConfig};
+use crate::infrastructure::storage::{ArtifactStore, copy_hashed};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tokio_util::io::SyncIoBridge;
+
+/// Restores and saves the dependency caches of jobs
+pub struct JobCache {
+    store: Arc<dyn ArtifactStore>,
+    entries: Arc<dyn CacheRepository>,
+}
+
+impl JobCache {
+    /// Create job caches keeping archives in a store
+    pub fn new(store: Arc<dyn ArtifactStore>, entries: Arc<dyn CacheRepository>) -> Self {
+        Self { store, entries }
+    }
+
+    /// Restore a job's cache into its workspace, returning the key of the
+    /// cache restored, if any
+    pub async fn restore(&self, context: &JobContext, config: &CacheConfig) -> crate::Result<Option<String>> {
+        let Some(mut entry) = self.find(context, config).await? else {
+            tracing::info!("No cache found for key {}", config.key);
+            return Ok(None);
+        };
+
+        let reader = SyncIoBridge::new(self.store.get(&entry.path).await?);
+        write_verified(&context.workspace, &entry.path, &entry.checksum, |staging| async move {
+            tokio::task::spawn_blocking(move || unpack_archive(&staging, reader))
+                .await
+                .map_err(|e| crate::Error::internal(format!("Cache unpacking task failed: {e}")))?
+        })
+        .await?;
+
+        entry.last_used_at = Utc::now();
+        self.entries.save(&entry).await?;
+
+        tracing::info!("Restored cache {} ({} bytes)", entry.key, entry.size);
+        Ok(Some(entry.key))
+    }
+
+    /// Save a job's cache from its workspace, unless a cache with its key
+    /// already exists or none of its paths exist
+    pub async fn save(&self, context: &JobContext, config: &CacheConfig) -> crate::Result<Option<CacheEntry>> {
+        let key = render_key(&context.workspace, &config.key).await?;
+        if self.entries.find(&context.project_id, &context.git_ref, &key).await?.is_some() {
+            tracing::debug!("Cache {} already exists", key);
+            return Ok(None);
+        }
+
+        let files = search(&context.workspace, &config.paths, &[]).await?;
+        if files.is_empty() {
+            tracing::warn!("No files matched cache paths {:?}", config.paths);
+            return Ok(None);
+        }
+
+        let path = format!("{}/{}/{}.tar.zst", context.project_id, context.git_ref, key);
+        let stored = upload_archive(self.store.as_ref(), &path, context.workspace.clone(), files).await?;
+        let now = Utc::now();
+        let entry = CacheEntry {
+            project_id: context.project_id.clone(),
+            git_ref: context.git_ref.clone(),
+            key,
+            path,
+            size: stored.size,
+            checksum: stored.checksum,
+            created_at: now,
+            last_used_at: now,
+        };
+        self.entries.save(&entry).await?;
+
+        tracing::info!("Saved cache {} ({} bytes)", entry.key, entry.size);
+        Ok(Some(entry))
+    }
+
+    /// Find the cache with the key of a job, falling back to its restore keys,
+    /// then to the caches of the default branch
+    async fn find(&self, context: &JobContext, config: &CacheConfig) -> crate::Result<Option<CacheEntry>> {
+        let key = render_key(&context.workspace, &config.key).await?;
+        let mut prefixes = Vec::with_capacity(config.restore_keys.len());
+        for prefix in &config.restore_keys {
+            prefixes.push(render_key(&context.workspace, prefix).await?);
+        }
+
+        let mut refs = vec![&context.git_ref];
+        if context.default_ref != context.git_ref {
+            refs.push(&context.default_ref);
+        }
+
+        for git_ref in refs {
+            if let Some(entry) = self.entries.find(&context.project_id, git_ref, &key).await? {
+                return Ok(Some(entry));
+            }
+
+            for prefix in &prefixes {
+                let newest = self.entries.find_by_prefix(&context.project_id, git_ref, prefix).await?.into_iter().next();
+                if newest.is_some() {
+                    return Ok(newest);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Expand the `hashFiles` expressions of a cache key
+///
+/// `hashFiles` hashes the hex encoded SHA256 of every matching file, in path
+/// order, and is empty when no file matches.
+pub async fn render_key(workspace: &Path, template: &str) -> crate::Result<String> {
+    let mut hashes = Vec::new();
+    pipeline_config::expand_expressions(template, |expression| {
+        let patterns = pipeline_config::hash_files_patterns(expression)?.ok_or_else(|| {
+            crate::Error::validation(format!("Unsupported expression in cache key: '{expression}'"))
+        })?;
+        hashes.push(patterns);
+        Ok(None)
+    })?;
+
+    let mut replacements = Vec::with_capacity(hashes.len());
+    for patterns in hashes {
+        replacements.push(hash_files(workspace, &patterns).await?);
+    }
+
+    let mut replacements = replacements.into_iter();
+    pipeline_config::expand_expressions(template, |_| Ok(replacements.next()))
+}
+
+/// Hash the files of a workspace matching patterns
+async fn hash_files(workspace: &Path, patterns: &[String]) -> crate::Result<String> {
+    let files = search(workspace, patterns, &[]).await?;
+    if files.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        let mut contents = tokio::fs::File::open(workspace.join(&file)).await?;
+        let (_, checksum) = copy_hashed(&mut contents, &mut tokio::io::sink(), u64::MAX).await?;
+        hasher.update(checksum);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{build_id::BuildId, pipeline_config::CachePolicy, project_id::ProjectId};
+    use crate::infrastructure::repositories::in_memory::InMemoryCacheRepository;
+    use crate::infrastructure::storage::local::LocalArtifactStore;
+    use std::fs;
+
+    fn workspace(project_id: &ProjectId, lockfile: &str) -> (JobContext, tempfile::TempDir) {
+        workspace_on(project_id, "refs/heads/main", lockfile)
+    }
+
+    fn workspace_on(project_id: &ProjectId, git_ref: &str, lockfile: &str) -> (JobContext, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.lock"), lockfile).unwrap();
+        let context = JobContext {
+            project_id: project_id.clone(),
+            build_id: BuildId::new(),
+            git_ref: git_ref.to_string(),
+            default_ref: "refs/heads/main".to_string(),
+            workspace: dir.path().to_path_buf(),
+        };
+        (context, dir)
+    }
+
+    #[tokio::test]
+    async fn test_render_key() {
+        let (context, _dir) = workspace(&ProjectId::new(), "lock");
+        let key = render_key(&context.workspace, "cargo-${{ hashFiles('Cargo.lock') }}").await.unwrap();
+        let expected = hex::encode(Sha256::digest(hex::encode(Sha256::digest(b"lock"))));
+        assert_eq!(key, format!("cargo-{expected}"));
+
+        assert_eq!(render_key(&context.workspace, "npm-${{ hashFiles('package-lock.json') }}").await.unwrap(), "npm-");
+        assert!(render_key(&context.workspace, "${{ secrets.TOKEN }}").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restore_and_save() {
+        let storage = tempfile::tempdir().unwrap();
+        let entries = Arc::new(InMemoryCacheRepository::new());
+        let cache = JobCache::new(Arc::new(LocalArtifactStore::new(storage.path(), 1024 * 1024)), entries.clone());
+        let config = CacheConfig {
+            key: "cargo-${{ hashFiles('Cargo.lock') }}".to_string(),
+            restore_keys: vec!["cargo-".to_string()],
+            paths: vec!["target".to_string()],
+            policy: CachePolicy::PullPush,
+        };
+        let project_id = ProjectId::new();
+
+        // Nothing to restore yet, and nothing to save without the paths
+        let (first, first_dir) = workspace(&project_id, "v1");
+        assert_eq!(cache.restore(&first, &config).await.unwrap(), None);
+        assert!(cache.save(&first, &config).await.unwrap().is_none());
+
+        fs::create_dir_all(first_dir.path().join("target/debug")).unwrap();
+        fs::write(first_dir.path().join("target/debug/app"), "v1 build").unwrap();
+        let saved = cache.save(&first, &config).await.unwrap().unwrap();
+        assert!(cache.save(&first, &config).await.unwrap().is_none());
+
+        // Same key
+        let (second, second_dir) = workspace(&project_id, "v1");
+        assert_eq!(cache.restore(&second, &config).await.unwrap(), Some(saved.key.clone()));
+        assert_eq!(fs::read_to_string(second_dir.path().join("target/debug/app")).unwrap(), "v1 build");
+        let restored = entries.find(&project_id, "refs/heads/main", &saved.key).await.unwrap().unwrap();
+        assert_eq!(restored.created_at, saved.created_at);
+        assert!(restored.last_used_at > saved.last_used_at);
+
+        // Changed lockfile: restored from the prefix, saved under a new key
+        let (third, third_dir) = workspace(&project_id, "v2");
+        assert_eq!(cache.restore(&third, &config).await.unwrap(), Some(saved.key.clone()));
+        fs::write(third_dir.path().join("target/debug/app"), "v2 build").unwrap();
+        let updated = cache.save(&third, &config).await.unwrap().unwrap();
+        assert_ne!(updated.key, saved.key);
+
+        // The newest cache matching the prefix wins
+        let (fourth, fourth_dir) = workspace(&project_id, "v3");
+        assert_eq!(cache.restore(&fourth, &config).await.unwrap(), Some(updated.key));
+        assert_eq!(fs::read_to_string(fourth_dir.path().join("target/debug/app")).unwrap(), "v2 build");
+
+        // Caches are per project
+        let (other, _other_dir) = workspace(&ProjectId::new(), "v1");
+        assert_eq!(cache.restore(&other, &config).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_caches_are_scoped_by_ref() {
+        let storage = tempfile::tempdir().unwrap();
+        let entries = Arc::new(InMemoryCacheRepository::new());
+        let cache = JobCache::new(Arc::new(LocalArtifactStore::new(storage.path(), 1024 * 1024)), entries.clone());
+        let config = CacheConfig {
+            key: "cargo-${{ hashFiles('Cargo.lock') }}".to_string(),
+            restore_keys: vec!["cargo-".to_string()],
+            paths: vec!["target".to_string()],
+            policy: CachePolicy::PullPush,
+        };
+        let project_id = ProjectId::new();
+
+        // A pull request saving a cache does not reach the default branch
+        let (pull, pull_dir) = workspace_on(&project_id, "refs/pull/7/head", "v1");
+        fs::create_dir_all(pull_dir.path().join("target")).unwrap();
+        fs::write(pull_dir.path().join("target/app"), "untrusted").unwrap();
+        let untrusted = cache.save(&pull, &config).await.unwrap().unwrap();
+        assert_eq!(untrusted.path, format!("{project_id}/refs/pull/7/head/{}.tar.zst", untrusted.key));
+        let (main, main_dir) = workspace(&project_id, "v1");
+        assert_eq!(cache.restore(&main, &config).await.unwrap(), None);
+
+        // The default branch saves its own cache under the same key
+        fs::create_dir_all(main_dir.path().join("target")).unwrap();
+        fs::write(main_dir.path().join("target/app"), "trusted").unwrap();
+        let trusted = cache.save(&main, &config).await.unwrap().unwrap();
+        assert_eq!(trusted.key, untrusted.key);
+        let (main, main_dir) = workspace(&project_id, "v1");
+        cache.restore(&main, &config).await.unwrap();
+        assert_eq!(fs::read_to_string(main_dir.path().join("target/app")).unwrap(), "trusted");
+
+        // Other refs prefer their own caches, then fall back to the default
+        // branch's without being able to save there
+        let (pull, pull_dir) = workspace_on(&project_id, "refs/pull/7/head", "v1");
+        cache.restore(&pull, &config).await.unwrap();
+        assert_eq!(fs::read_to_string(pull_dir.path().join("target/app")).unwrap(), "untrusted");
+        let (feature, feature_dir) = workspace_on(&project_id, "refs/heads/feature", "v2");
+        assert_eq!(cache.restore(&feature, &config).await.unwrap(), Some(trusted.key.clone()));
+        assert_eq!(fs::read_to_string(feature_dir.path().join("target/app")).unwrap(), "trusted");
+        fs::write(feature_dir.path().join("target/app"), "feature").unwrap();
+        let saved = cache.save(&feature, &config).await.unwrap().unwrap();
+        assert_eq!(saved.git_ref, "refs/heads/feature");
+        let (main, _main_dir) = workspace(&project_id, "v2");
+        assert_eq!(cache.restore(&main, &config).await.unwrap(), Some(trusted.key));
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_cache_is_not_restored() {
+        let storage = tempfile::tempdir().unwrap();
+        let entries = Arc::new(InMemoryCacheRepository::new());
+        let cache = JobCache::new(Arc::new(LocalArtifactStore::new(storage.path(), 1024 * 1024)), entries.clone());
+        let config = CacheConfig {
+            key: "cargo".to_string(),
+            restore_keys: Vec::new(),
+            paths: vec!["target".to_string()],
+            policy: CachePolicy::PullPush,
+        };
+        let project_id = ProjectId::new();
+
+        let (first, first_dir) = workspace(&project_id, "v1");
+        fs::create_dir_all(first_dir.path().join("target")).unwrap();
+        fs::write(first_dir.path().join("target/app"), "build").unwrap();
+        let saved = cache.save(&first, &config).await.unwrap().unwrap();
+        entries.save(&CacheEntry { checksum: "0".repeat(64), ..saved }).await.unwrap();
+
+        let (second, second_dir) = workspace(&project_id, "v1");
+        assert!(cache.restore(&second, &config).await.is_err());
+        let files: Vec<_> = fs::read_dir(second_dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(files, ["Cargo.lock"]);
+    }
+}
@@ -0,0 +1,323 @@
+//! Steps run around the commands of a job
+//!
+//! Agents run the commands of a job in its workspace. Before and after them,
+//! the executor prepares the workspace and environment and keeps what the job
+//! produced.
+
+pub mod artifacts;
+pub mod cache;
+
+use self::cache::JobCache;
+use crate::domain::entities::{artifact::Artifact, job::Job as JobEntity, pipeline::Pipeline};
+use crate::domain::repositories::artifact::ArtifactRepository;
+use crate::domain::services::{
+    log_masking::LogMasker,
+    secret::{JobEnvironment, SecretService},
+};
+use crate::domain::value_objects::{build_id::BuildId, pipeline_config::Job, project_id::ProjectId};
+use crate::infrastructure::storage::ArtifactStore;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The build and workspace a job runs in
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    /// Project of the build
+    pub project_id: ProjectId,
+    /// Build the job is part of
+    pub build_id: BuildId,
+    /// Full name of the ref the build runs on, e.g. `refs/heads/feature`
+    pub git_ref: String,
+    /// Full name of the project's default branch, whose caches every ref may
+    /// restore
+    pub default_ref: String,
+    /// Directory the job runs in
+    pub workspace: PathBuf,
+}
+
+/// A job ready to run its commands
+///
+/// Its environment holds decrypted secrets, so its debug output hides the
+/// variable values, and the output of its commands must go through
+/// `append_logs` so the secrets are masked before being stored.
+pub struct JobRun {
+    environment: JobEnvironment,
+    masker: LogMasker,
+}
+
+impl JobRun {
+    fn new(environment: JobEnvironment) -> Self {
+        let masker = environment.log_masker();
+        Self { environment, masker }
+    }
+
+    /// Get the environment variables the commands run with
+    pub fn environment(&self) -> &HashMap<String, String> {
+        self.environment.variables()
+    }
+
+    /// Mask secrets in a chunk of the commands' output and append it to the
+    /// job's logs
+    ///
+    /// The end of a chunk may be held back until the next one, in case it
+    /// starts a secret; call `finish_logs` once the commands are done.
+    pub fn append_logs(&mut self, job: &mut JobEntity, chunk: &str) {
+        let masked = self.masker.mask(chunk);
+        if !masked.is_empty() {
+            job.append_logs(masked);
+        }
+    }
+
+    /// Append the output held back by `append_logs` to the job's logs
+    pub fn finish_logs(&mut self, job: &mut JobEntity) {
+        let masked = self.masker.finish();
+        if !masked.is_empty() {
+            job.append_logs(masked);
+        }
+    }
+}
+
+impl fmt::Debug for JobRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobRun")
+            .field("environment", &self.environment)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs the steps around the commands of jobs
+pub struct JobExecutor {
+    store: Arc<dyn ArtifactStore>,
+    artifacts: Arc<dyn ArtifactRepository>,
+    secrets: Arc<SecretService>,
+    cache: Option<JobCache>,
+}
+
+impl JobExecutor {
+    /// Create an executor keeping artifacts in a store and resolving the
+    /// secrets of job environments
+    pub fn new(
+        store: Arc<dyn ArtifactStore>,
+        artifacts: Arc<dyn ArtifactRepository>,
+        secrets: Arc<SecretService>,
+    ) -> Self {
+        Self {
+            store,
+            artifacts,
+            secrets,
+            cache: None,
+        }
+    }
+
+    /// Restore and save the dependency caches of jobs
+    pub fn with_cache(mut self, cache: JobCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Run the steps before the commands of a job, returning what its
+    /// commands run with
+    ///
+    /// The job's environment is resolved first, so a job referencing an
+    /// unknown secret fails right away. Its cache is restored, then the
+    /// artifacts of the jobs it depends on are downloaded into the workspace.
+    /// The job fails if one of them saved no artifact, or if the artifact
+    /// expired or is missing from the store. Caches that cannot be restored
+    /// only slow the job down.
+    pub async fn before_job(&self, context: &JobContext, pipeline: &Pipeline, job: &Job) -> crate::Result<JobRun> {
+        let run = JobRun::new(self.secrets.job_environment(pipeline, job).await?);
+
+        if let (Some(cache), Some(config)) = (&self.cache, &job.cache) {
+            if config.policy.pulls() {
+                if let Err(e) = cache.restore(context, config).await {
+                    tracing::warn!("Could not restore the cache of job {}: {}", job.name, e);
+                }
+            }
+        }
+
+        if job.dependencies.is_empty() {
+            return Ok(run);
+        }
+
+        let saved = self.artifacts.find_by_build(&context.build_id).await?;
+        for dependency in &job.dependencies {
+            // Retried jobs save an artifact per attempt; the last one wins
+            let artifact = saved
+                .iter()
+                .rev()
+                .find(|artifact| artifact.job() == Some(dependency.as_str()))
+                .ok_or_else(|| {
+                    crate::Error::build(format!(
+                        "Job {} depends on the artifacts of job {dependency}, which saved none",
+                        job.name
+                    ))
+                })?;
+
+            if artifact.is_expired() {
+                return Err(crate::Error::build(format!(
+                    "Artifact {} of job {dependency} has expired",
+                    artifact.name()
+                )));
+            }
+            if !self.store.exists(artifact.path()).await? {
+                return Err(crate::Error::build(format!(
+                    "Artifact {} of job {dependency} is missing from the artifact store",
+                    artifact.name()
+                )));
+            }
+
+            artifacts::download(self.store.as_ref(), &context.workspace, artifact)
+                .await
+                .map_err(|e| e.context(format!("Downloading artifact {} of job {dependency}", artifact.name())))?;
+            tracing::info!("Downloaded artifact {} of job {} for job {}", artifact.name(), dependency, job.name);
+        }
+
+        Ok(run)
+    }
+
+    /// Run the steps after the commands of a job, returning the artifact it
+    /// produced, if any
+    ///
+    /// Artifacts are collected whether the commands succeeded or not, so
+    /// the reports of failed tests are kept. Caches are only saved after the
+    /// commands succeeded, so a broken dependency install is not reused.
+    pub async fn after_job(&self, context: &JobContext, job: &Job, succeeded: bool) -> crate::Result<Option<Artifact>> {
+        if let (Some(cache), Some(config)) = (&self.cache, &job.cache) {
+            if succeeded && config.policy.pushes() {
+                if let Err(e) = cache.save(context, config).await {
+                    tracing::warn!("Could not save the cache of job {}: {}", job.name, e);
+                }
+            }
+        }
+
+        let Some(config) = &job.artifacts else {
+            return Ok(None);
+        };
+
+        let artifact = artifacts::collect(self.store.as_ref(), context, &job.name, config).await?;
+        if let Some(artifact) = &artifact {
+            self.artifacts.save(artifact).await?;
+            tracing::info!("Job {} produced artifact {} ({} bytes)", job.name, artifact.name(), artifact.size());
+        }
+        Ok(artifact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::domain::entities::secret::SecretScope;
+    use crate::domain::value_objects::{build_id::BuildId, pipeline_config::PipelineConfig, project_id::ProjectId};
+    use crate::infrastructure::repositories::in_memory::{InMemoryArtifactRepository, InMemorySecretRepository};
+    use crate::infrastructure::storage::local::LocalArtifactStore;
+    use chrono::{Duration, Utc};
+    use std::fs;
+
+    fn executor(storage: &tempfile::TempDir) -> (JobExecutor, Arc<InMemoryArtifactRepository>, Arc<SecretService>) {
+        let repository = Arc::new(InMemoryArtifactRepository::new());
+        let secrets = Arc::new(SecretService::new(
+            Arc::new(InMemorySecretRepository::new()),
+            &Config::default().security,
+        ));
+        let store = Arc::new(LocalArtifactStore::new(storage.path(), 1024 * 1024));
+        (JobExecutor::new(store, repository.clone(), secrets.clone()), repository, secrets)
+    }
+
+    fn pipeline() -> Pipeline {
+        Pipeline::new(ProjectId::new(), "ci".to_string(), PipelineConfig::new(vec![], vec![]))
+    }
+
+    fn job(name: &str, artifacts: Option<&str>, dependencies: &[&str]) -> Job {
+        let mut job = Job::new(name.to_string());
+        job.add_command("make".to_string());
+        job.artifacts = artifacts.map(|paths| serde_yaml::from_str(paths).unwrap());
+        job.dependencies = dependencies.iter().map(|dependency| dependency.to_string()).collect();
+        job
+    }
+
+    fn context(build_id: &BuildId) -> (JobContext, tempfile::TempDir) {
+        let workspace = tempfile::tempdir().unwrap();
+        let context = JobContext {
+            project_id: ProjectId::new(),
+            build_id: build_id.clone(),
+            git_ref: "refs/heads/main".to_string(),
+            default_ref: "refs/heads/main".to_string(),
+            workspace: workspace.path().to_path_buf(),
+        };
+        (context, workspace)
+    }
+
+    #[tokio::test]
+    async fn test_dependencies_download_artifacts() {
+        let storage = tempfile::tempdir().unwrap();
+        let (executor, repository, _) = executor(&storage);
+        let store = executor.store.clone();
+        let pipeline = pipeline();
+        let build_id = BuildId::new();
+
+        // Upstream jobs save an archive and a single file
+        let compile = job("compile", Some("paths: [dist]"), &[]);
+        let report = job("report", Some("paths: [reports]"), &[]);
+        let (upstream, upstream_workspace) = context(&build_id);
+        for (file, contents) in [("dist/app", "binary"), ("dist/lib/core.so", "library"), ("reports/unit.xml", "<ok/>")] {
+            let path = upstream_workspace.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        executor.after_job(&upstream, &compile, true).await.unwrap().unwrap();
+        let saved = executor.after_job(&upstream, &report, false).await.unwrap().unwrap();
+
+        let (downstream, workspace) = context(&build_id);
+        executor.before_job(&downstream, &pipeline, &job("package", None, &["compile", "report"])).await.unwrap();
+        assert_eq!(fs::read_to_string(workspace.path().join("dist/app")).unwrap(), "binary");
+        assert_eq!(fs::read_to_string(workspace.path().join("dist/lib/core.so")).unwrap(), "library");
+        assert_eq!(fs::read_to_string(workspace.path().join("reports/unit.xml")).unwrap(), "<ok/>");
+
+        // Jobs without artifacts, expired artifacts and deleted ones fail the job
+        let error = executor.before_job(&downstream, &pipeline, &job("package", None, &["lint"])).await.unwrap_err();
+        assert!(error.to_string().contains("which saved none"), "{error}");
+
+        let mut expired = saved.clone();
+        expired.set_expiration(Utc::now() - Duration::days(1));
+        repository.update(&expired).await.unwrap();
+        let error = executor.before_job(&downstream, &pipeline, &job("package", None, &["report"])).await.unwrap_err();
+        assert!(error.to_string().contains("has expired"), "{error}");
+
+        repository.update(&saved).await.unwrap();
+        store.delete(saved.path()).await.unwrap();
+        let error = executor.before_job(&downstream, &pipeline, &job("package", None, &["report"])).await.unwrap_err();
+        assert!(error.to_string().contains("missing from the artifact store"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_environment_resolves_secrets() {
+        let storage = tempfile::tempdir().unwrap();
+        let (executor, _, secrets) = executor(&storage);
+        let pipeline = pipeline();
+        let scope = SecretScope::Project(pipeline.project_id().clone());
+        secrets.set_secret(scope, "REGISTRY_PASSWORD".to_string(), "hunter2").await.unwrap();
+
+        let mut deploy = job("deploy", None, &[]);
+        deploy.environment.insert("REGISTRY_AUTH".to_string(), "ci:${{ secrets.REGISTRY_PASSWORD }}".to_string());
+        let (context, _workspace) = context(&BuildId::new());
+        let mut run = executor.before_job(&context, &pipeline, &deploy).await.unwrap();
+        assert_eq!(run.environment()["REGISTRY_AUTH"], "ci:hunter2");
+        assert!(!format!("{run:?}").contains("hunter2"));
+
+        // Secrets echoed by the commands never reach the logs, even when the
+        // output splits them
+        let mut logs = JobEntity::new(context.build_id.clone(), "deploy".to_string(), "deploy".to_string(), vec![]);
+        run.append_logs(&mut logs, "Logging in as ci:hun");
+        assert!(!logs.logs().contains("hun"));
+        run.append_logs(&mut logs, "ter2\n");
+        run.finish_logs(&mut logs);
+        assert_eq!(logs.logs(), "Logging in as ci:***\n");
+
+        deploy.environment.insert("TOKEN".to_string(), "${{ secrets.MISSING }}".to_string());
+        let error = executor.before_job(&context, &pipeline, &deploy).await.unwrap_err();
+        assert!(matches!(error, crate::Error::Validation(_)), "{error}");
+    }
+}
@@ -0,0 +1,612 @@
+//! Checkouts of project repositories into build workspaces
+//!
+//! libgit2 is blocking, so checkouts run on the blocking thread pool under the
+//! clone or fetch timeout. With a repository mirror, objects are borrowed from
+//! it through git alternates and the forge is only contacted for commits the
+//! mirror lacks.
+
+use super::transfer::{self, Transfer};
+use crate::config::GitConfig;
+use crate::domain::entities::workspace::Workspace;
+use crate::domain::value_objects::pipeline_config::{CheckoutConfig, SubmoduleMode};
+use git2::build::CheckoutBuilder;
+use git2::{AutotagOption, Commit, IndexEntry, Oid, Remote, Repository, SubmoduleUpdateOptions};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const REMOTE: &str = "origin";
+
+/// Submodule definitions, checked out even when outside the sparse paths
+const GITMODULES: &str = ".gitmodules";
+
+/// Extended index flag of entries outside a sparse checkout
+const SKIP_WORKTREE: u16 = 1 << 14;
+
+/// `depth` value asking libgit2 to fetch the missing history of a shallow clone
+const UNSHALLOW: i32 = i32::MAX;
+
+/// What to check out into a workspace
+#[derive(Clone)]
+pub struct CheckoutRequest {
+    /// Repository to clone or fetch
+    pub repository_url: String,
+
+    /// Commit to check out
+    pub commit_sha: String,
+
+    /// Branch or tag the commit was built from
+    pub branch: String,
+
+    /// Token for HTTPS repositories
+    pub https_token: Option<String>,
+
+    /// Depth, sparse paths, submodules and tags
+    pub options: CheckoutConfig,
+
+    /// Bare mirror of the repository to borrow objects from
+    pub mirror: Option<PathBuf>,
+}
+
+impl fmt::Debug for CheckoutRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckoutRequest")
+            .field("repository_url", &self.repository_url)
+            .field("commit_sha", &self.commit_sha)
+            .field("branch", &self.branch)
+            .field("https_token", &self.https_token.as_ref().map(|_| "***"))
+            .field("options", &self.options)
+            .field("mirror", &self.mirror)
+            .finish()
+    }
+}
+
+/// Clones or fetches repositories into workspaces
+pub struct GitCheckout {
+    config: GitConfig,
+}
+
+impl GitCheckout {
+    /// Create a checkout service
+    pub fn new(config: GitConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check out a commit into a workspace
+    ///
+    /// A workspace already holding a clone is fetched into; otherwise the
+    /// repository is cloned. The workspace ends up on a detached `HEAD` at the
+    /// requested commit and is marked ready, or marked as failed on error.
+    pub async fn checkout(&self, workspace: &mut Workspace, request: CheckoutRequest) -> crate::Result<()> {
+        workspace.set_repository(
+            request.repository_url.clone(),
+            request.commit_sha.clone(),
+            request.branch.clone(),
+        );
+
+        let path = workspace.path().clone();
+        let timeout = Duration::from_secs(if path.join(".git").exists() {
+            self.config.fetch_timeout
+        } else {
+            self.config.clone_timeout
+        });
+
+        let config = self.config.clone();
+        let result = transfer::run_blocking(timeout, move |cancelled| {
+            let transfer = Transfer::new(&config, &request.repository_url, request.https_token.as_deref(), cancelled);
+            checkout_blocking(&config, &transfer, &path, &request)
+        })
+        .await;
+
+        match result {
+            Ok(size) => {
+                workspace.update_size(size);
+                workspace.mark_ready()
+            }
+            Err(e) => {
+                workspace.mark_error();
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Fetch and check out the requested commit, returning the workspace size
+fn checkout_blocking(
+    config: &GitConfig,
+    transfer: &Transfer<'_>,
+    path: &Path,
+    request: &CheckoutRequest,
+) -> crate::Result<u64> {
+    let sha = parse_sha(&request.commit_sha)?;
+    let repository = if let Ok(repository) = Repository::open(path) {
+        repository
+    } else {
+        fs::create_dir_all(path)?;
+        Repository::init(path)?
+    };
+    let mut remote = origin(&repository, &request.repository_url)?;
+    if let Some(mirror) = &request.mirror {
+        borrow_objects(&repository, mirror)?;
+    }
+
+    // A workspace reused from a shallow build gets its history back
+    let unshallow = request.options.depth.is_none() && repository.is_shallow();
+    let commit = if let Some(commit) = find_commit(&repository, sha).filter(|_| !unshallow) {
+        commit
+    } else {
+        let branch = &request.branch;
+        let refspecs = [
+            format!("+refs/heads/{branch}:refs/remotes/{REMOTE}/{branch}"),
+            format!("+refs/tags/{branch}:refs/tags/{branch}"),
+        ];
+        fetch(transfer, &repository, &mut remote, &request.options, &refspecs)?;
+
+        // The commit may no longer be on the branch, e.g. after a force push
+        if let Some(commit) = find_commit(&repository, sha) {
+            commit
+        } else {
+            let refspecs = std::slice::from_ref(&request.commit_sha);
+            fetch(transfer, &repository, &mut remote, &request.options, refspecs)?;
+            find_commit(&repository, sha).ok_or_else(|| {
+                crate::Error::git(format!("Commit {} not found in {}", request.commit_sha, request.repository_url))
+            })?
+        }
+    };
+
+    let sparse_paths = &request.options.sparse_paths;
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force().remove_untracked(true);
+    if !sparse_paths.is_empty() {
+        checkout.path(GITMODULES);
+    }
+    for path in sparse_paths {
+        checkout.path(path.as_str());
+    }
+    repository.checkout_tree(commit.as_object(), Some(&mut checkout))?;
+    repository.set_head_detached(commit.id())?;
+    if !sparse_paths.is_empty() {
+        mark_sparse(&repository, &commit, sparse_paths)?;
+    }
+
+    match request.options.submodules {
+        SubmoduleMode::None => {}
+        SubmoduleMode::Init => update_submodules(transfer, &repository, sparse_paths, false)?,
+        SubmoduleMode::Recursive => update_submodules(transfer, &repository, sparse_paths, true)?,
+    }
+
+    // Borrowed objects stay in the mirror and do not count
+    let size = directory_size(path)?;
+    if size > transfer::max_size_bytes(config) {
+        return Err(transfer::too_large(config));
+    }
+    Ok(size)
+}
+
+/// Get the `origin` remote, pointing it at `url`
+fn origin<'r>(repository: &'r Repository, url: &str) -> crate::Result<Remote<'r>> {
+    match repository.find_remote(REMOTE) {
+        Ok(remote) if remote.url() == Some(url) => Ok(remote),
+        Ok(_) => {
+            repository.remote_set_url(REMOTE, url)?;
+            Ok(repository.find_remote(REMOTE)?)
+        }
+        Err(_) => Ok(repository.remote(REMOTE, url)?),
+    }
+}
+
+/// Make the objects of a mirror available to a repository
+///
+/// The mirror is listed in `objects/info/alternates`, so git commands run by
+/// jobs see its objects too.
+fn borrow_objects(repository: &Repository, mirror: &Path) -> crate::Result<()> {
+    let objects = mirror.join("objects");
+    let objects = objects
+        .to_str()
+        .ok_or_else(|| crate::Error::git(format!("Unsupported mirror path: {}", mirror.display())))?;
+
+    let alternates = repository.path().join("objects/info/alternates");
+    let listed = fs::read_to_string(&alternates).unwrap_or_default();
+    if !listed.lines().any(|line| line == objects) {
+        fs::create_dir_all(alternates.parent().unwrap_or(repository.path()))?;
+        fs::write(&alternates, format!("{listed}{objects}\n"))?;
+    }
+    repository.odb()?.add_disk_alternate(objects)?;
+    Ok(())
+}
+
+/// Fetch refspecs with the depth and tags of the checkout options
+fn fetch(
+    transfer: &Transfer<'_>,
+    repository: &Repository,
+    remote: &mut Remote<'_>,
+    options: &CheckoutConfig,
+    refspecs: &[String],
+) -> crate::Result<()> {
+    let mut fetch_options = transfer.fetch_options();
+    fetch_options.download_tags(if options.fetch_tags { AutotagOption::All } else { AutotagOption::None });
+    match options.depth {
+        Some(depth) => {
+            fetch_options.depth(i32::try_from(depth).unwrap_or(i32::MAX));
+        }
+        None if repository.is_shallow() => {
+            fetch_options.depth(UNSHALLOW);
+        }
+        None => {}
+    }
+
+    let result = remote.fetch(refspecs, Some(&mut fetch_options), None);
+    transfer.check(result)
+}
+
+/// Check out the submodules of a repository at the commits it records
+///
+/// Submodules outside the sparse checkout paths are left empty.
+fn update_submodules(
+    transfer: &Transfer<'_>,
+    repository: &Repository,
+    sparse_paths: &[String],
+    recursive: bool,
+) -> crate::Result<()> {
+    for mut submodule in repository.submodules()? {
+        let path = submodule.path().to_string_lossy().into_owned();
+        if !sparse_paths.is_empty() && !in_sparse_paths(&path, sparse_paths) {
+            continue;
+        }
+
+        let mut options = SubmoduleUpdateOptions::new();
+        options.fetch(transfer.fetch_options());
+        let result = submodule.update(true, Some(&mut options));
+        transfer.check(result)?;
+
+        if recursive {
+            update_submodules(transfer, &submodule.open()?, &[], true)?;
+        }
+    }
+    Ok(())
+}
+
+/// Mark files outside the sparse paths as skipped in the index
+///
+/// The checkout only wrote the sparse paths and `.gitmodules`; without this
+/// the other files would show up as deleted. The patterns are also written to
+/// `.git/info/sparse-checkout` so git commands run by jobs keep to them.
+fn mark_sparse(repository: &Repository, commit: &Commit<'_>, sparse_paths: &[String]) -> crate::Result<()> {
+    let mut index = repository.index()?;
+    index.read_tree(&commit.tree()?)?;
+    let skipped: Vec<IndexEntry> = index
+        .iter()
+        .filter(|entry| entry.path != GITMODULES.as_bytes())
+        .filter(|entry| !in_sparse_paths(&String::from_utf8_lossy(&entry.path), sparse_paths))
+        .collect();
+    for mut entry in skipped {
+        entry.flags_extended |= SKIP_WORKTREE;
+        index.add(&entry)?;
+    }
+    index.write()?;
+
+    let mut patterns = String::new();
+    for path in std::iter::once(GITMODULES).chain(sparse_paths.iter().map(|path| path.trim_end_matches('/'))) {
+        patterns.push('/');
+        patterns.push_str(path);
+        patterns.push('\n');
+    }
+    let info = repository.path().join("info");
+    fs::create_dir_all(&info)?;
+    fs::write(info.join("sparse-checkout"), patterns)?;
+    repository.config()?.set_bool("core.sparseCheckout", true)?;
+    Ok(())
+}
+
+/// Check if a path is one of the sparse paths or inside one of them
+fn in_sparse_paths(path: &str, sparse_paths: &[String]) -> bool {
+    sparse_paths.iter().any(|sparse| {
+        let sparse = sparse.trim_end_matches('/');
+        path.strip_prefix(sparse).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+fn find_commit(repository: &Repository, sha: Oid) -> Option<Commit<'_>> {
+    repository.find_commit(sha).ok()
+}
+
+/// Parse a full commit SHA, rejecting abbreviations and other revisions
+fn parse_sha(sha: &str) -> crate::Result<Oid> {
+    if sha.len() != 40 || !sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(crate::Error::validation(format!("Invalid commit SHA: {sha}")));
+    }
+    Ok(Oid::from_str(sha)?)
+}
+
+/// Total size of the files under a directory, without following symlinks
+fn directory_size(path: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.path().symlink_metadata()?;
+        size += if metadata.is_dir() {
+            directory_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::workspace::WorkspaceStatus;
+    use crate::domain::value_objects::build_id::BuildId;
+    use git2::Signature;
+    use std::process::{Child, Command, Stdio};
+
+    fn config(max_repo_size: u64) -> GitConfig {
+        GitConfig {
+            ssh_key_path: None,
+            known_hosts_path: None,
+            clone_timeout: 30,
+            fetch_timeout: 30,
+            max_repo_size,
+            mirror_refresh_interval: 300,
+        }
+    }
+
+    /// Commit a file on HEAD of a repository, returning the commit ID
+    fn commit(repository: &Repository, path: &str, content: &str) -> String {
+        let file = repository.workdir().unwrap().join(path);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, content).unwrap();
+        let mut index = repository.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repository.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repository
+            .commit(Some("HEAD"), &signature, &signature, "commit", &tree, &parent.iter().collect::<Vec<_>>())
+            .unwrap()
+            .to_string()
+    }
+
+    fn init(path: &Path) -> Repository {
+        let repository = Repository::init(path).unwrap();
+        repository.set_head("refs/heads/main").unwrap();
+        repository
+    }
+
+    fn request(origin: &Path, commit_sha: &str) -> CheckoutRequest {
+        CheckoutRequest {
+            repository_url: origin.to_str().unwrap().to_string(),
+            commit_sha: commit_sha.to_string(),
+            branch: "main".to_string(),
+            https_token: None,
+            options: CheckoutConfig::default(),
+            mirror: None,
+        }
+    }
+
+    fn head(path: &Path) -> String {
+        Repository::open(path).unwrap().head().unwrap().target().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_clone_and_fetch() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let first = commit(&origin, "README.md", "# Project");
+
+        let workspaces = tempfile::tempdir().unwrap();
+        let path = workspaces.path().join("build");
+        let checkout = GitCheckout::new(config(100));
+
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        checkout.checkout(&mut workspace, request(origin_dir.path(), &first)).await.unwrap();
+        assert_eq!(workspace.status(), &WorkspaceStatus::Ready);
+        assert!(workspace.size() > 0);
+        assert_eq!(head(&path), first);
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "# Project");
+
+        // A second build fetches into the existing clone
+        let second = commit(&origin, "README.md", "# Project v2");
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        checkout.checkout(&mut workspace, request(origin_dir.path(), &second)).await.unwrap();
+        assert_eq!(head(&path), second);
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "# Project v2");
+
+        // Older commits of the branch can still be checked out
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        checkout.checkout(&mut workspace, request(origin_dir.path(), &first)).await.unwrap();
+        assert_eq!(head(&path), first);
+
+        // Commits missing from the branch are fetched by ID
+        let first_commit = origin.find_commit(git2::Oid::from_str(&first).unwrap()).unwrap();
+        origin.branch("other", &first_commit, false).unwrap();
+        origin.set_head("refs/heads/other").unwrap();
+        let other = commit(&origin, "OTHER.md", "other");
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        checkout.checkout(&mut workspace, request(origin_dir.path(), &other)).await.unwrap();
+        assert_eq!(head(&path), other);
+        assert!(path.join("OTHER.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_checkout_failures() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let sha = commit(&origin, "README.md", "# Project");
+        let workspaces = tempfile::tempdir().unwrap();
+
+        let mut workspace = Workspace::new(BuildId::new(), workspaces.path().join("missing"));
+        let missing = "0123456789012345678901234567890123456789";
+        assert!(GitCheckout::new(config(100))
+            .checkout(&mut workspace, request(origin_dir.path(), missing))
+            .await
+            .is_err());
+        assert_eq!(workspace.status(), &WorkspaceStatus::Error);
+
+        // Only full commit SHAs are checked out, never other revisions
+        for revision in ["main", "HEAD~1", &sha[..7], &sha.to_uppercase()[..39]] {
+            let mut workspace = Workspace::new(BuildId::new(), workspaces.path().join("revision"));
+            let error = GitCheckout::new(config(100))
+                .checkout(&mut workspace, request(origin_dir.path(), revision))
+                .await
+                .unwrap_err();
+            assert!(matches!(error, crate::Error::Validation(_)), "{revision}: {error}");
+        }
+
+        let mut workspace = Workspace::new(BuildId::new(), workspaces.path().join("too-large"));
+        let result = GitCheckout::new(config(0))
+            .checkout(&mut workspace, request(origin_dir.path(), &sha))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("maximum size"));
+        assert_eq!(workspace.status(), &WorkspaceStatus::Error);
+    }
+
+    #[tokio::test]
+    async fn test_sparse_checkout_and_submodules() {
+        let library_dir = tempfile::tempdir().unwrap();
+        let library = init(library_dir.path());
+        commit(&library, "lib.rs", "pub fn lib() {}");
+
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = init(origin_dir.path());
+        commit(&origin, "services/web/index.html", "<html>");
+        let mut submodule = origin
+            .submodule(library_dir.path().to_str().unwrap(), Path::new("services/api/vendor"), true)
+            .unwrap();
+        submodule.clone(None).unwrap();
+        submodule.add_finalize().unwrap();
+        let sha = commit(&origin, "services/api/main.rs", "fn main() {}");
+        let head = origin.find_object(git2::Oid::from_str(&sha).unwrap(), None).unwrap();
+        origin.tag_lightweight("v1.0.0", &head, false).unwrap();
+
+        let workspaces = tempfile::tempdir().unwrap();
+        let path = workspaces.path().join("build");
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        let mut request = request(origin_dir.path(), &sha);
+        request.options = CheckoutConfig {
+            depth: None,
+            sparse_paths: vec!["services/api".to_string()],
+            submodules: SubmoduleMode::Init,
+            fetch_tags: false,
+        };
+        GitCheckout::new(config(100)).checkout(&mut workspace, request).await.unwrap();
+
+        assert!(path.join("services/api/main.rs").exists());
+        assert!(!path.join("services/web/index.html").exists());
+        assert!(path.join("services/api/vendor/lib.rs").exists());
+
+        let repository = Repository::open(&path).unwrap();
+        assert!(repository.tag_names(None).unwrap().is_empty());
+        let index = repository.index().unwrap();
+        let web = index.get_path(Path::new("services/web/index.html"), 0).unwrap();
+        assert_ne!(web.flags_extended & SKIP_WORKTREE, 0);
+        assert!(fs::read_to_string(path.join(".git/info/sparse-checkout")).unwrap().contains("/services/api"));
+    }
+
+    /// A `git daemon` serving the repositories in a directory
+    ///
+    /// libgit2 only fetches shallow over smart transports, not from local paths.
+    struct GitDaemon {
+        child: Child,
+        port: u16,
+    }
+
+    impl GitDaemon {
+        fn start(base_path: &Path) -> Self {
+            let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+            // `git daemon` would run the daemon as a child that outlives it
+            let exec_path = Command::new("git").arg("--exec-path").output().expect("Failed to run git").stdout;
+            let child = Command::new(Path::new(String::from_utf8(exec_path).unwrap().trim()).join("git-daemon"))
+                .args(["--export-all", "--reuseaddr", "--listen=127.0.0.1"])
+                .arg(format!("--port={port}"))
+                .arg(format!("--base-path={}", base_path.display()))
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("Failed to start git daemon");
+
+            for _ in 0..50 {
+                if std::net::TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                    return Self { child, port };
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            panic!("git daemon did not start");
+        }
+
+        fn url(&self, name: &str) -> String {
+            format!("git://127.0.0.1:{}/{name}", self.port)
+        }
+    }
+
+    impl Drop for GitDaemon {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    /// Number of commits reachable from a commit of a repository
+    fn history_len(path: &Path, sha: &str) -> usize {
+        let repository = Repository::open(path).unwrap();
+        let mut walk = repository.revwalk().unwrap();
+        walk.push(git2::Oid::from_str(sha).unwrap()).unwrap();
+        walk.count()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires git daemon"]
+    async fn test_shallow_checkout() {
+        let repositories = tempfile::tempdir().unwrap();
+        let origin_path = repositories.path().join("origin");
+        let origin = init(&origin_path);
+        // Commits below the shallow boundary are fetched by ID
+        origin.config().unwrap().set_bool("uploadpack.allowAnySHA1InWant", true).unwrap();
+        let commits: Vec<String> = (0..5).map(|i| commit(&origin, "README.md", &format!("v{i}"))).collect();
+        let daemon = GitDaemon::start(repositories.path());
+
+        let workspaces = tempfile::tempdir().unwrap();
+        let path = workspaces.path().join("build");
+        let checkout = GitCheckout::new(config(100));
+        let at_depth = |commit_sha: &str, depth: Option<u32>| {
+            let mut request = request(&origin_path, commit_sha);
+            request.repository_url = daemon.url("origin");
+            request.options.depth = depth;
+            request
+        };
+
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        checkout.checkout(&mut workspace, at_depth(&commits[4], Some(2))).await.unwrap();
+        assert_eq!(head(&path), commits[4]);
+        assert!(Repository::open(&path).unwrap().is_shallow());
+        assert_eq!(history_len(&path, &commits[4]), 2);
+
+        // A commit outside the shallow history is fetched into it
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        checkout.checkout(&mut workspace, at_depth(&commits[1], Some(2))).await.unwrap();
+        assert_eq!(head(&path), commits[1]);
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "v1");
+
+        // Without a depth the repository is unshallowed
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        checkout.checkout(&mut workspace, at_depth(&commits[4], None)).await.unwrap();
+        assert!(!Repository::open(&path).unwrap().is_shallow());
+        assert_eq!(history_len(&path, &commits[4]), 5);
+    }
+
+    #[test]
+    fn test_sparse_paths() {
+        let sparse = ["services/api/".to_string(), "Cargo.toml".to_string()];
+        assert!(in_sparse_paths("services/api", &sparse));
+        assert!(in_sparse_paths("services/api/src/main.rs", &sparse));
+        assert!(in_sparse_paths("Cargo.toml", &sparse));
+        assert!(!in_sparse_paths("services/api-gateway/main.rs", &sparse));
+        assert!(!in_sparse_paths("services/web/index.html", &sparse));
+    }
+}
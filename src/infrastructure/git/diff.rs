@@ -0,0 +1,122 @@
+//! Changed files between commits
+//!
+//! Used for trigger path filters when a webhook payload does not list the
+//! changed files, e.g. for pull requests or large pushes.
+
+use git2::{Oid, Repository, Tree};
+
+/// List the files changed between two commits of a local repository
+///
+/// Renamed files are listed under both their old and new paths.
+pub fn changed_files(repository: &Repository, before: &str, after: &str) -> crate::Result<Vec<String>> {
+    let before = tree(repository, before)?;
+    let after = tree(repository, after)?;
+
+    let diff = repository.diff_tree_to_tree(Some(&before), Some(&after), None)?;
+    let mut files: Vec<String> = diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .filter_map(|path| path.to_str().map(str::to_string))
+        .collect();
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// List the files a pull request changes
+///
+/// Like a forge, this compares the head with its merge base on the target
+/// branch, so changes made on the target branch meanwhile are left out.
+pub fn pull_request_changed_files(
+    repository: &Repository,
+    target: &str,
+    head: &str,
+) -> crate::Result<Vec<String>> {
+    let base = repository.merge_base(commit_id(repository, target)?, commit_id(repository, head)?)?;
+    changed_files(repository, &base.to_string(), head)
+}
+
+fn commit_id(repository: &Repository, revision: &str) -> crate::Result<Oid> {
+    Ok(repository.revparse_single(revision)?.peel_to_commit()?.id())
+}
+
+fn tree<'r>(repository: &'r Repository, revision: &str) -> crate::Result<Tree<'r>> {
+    Ok(repository.revparse_single(revision)?.peel_to_tree()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::fs;
+    use std::path::Path;
+
+    /// Write files and commit them on HEAD, returning the commit ID
+    fn commit(repository: &Repository, files: &[(&str, Option<&str>)]) -> String {
+        let workdir = repository.workdir().unwrap();
+        let mut index = repository.index().unwrap();
+        for (path, content) in files {
+            match content {
+                Some(content) => {
+                    let file = workdir.join(path);
+                    fs::create_dir_all(file.parent().unwrap()).unwrap();
+                    fs::write(file, content).unwrap();
+                    index.add_path(Path::new(path)).unwrap();
+                }
+                None => index.remove_path(Path::new(path)).unwrap(),
+            }
+        }
+        index.write().unwrap();
+
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repository.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repository
+            .commit(Some("HEAD"), &signature, &signature, "commit", &tree, &parent.iter().collect::<Vec<_>>())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Repository::init(dir.path()).unwrap();
+
+        let first = commit(&repository, &[
+            ("services/api/main.rs", Some("fn main() {}")),
+            ("services/web/index.html", Some("<html>")),
+            ("README.md", Some("# Monorepo")),
+        ]);
+        let second = commit(&repository, &[
+            ("services/api/main.rs", Some("fn main() { run() }")),
+            ("services/web/index.html", None),
+            ("docs/guide.md", Some("Guide")),
+        ]);
+
+        assert_eq!(
+            changed_files(&repository, &first, &second).unwrap(),
+            ["docs/guide.md", "services/api/main.rs", "services/web/index.html"]
+        );
+        assert!(changed_files(&repository, &first, &first).unwrap().is_empty());
+        assert!(changed_files(&repository, &first, "0000000").is_err());
+    }
+
+    #[test]
+    fn test_pull_request_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = Repository::init(dir.path()).unwrap();
+
+        let base = commit(&repository, &[("README.md", Some("# Project"))]);
+        let head = commit(&repository, &[("src/feature.rs", Some("pub fn feature() {}"))]);
+
+        // Move the target branch on independently of the pull request
+        let base_commit = repository.find_commit(Oid::from_str(&base).unwrap()).unwrap();
+        repository.branch("target", &base_commit, false).unwrap();
+        repository.set_head("refs/heads/target").unwrap();
+        let target = commit(&repository, &[("CHANGELOG.md", Some("Unreleased"))]);
+
+        assert_eq!(pull_request_changed_files(&repository, &target, &head).unwrap(), ["src/feature.rs"]);
+    }
+}
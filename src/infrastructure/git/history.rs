@@ -0,0 +1,313 @@
+//! Commit history read from project mirrors
+//!
+//! Gives builds the details of their commit and the commits they bring in,
+//! whatever triggered them.
+
+use super::diff;
+use super::mirror::{self, MirrorCache};
+use crate::domain::repositories::commit::CommitRepository;
+use crate::domain::services::project::ProjectService;
+use crate::domain::value_objects::{commit_info::CommitInfo, project_id::ProjectId};
+use async_trait::async_trait;
+use chrono::DateTime;
+use git2::{Commit, Oid, Repository, Sort};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Commit repository backed by the mirror cache
+pub struct GitCommitRepository {
+    mirrors: Arc<MirrorCache>,
+    projects: Arc<ProjectService>,
+}
+
+impl GitCommitRepository {
+    /// Create a commit repository reading project mirrors
+    pub fn new(mirrors: Arc<MirrorCache>, projects: Arc<ProjectService>) -> Self {
+        Self { mirrors, projects }
+    }
+
+    /// Refresh the mirror of a project unless it has a commit
+    ///
+    /// Branches and tags move, so only known commits skip the refresh. A
+    /// failed refresh leaves the mirror as it is.
+    async fn refresh_unless_known(&self, project_id: &ProjectId, revision: &str) -> crate::Result<()> {
+        let path = self.mirrors.mirror_path(project_id);
+        if mirror::contains_commit(path, revision.to_string()).await {
+            return Ok(());
+        }
+
+        let project = self.projects.get_project(project_id).await?;
+        if let Err(e) = self.mirrors.update(project_id, project.repository_url()).await {
+            tracing::warn!("Could not update the mirror of project {}: {}", project_id, e);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommitRepository for GitCommitRepository {
+    async fn resolve(&self, project_id: &ProjectId, revision: &str) -> crate::Result<CommitInfo> {
+        self.refresh_unless_known(project_id, revision).await?;
+
+        let revision = revision.to_string();
+        read_mirror(self.mirrors.mirror_path(project_id), move |repository| {
+            let commit = match Oid::from_str(&revision).ok().filter(|_| revision.len() == 40) {
+                Some(oid) => repository.find_commit(oid),
+                None => repository
+                    .find_reference(&revision)
+                    .and_then(|reference| reference.peel_to_commit()),
+            }
+            .map_err(|_| crate::Error::not_found(format!("Revision {revision} not found")))?;
+            Ok(commit_info(&commit))
+        })
+        .await
+    }
+
+    async fn history(
+        &self,
+        project_id: &ProjectId,
+        commit_sha: &str,
+        since: Option<&str>,
+        limit: usize,
+    ) -> crate::Result<Vec<CommitInfo>> {
+        let path = self.mirrors.mirror_path(project_id);
+        let commit_sha = commit_sha.to_string();
+        let since = since.map(str::to_string);
+
+        read_mirror(path, move |repository| {
+            let mut walk = repository.revwalk()?;
+            walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+            walk.push(Oid::from_str(&commit_sha)?)?;
+            if let Some(since) = since.and_then(|sha| Oid::from_str(&sha).ok()) {
+                if repository.find_commit(since).is_ok() {
+                    walk.hide(since)?;
+                }
+            }
+
+            walk.take(limit)
+                .map(|oid| Ok(commit_info(&repository.find_commit(oid?)?)))
+                .collect()
+        })
+        .await
+    }
+
+    async fn changed_files(
+        &self,
+        project_id: &ProjectId,
+        before: &str,
+        after: &str,
+    ) -> crate::Result<Vec<String>> {
+        self.refresh_unless_known(project_id, after).await?;
+
+        let (before, after) = (before.to_string(), after.to_string());
+        read_mirror(self.mirrors.mirror_path(project_id), move |repository| {
+            diff::changed_files(repository, &before, &after)
+        })
+        .await
+    }
+
+    async fn pull_request_changed_files(
+        &self,
+        project_id: &ProjectId,
+        target_branch: &str,
+        head: &str,
+    ) -> crate::Result<Vec<String>> {
+        self.refresh_unless_known(project_id, head).await?;
+
+        let target = format!("refs/heads/{target_branch}");
+        let head = head.to_string();
+        read_mirror(self.mirrors.mirror_path(project_id), move |repository| {
+            diff::pull_request_changed_files(repository, &target, &head)
+        })
+        .await
+    }
+}
+
+/// Run a read of a mirror on the blocking pool
+async fn read_mirror<T, F>(path: PathBuf, read: F) -> crate::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Repository) -> crate::Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let repository = Repository::open_bare(&path)
+            .map_err(|_| crate::Error::not_found("The project repository has not been fetched"))?;
+        read(&repository)
+    })
+        .await
+        .map_err(|e| crate::Error::internal(format!("Mirror read task failed: {e}")))?
+}
+
+fn commit_info(commit: &Commit<'_>) -> CommitInfo {
+    CommitInfo {
+        sha: commit.id().to_string(),
+        message: String::from_utf8_lossy(commit.message_bytes()).trim_end().to_string(),
+        author: commit.author().to_string(),
+        committer: commit.committer().to_string(),
+        timestamp: DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, EventsConfig};
+    use crate::domain::entities::build::{Build, BuildTrigger};
+    use crate::domain::services::{build::BuildService, secret::SecretService};
+    use crate::domain::value_objects::{agent_id::AgentId, pipeline_id::PipelineId};
+    use crate::infrastructure::events::EventBus;
+    use crate::infrastructure::repositories::in_memory::{
+        InMemoryBuildRepository, InMemoryProjectRepository, InMemorySecretRepository,
+    };
+    use git2::Signature;
+    use std::path::Path;
+
+    fn commit(repository: &Repository, message: &str) -> String {
+        std::fs::write(repository.workdir().unwrap().join("README.md"), message).unwrap();
+        let mut index = repository.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        index.write().unwrap();
+
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let author = Signature::now("Alice", "alice@example.com").unwrap();
+        let committer = Signature::now("Bob", "bob@example.com").unwrap();
+        let parent = repository.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repository
+            .commit(Some("HEAD"), &author, &committer, message, &tree, &parent.iter().collect::<Vec<_>>())
+            .unwrap()
+            .to_string()
+    }
+
+    fn mirror_cache(path: &Path) -> MirrorCache {
+        let config = Config::default();
+        let secrets = SecretService::new(Arc::new(InMemorySecretRepository::new()), &config.security);
+        MirrorCache::new(path, config.git, Arc::new(secrets))
+    }
+
+    #[tokio::test]
+    async fn test_build_commit_details() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let first = commit(&origin, "First\n\nWith a body.\n");
+        let second = commit(&origin, "Second");
+
+        let events = Arc::new(EventBus::new(EventsConfig::default()));
+        let projects = Arc::new(ProjectService::new(Arc::new(InMemoryProjectRepository::new()), events.clone()));
+        let mirrors = Arc::new(mirror_cache(cache_dir.path()));
+        let builds = BuildService::new(Arc::new(InMemoryBuildRepository::new()), events)
+            .with_commit_repository(Arc::new(GitCommitRepository::new(mirrors, projects.clone())));
+
+        let project = projects
+            .create_project(
+                "history".to_string(),
+                origin_dir.path().to_str().unwrap().to_string(),
+                "main".to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let pipeline_id = PipelineId::new();
+        let create = |commit_sha: String| {
+            builds.create_build(
+                pipeline_id.clone(),
+                project.id().clone(),
+                commit_sha,
+                "main".to_string(),
+                BuildTrigger::Manual { user_id: "user123".to_string() },
+            )
+        };
+
+        // Branches resolve to their head, even when a tag has the same name
+        let tagged = origin.find_object(Oid::from_str(&first).unwrap(), None).unwrap();
+        origin.tag_lightweight("main", &tagged, false).unwrap();
+        assert_eq!(builds.resolve_branch(project.id(), "main").await.unwrap(), second);
+        assert!(builds.resolve_branch(project.id(), "missing").await.is_err());
+
+        // Commits are described in the background; describe them now
+        let builds = &builds;
+        let describe = |build: Build| async move {
+            let result = builds.describe_commits(build.id()).await;
+            (builds.get_build(build.id()).await.unwrap(), result)
+        };
+
+        // Without a successful build, the whole history is new
+        let (build, result) = describe(create(second.clone()).await.unwrap()).await;
+        result.unwrap();
+        assert_eq!(build.commit_message(), Some("Second"));
+        assert_eq!(build.commit_author(), Some("Alice <alice@example.com>"));
+        assert_eq!(build.commit_committer(), Some("Bob <bob@example.com>"));
+        assert!(build.committed_at().is_some());
+        let changes: Vec<_> = build.changes().iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(changes, [second.as_str(), first.as_str()]);
+        assert_eq!(build.changes()[1].message, "First\n\nWith a body.");
+
+        builds.start_build(build.id(), AgentId::new()).await.unwrap();
+        builds.complete_build(build.id()).await.unwrap();
+
+        // Later builds list the commits since the last successful one
+        let third = commit(&origin, "Third");
+        let fourth = commit(&origin, "Fourth");
+        let head = builds.resolve_branch(project.id(), "main").await.unwrap();
+        assert_eq!(head, fourth);
+        let (build, result) = describe(create(head).await.unwrap()).await;
+        result.unwrap();
+        let changes: Vec<_> = build.changes().iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(changes, [fourth.as_str(), third.as_str()]);
+
+        // Unknown commits are still built, without details
+        let (build, result) = describe(create("0".repeat(40)).await.unwrap()).await;
+        assert!(result.is_err());
+        assert_eq!(build.commit_message(), None);
+        assert!(build.changes().is_empty());
+
+        // Without being asked, builds are described in the background
+        let build = create(third.clone()).await.unwrap();
+        for _ in 0..100 {
+            if builds.get_build(build.id()).await.unwrap().commit_message().is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(builds.get_build(build.id()).await.unwrap().commit_message(), Some("Third"));
+    }
+
+    #[tokio::test]
+    async fn test_changed_files_from_mirror() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let first = commit(&origin, "First");
+
+        let events = Arc::new(EventBus::new(EventsConfig::default()));
+        let projects = ProjectService::new(Arc::new(InMemoryProjectRepository::new()), events);
+        let project = projects
+            .create_project(
+                "diff".to_string(),
+                origin_dir.path().to_str().unwrap().to_string(),
+                "main".to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let mirrors = Arc::new(mirror_cache(cache_dir.path()));
+        let commits = GitCommitRepository::new(mirrors, Arc::new(projects));
+
+        // Commits pushed since the mirror was fetched are fetched first
+        let second = commit(&origin, "Second");
+        assert_eq!(commits.changed_files(project.id(), &first, &second).await.unwrap(), ["README.md"]);
+        assert!(commits.changed_files(project.id(), &second, &second).await.unwrap().is_empty());
+
+        origin.branch("feature", &origin.find_commit(Oid::from_str(&first).unwrap()).unwrap(), false).unwrap();
+        origin.set_head("refs/heads/feature").unwrap();
+        let feature = commit(&origin, "Feature");
+        let files = commits.pull_request_changed_files(project.id(), "main", &feature).await.unwrap();
+        assert_eq!(files, ["README.md"]);
+
+        assert!(commits.changed_files(project.id(), &first, &"0".repeat(40)).await.is_err());
+    }
+}
@@ -0,0 +1,365 @@
+//! Bare mirrors of project repositories
+//!
+//! Each project gets a bare mirror under `<cache_path>/mirrors/<project id>.git`
+//! that is refreshed in the background. Checkouts borrow its objects, so a
+//! build only reaches the forge for commits pushed since the last refresh and
+//! keeps working from the mirror while the forge is down.
+//!
+//! A lock file next to each mirror guards it across processes sharing the
+//! cache: fetches and removal take it exclusively, checkouts share it.
+//!
+//! Private HTTPS repositories are fetched with the project's repository token,
+//! kept as a secret so background refreshes find it after a restart too.
+
+use super::checkout::{CheckoutRequest, GitCheckout};
+use super::transfer::{self, Transfer};
+use crate::config::GitConfig;
+use crate::domain::entities::project::Project;
+use crate::domain::entities::workspace::Workspace;
+use crate::domain::services::{project::ProjectService, secret::SecretService};
+use crate::domain::value_objects::project_id::ProjectId;
+use git2::{AutotagOption, FetchPrune, Oid, Repository};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Refs kept in a mirror
+const MIRROR_REFSPECS: [&str; 2] = ["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"];
+
+/// Cache of bare repository mirrors, one per project
+pub struct MirrorCache {
+    root: PathBuf,
+    config: GitConfig,
+    secrets: Arc<SecretService>,
+}
+
+impl MirrorCache {
+    /// Create a mirror cache under a cache directory
+    pub fn new(cache_path: impl AsRef<Path>, config: GitConfig, secrets: Arc<SecretService>) -> Self {
+        Self {
+            root: cache_path.as_ref().join("mirrors"),
+            config,
+            secrets,
+        }
+    }
+
+    /// Get the path of a project's mirror
+    pub fn mirror_path(&self, project_id: &ProjectId) -> PathBuf {
+        self.root.join(format!("{project_id}.git"))
+    }
+
+    fn lock_path(&self, project_id: &ProjectId) -> PathBuf {
+        self.root.join(format!("{project_id}.lock"))
+    }
+
+    /// Get the token authenticating HTTPS fetches of a project's repository
+    pub async fn https_token(&self, project_id: &ProjectId) -> crate::Result<Option<String>> {
+        self.secrets.repository_token(project_id).await
+    }
+
+    /// Create or refresh the mirror of a project
+    pub async fn update(&self, project_id: &ProjectId, repository_url: &str) -> crate::Result<()> {
+        let token = self.https_token(project_id).await?;
+        let path = self.mirror_path(project_id);
+        let timeout = Duration::from_secs(if path.exists() {
+            self.config.fetch_timeout
+        } else {
+            self.config.clone_timeout
+        });
+        let lock = self.lock_path(project_id);
+        let config = self.config.clone();
+        let url = repository_url.to_string();
+
+        transfer::run_blocking(timeout, move |cancelled| {
+            let _lock = lock_file(&lock, true)?;
+            let repository = match Repository::open_bare(&path) {
+                Ok(repository) => repository,
+                Err(_) => Repository::init_bare(&path)?,
+            };
+
+            let transfer = Transfer::new(&config, &url, token.as_deref(), cancelled);
+            let mut options = transfer.fetch_options();
+            options.prune(FetchPrune::On).download_tags(AutotagOption::All);
+            let result = repository.remote_anonymous(&url)?.fetch(&MIRROR_REFSPECS, Some(&mut options), None);
+            transfer.check(result)
+        })
+        .await
+    }
+
+    /// Check out a commit into a workspace, borrowing objects from the
+    /// project's mirror
+    ///
+    /// The mirror is refreshed first when it lacks the commit. If that fails,
+    /// e.g. because the forge is down, the checkout still goes ahead with what
+    /// the mirror has. Without a token in the request, the project's
+    /// repository token is used.
+    pub async fn checkout(
+        &self,
+        checkout: &GitCheckout,
+        project_id: &ProjectId,
+        workspace: &mut Workspace,
+        mut request: CheckoutRequest,
+    ) -> crate::Result<()> {
+        if request.https_token.is_none() {
+            request.https_token = self.https_token(project_id).await?;
+        }
+
+        let path = self.mirror_path(project_id);
+        if !contains_commit(path.clone(), request.commit_sha.clone()).await {
+            if let Err(e) = self.update(project_id, &request.repository_url).await {
+                tracing::warn!("Could not update the mirror of project {}: {}", project_id, e);
+            }
+        }
+
+        let lock = self.lock_path(project_id);
+        let _lock = tokio::task::spawn_blocking(move || lock_file(&lock, false))
+            .await
+            .map_err(|e| crate::Error::internal(format!("Mirror lock task failed: {e}")))??;
+
+        if Repository::open_bare(&path).is_ok() {
+            request.mirror = Some(path);
+        }
+        checkout.checkout(workspace, request).await
+    }
+
+    /// Remove the mirrors of projects not in `projects`, returning their IDs
+    pub async fn remove_unused(&self, projects: &[ProjectId]) -> crate::Result<Vec<ProjectId>> {
+        let root = self.root.clone();
+        let projects = projects.to_vec();
+
+        tokio::task::spawn_blocking(move || -> crate::Result<Vec<ProjectId>> {
+            let entries = match fs::read_dir(&root) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e.into()),
+            };
+
+            let mut removed = Vec::new();
+            for entry in entries {
+                let name = entry?.file_name();
+                let Some(project_id) = name
+                    .to_str()
+                    .and_then(|name| name.strip_suffix(".git"))
+                    .and_then(|id| ProjectId::parse(id).ok())
+                else {
+                    continue;
+                };
+                if projects.contains(&project_id) {
+                    continue;
+                }
+
+                // Wait for running checkouts to finish with the mirror
+                let lock = root.join(format!("{project_id}.lock"));
+                let _lock = lock_file(&lock, true)?;
+                fs::remove_dir_all(root.join(&name))?;
+                fs::remove_file(&lock)?;
+                removed.push(project_id);
+            }
+            Ok(removed)
+        })
+        .await
+        .map_err(|e| crate::Error::internal(format!("Mirror cleanup task failed: {e}")))?
+    }
+
+    /// Refresh the existing mirrors of projects and remove those of deleted
+    /// projects
+    pub async fn refresh(&self, projects: &[Project]) {
+        for project in projects {
+            if !self.mirror_path(project.id()).exists() {
+                continue;
+            }
+            if let Err(e) = self.update(project.id(), project.repository_url()).await {
+                tracing::warn!("Could not refresh the mirror of project {}: {}", project.id(), e);
+            }
+        }
+
+        let ids: Vec<ProjectId> = projects.iter().map(|project| project.id().clone()).collect();
+        match self.remove_unused(&ids).await {
+            Ok(removed) => {
+                for project_id in removed {
+                    tracing::info!("Removed the mirror of deleted project {}", project_id);
+                }
+            }
+            Err(e) => tracing::warn!("Could not remove unused mirrors: {}", e),
+        }
+    }
+
+    /// Refresh mirrors periodically until `shutdown` becomes true
+    pub async fn run(self: Arc<Self>, projects: Arc<ProjectService>, mut shutdown: watch::Receiver<bool>) {
+        let period = Duration::from_secs(self.config.mirror_refresh_interval.max(1));
+        let mut interval = tokio::time::interval(period);
+
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+
+            match projects.list_projects().await {
+                Ok(projects) => self.refresh(&projects).await,
+                Err(e) => tracing::warn!("Could not list projects to refresh mirrors: {}", e),
+            }
+        }
+    }
+
+    /// Run the periodic refresh on a background task
+    pub fn spawn(self: Arc<Self>, projects: Arc<ProjectService>, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(projects, shutdown))
+    }
+}
+
+/// Check if a mirror has a commit, without touching the network
+pub(super) async fn contains_commit(mirror: PathBuf, sha: String) -> bool {
+    tokio::task::spawn_blocking(move || {
+        let Ok(repository) = Repository::open_bare(&mirror) else {
+            return false;
+        };
+        Oid::from_str(&sha).is_ok_and(|oid| repository.find_commit(oid).is_ok())
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Open and lock a mirror's lock file, blocking until the lock is granted
+///
+/// The lock is released when the returned file is closed.
+fn lock_file(path: &Path, exclusive: bool) -> crate::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(path)?;
+    if exclusive {
+        file.lock()?;
+    } else {
+        file.lock_shared()?;
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::domain::entities::workspace::WorkspaceStatus;
+    use crate::domain::value_objects::build_id::BuildId;
+    use crate::domain::value_objects::pipeline_config::CheckoutConfig;
+    use crate::infrastructure::repositories::in_memory::InMemorySecretRepository;
+    use git2::Signature;
+
+    fn config() -> GitConfig {
+        GitConfig {
+            ssh_key_path: None,
+            known_hosts_path: None,
+            clone_timeout: 30,
+            fetch_timeout: 30,
+            max_repo_size: 100,
+            mirror_refresh_interval: 300,
+        }
+    }
+
+    fn secrets() -> Arc<SecretService> {
+        Arc::new(SecretService::new(Arc::new(InMemorySecretRepository::new()), &Config::default().security))
+    }
+
+    /// Commit a file on `main`, returning the commit ID
+    fn commit(repository: &Repository, path: &str, content: &str) -> String {
+        fs::write(repository.workdir().unwrap().join(path), content).unwrap();
+        let mut index = repository.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repository.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repository
+            .commit(Some("HEAD"), &signature, &signature, "commit", &tree, &parent.iter().collect::<Vec<_>>())
+            .unwrap()
+            .to_string()
+    }
+
+    fn request(origin: &Path, commit_sha: &str) -> CheckoutRequest {
+        CheckoutRequest {
+            repository_url: origin.to_str().unwrap().to_string(),
+            commit_sha: commit_sha.to_string(),
+            branch: "main".to_string(),
+            https_token: None,
+            options: CheckoutConfig::default(),
+            mirror: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkout_through_mirror() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let first = commit(&origin, "README.md", "# Project");
+
+        let cache = tempfile::tempdir().unwrap();
+        let mirrors = MirrorCache::new(cache.path(), config(), secrets());
+        let checkout = GitCheckout::new(config());
+        let project_id = ProjectId::new();
+        let workspaces = tempfile::tempdir().unwrap();
+
+        // The first checkout creates the mirror and borrows its objects
+        let path = workspaces.path().join("first");
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        mirrors.checkout(&checkout, &project_id, &mut workspace, request(origin_dir.path(), &first)).await.unwrap();
+        assert_eq!(workspace.status(), &WorkspaceStatus::Ready);
+        assert!(mirrors.mirror_path(&project_id).join("refs/heads/main").exists());
+        let alternates = fs::read_to_string(path.join(".git/objects/info/alternates")).unwrap();
+        assert!(alternates.contains(mirrors.mirror_path(&project_id).to_str().unwrap()));
+
+        // New commits are fetched into the mirror on demand
+        let second = commit(&origin, "README.md", "# Project v2");
+        let path = workspaces.path().join("second");
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        mirrors.checkout(&checkout, &project_id, &mut workspace, request(origin_dir.path(), &second)).await.unwrap();
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "# Project v2");
+
+        // Known commits still check out while the forge is unreachable
+        let gone = workspaces.path().join("gone");
+        let path = workspaces.path().join("offline");
+        let mut workspace = Workspace::new(BuildId::new(), path.clone());
+        mirrors.checkout(&checkout, &project_id, &mut workspace, request(&gone, &first)).await.unwrap();
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "# Project");
+    }
+
+    #[tokio::test]
+    async fn test_remove_unused() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        commit(&origin, "README.md", "# Project");
+        let url = origin_dir.path().to_str().unwrap();
+
+        let cache = tempfile::tempdir().unwrap();
+        let mirrors = MirrorCache::new(cache.path(), config(), secrets());
+        let kept = ProjectId::new();
+        let deleted = ProjectId::new();
+        mirrors.update(&kept, url).await.unwrap();
+        mirrors.update(&deleted, url).await.unwrap();
+
+        assert_eq!(mirrors.remove_unused(&[kept.clone()]).await.unwrap(), [deleted.clone()]);
+        assert!(mirrors.mirror_path(&kept).exists());
+        assert!(!mirrors.mirror_path(&deleted).exists());
+
+        let empty = MirrorCache::new(cache.path().join("missing"), config(), secrets());
+        assert!(empty.remove_unused(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_https_token_from_project_secret() {
+        let cache = tempfile::tempdir().unwrap();
+        let secrets = secrets();
+        let project_id = ProjectId::new();
+
+        // A fresh cache, e.g. after a restart, still finds the token
+        secrets.set_repository_token(&project_id, "ghp_token").await.unwrap();
+        let mirrors = MirrorCache::new(cache.path(), config(), secrets.clone());
+        assert_eq!(mirrors.https_token(&project_id).await.unwrap().as_deref(), Some("ghp_token"));
+        assert!(mirrors.https_token(&ProjectId::new()).await.unwrap().is_none());
+    }
+}
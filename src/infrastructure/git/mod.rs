@@ -1,4 +1,8 @@
 //! Git operations
 
-// TODO: Implement Git operations using git2
-
+pub mod checkout;
+pub mod diff;
+pub mod history;
+pub mod mirror;
+pub mod poller;
+mod transfer;
@@ -0,0 +1,511 @@
+//! Repository polling for pipelines with `Poll` triggers
+//!
+//! Some forges cannot send webhooks. For pipelines with a poll trigger, the
+//! poller lists the refs of the repository like `git ls-remote` and creates a
+//! build for every matching branch or tag that moved since the last poll.
+//!
+//! The refs last seen are kept per pipeline in memory, each recorded once its
+//! build was created, so a ref whose build failed is retried by the next poll.
+//! The first poll of a
+//! pipeline, e.g. after a restart, compares the refs with the commits its
+//! builds were last created for, so refs that moved while the server was down
+//! are still built. Refs the pipeline never built are only recorded, so a
+//! restart does not rebuild every branch.
+//!
+//! Private HTTPS repositories are listed with the project's repository token.
+
+use super::transfer::{self, Transfer};
+use crate::config::GitConfig;
+use crate::domain::entities::{build::Build, pipeline::Pipeline, project::Project};
+use crate::domain::services::{
+    build::BuildService, pipeline::PipelineService, project::ProjectService, secret::SecretService,
+};
+use crate::domain::value_objects::pipeline_config::Trigger;
+use crate::domain::value_objects::pipeline_id::PipelineId;
+use crate::domain::value_objects::scm_event::{POLL_SOURCE, ScmEvent, ScmEventKind};
+use git2::{Direction, Remote};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+
+/// How often the poller checks which pipelines are due
+const TICK: Duration = Duration::from_secs(10);
+
+/// Refs seen by the last poll of a pipeline
+struct PollState {
+    polled_at: Instant,
+    refs: HashMap<String, String>,
+}
+
+/// Polls repositories and creates builds for new commits
+pub struct RepositoryPoller {
+    config: GitConfig,
+    project_service: Arc<ProjectService>,
+    pipeline_service: Arc<PipelineService>,
+    build_service: Arc<BuildService>,
+    secret_service: Arc<SecretService>,
+    state: Mutex<HashMap<PipelineId, PollState>>,
+}
+
+impl RepositoryPoller {
+    /// Create a repository poller
+    pub fn new(
+        config: GitConfig,
+        project_service: Arc<ProjectService>,
+        pipeline_service: Arc<PipelineService>,
+        build_service: Arc<BuildService>,
+        secret_service: Arc<SecretService>,
+    ) -> Self {
+        Self {
+            config,
+            project_service,
+            pipeline_service,
+            build_service,
+            secret_service,
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Poll the repository of a pipeline now, creating builds for the refs
+    /// that moved since its last poll
+    pub async fn poll_pipeline(&self, project: &Project, pipeline: &Pipeline) -> crate::Result<Vec<Build>> {
+        let token = self.secret_service.repository_token(project.id()).await?;
+        let refs = list_refs(&self.config, project.repository_url(), token).await?;
+        let polled = self.state.lock().await.get(pipeline.id()).map(|state| state.refs.clone());
+        let previous = match polled {
+            Some(previous) => previous,
+            None => self.last_built_refs(pipeline, &refs).await?,
+        };
+
+        // Moved refs count as seen only once they are handled
+        let mut seen: HashMap<String, String> = refs
+            .keys()
+            .filter_map(|name| previous.get(name).map(|sha| (name.clone(), sha.clone())))
+            .collect();
+        let mut moved: Vec<_> = refs.iter().filter(|(name, sha)| previous.get(*name) != Some(sha)).collect();
+        moved.sort();
+
+        let mut builds = Vec::new();
+        for (name, sha) in moved {
+            match self.build_moved_ref(pipeline, name, sha, previous.get(name)).await {
+                Ok(build) => builds.extend(build),
+                Err(e) => {
+                    self.record_refs(pipeline, seen).await;
+                    return Err(e);
+                }
+            }
+            seen.insert(name.clone(), sha.clone());
+        }
+        self.record_refs(pipeline, seen).await;
+
+        if !builds.is_empty() {
+            tracing::info!("Polling {} triggered {} build(s)", project.name(), builds.len());
+        }
+        Ok(builds)
+    }
+
+    /// Record the refs seen by a poll of a pipeline
+    async fn record_refs(&self, pipeline: &Pipeline, refs: HashMap<String, String>) {
+        self.state
+            .lock()
+            .await
+            .insert(pipeline.id().clone(), PollState { polled_at: Instant::now(), refs });
+    }
+
+    /// Create a build for a moved ref if a poll trigger of the pipeline matches it
+    async fn build_moved_ref(
+        &self,
+        pipeline: &Pipeline,
+        name: &str,
+        sha: &str,
+        before_sha: Option<&String>,
+    ) -> crate::Result<Option<Build>> {
+        let Some(kind) = ref_event_kind(name) else {
+            return Ok(None);
+        };
+        let event = ScmEvent {
+            source: POLL_SOURCE.to_string(),
+            kind,
+            commit_sha: sha.to_string(),
+            commit_message: String::new(),
+            commit_author: String::new(),
+            before_sha: before_sha.cloned(),
+            changed_files: None,
+        };
+
+        let triggered = pipeline
+            .config()
+            .triggers
+            .iter()
+            .any(|trigger| matches!(trigger, Trigger::Poll { .. }) && trigger.matches(&event));
+        if !triggered {
+            return Ok(None);
+        }
+        Ok(Some(self.build_service.create_event_build(pipeline, &event).await?))
+    }
+
+    /// Get the commit each ref was last built at by a pipeline, falling back to
+    /// its current commit for refs the pipeline never built
+    async fn last_built_refs(
+        &self,
+        pipeline: &Pipeline,
+        refs: &HashMap<String, String>,
+    ) -> crate::Result<HashMap<String, String>> {
+        let mut builds = self.build_service.get_pipeline_builds(pipeline.id()).await?;
+        builds.sort_by_key(Build::number);
+
+        // Keyed by full ref, as a branch and a tag can share a name
+        let mut built = HashMap::new();
+        for build in &builds {
+            built.insert(build.git_ref(), build.commit_sha().to_string());
+        }
+
+        Ok(refs
+            .iter()
+            .map(|(name, sha)| (name.clone(), built.get(name).unwrap_or(sha).clone()))
+            .collect())
+    }
+
+    /// Poll every enabled pipeline whose poll interval has elapsed
+    pub async fn poll_due(&self) -> crate::Result<Vec<Build>> {
+        let mut builds = Vec::new();
+        let mut polled = Vec::new();
+
+        for project in self.project_service.list_projects().await? {
+            for pipeline in self.pipeline_service.get_project_pipelines(project.id()).await? {
+                let Some(interval) = poll_interval(&pipeline).filter(|_| pipeline.is_enabled()) else {
+                    continue;
+                };
+                polled.push(pipeline.id().clone());
+
+                let due = self
+                    .state
+                    .lock()
+                    .await
+                    .get(pipeline.id())
+                    .is_none_or(|state| state.polled_at.elapsed() >= interval);
+                if !due {
+                    continue;
+                }
+
+                match self.poll_pipeline(&project, &pipeline).await {
+                    Ok(created) => builds.extend(created),
+                    Err(e) => tracing::warn!("Could not poll {} for {}: {}", project.repository_url(), pipeline.name(), e),
+                }
+            }
+        }
+
+        // Forget pipelines that were deleted or stopped polling
+        self.state.lock().await.retain(|pipeline_id, _| polled.contains(pipeline_id));
+        Ok(builds)
+    }
+
+    /// Poll until `shutdown` becomes true
+    pub async fn run(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(TICK);
+
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+
+            if let Err(e) = self.poll_due().await {
+                tracing::warn!("Repository polling failed: {}", e);
+            }
+        }
+    }
+
+    /// Run the poller on a background task
+    pub fn spawn(self: Arc<Self>, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(shutdown))
+    }
+}
+
+/// Get the shortest poll interval of a pipeline, if it polls at all
+fn poll_interval(pipeline: &Pipeline) -> Option<Duration> {
+    pipeline
+        .config()
+        .triggers
+        .iter()
+        .filter_map(|trigger| match trigger {
+            Trigger::Poll { interval, .. } => Some(Duration::from_secs(*interval)),
+            _ => None,
+        })
+        .min()
+}
+
+/// Turn a branch or tag ref into the kind of event it would have sent
+fn ref_event_kind(name: &str) -> Option<ScmEventKind> {
+    if let Some(branch) = name.strip_prefix("refs/heads/") {
+        Some(ScmEventKind::Push { branch: branch.to_string() })
+    } else {
+        name.strip_prefix("refs/tags/").map(|tag| ScmEventKind::Tag { name: tag.to_string() })
+    }
+}
+
+/// List the branches and tags of a remote repository with their commits
+async fn list_refs(
+    config: &GitConfig,
+    url: &str,
+    https_token: Option<String>,
+) -> crate::Result<HashMap<String, String>> {
+    let config = config.clone();
+    let url = url.to_string();
+
+    transfer::run_blocking(Duration::from_secs(config.fetch_timeout), move |cancelled| {
+        let transfer = Transfer::new(&config, &url, https_token.as_deref(), cancelled);
+        let mut remote = Remote::create_detached(url.as_str())?;
+        let connection = remote.connect_auth(Direction::Fetch, Some(transfer.remote_callbacks()), None)?;
+
+        let mut refs = HashMap::new();
+        for head in connection.list()? {
+            let name = head.name();
+            if let Some(tag) = name.strip_suffix("^{}") {
+                // Annotated tags are listed again, peeled to their commit
+                refs.insert(tag.to_string(), head.oid().to_string());
+            } else if name.starts_with("refs/heads/") || name.starts_with("refs/tags/") {
+                refs.entry(name.to_string()).or_insert_with(|| head.oid().to_string());
+            }
+        }
+        Ok(refs)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, EventsConfig};
+    use crate::domain::entities::build::BuildTrigger;
+    use crate::domain::repositories::build::{BuildQueryOptions, BuildRepository};
+    use crate::domain::value_objects::{
+        build_id::BuildId,
+        build_status::BuildStatus,
+        pipeline_config::{Job, PipelineConfig, Stage},
+        project_id::ProjectId,
+    };
+    use crate::infrastructure::events::EventBus;
+    use crate::infrastructure::repositories::in_memory::{
+        InMemoryBuildRepository,
+        InMemoryPipelineRepository,
+        InMemoryProjectRepository,
+        InMemorySecretRepository,
+    };
+    use async_trait::async_trait;
+    use git2::{Repository, Signature};
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Build repository whose saves fail while `failing` is set
+    struct FlakyBuildRepository {
+        inner: InMemoryBuildRepository,
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl BuildRepository for FlakyBuildRepository {
+        async fn save(&self, build: &Build) -> crate::Result<()> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(crate::Error::storage("unavailable"));
+            }
+            self.inner.save(build).await
+        }
+
+        async fn find_by_id(&self, id: &BuildId) -> crate::Result<Option<Build>> {
+            self.inner.find_by_id(id).await
+        }
+
+        async fn find_by_pipeline(&self, pipeline_id: &PipelineId) -> crate::Result<Vec<Build>> {
+            self.inner.find_by_pipeline(pipeline_id).await
+        }
+
+        async fn find_by_project(&self, project_id: &ProjectId) -> crate::Result<Vec<Build>> {
+            self.inner.find_by_project(project_id).await
+        }
+
+        async fn query(&self, options: BuildQueryOptions) -> crate::Result<Vec<Build>> {
+            self.inner.query(options).await
+        }
+
+        async fn find_running(&self) -> crate::Result<Vec<Build>> {
+            self.inner.find_running().await
+        }
+
+        async fn next_build_number(&self, pipeline_id: &PipelineId) -> crate::Result<u64> {
+            self.inner.next_build_number(pipeline_id).await
+        }
+
+        async fn update(&self, build: &Build) -> crate::Result<()> {
+            self.inner.update(build).await
+        }
+
+        async fn delete(&self, id: &BuildId) -> crate::Result<()> {
+            self.inner.delete(id).await
+        }
+
+        async fn count_by_status(&self, status: &BuildStatus) -> crate::Result<u64> {
+            self.inner.count_by_status(status).await
+        }
+    }
+
+    fn commit(repository: &Repository, content: &str) -> git2::Oid {
+        std::fs::write(repository.workdir().unwrap().join("README.md"), content).unwrap();
+        let mut index = repository.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        index.write().unwrap();
+
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repository.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repository
+            .commit(Some("HEAD"), &signature, &signature, "commit", &tree, &parent.iter().collect::<Vec<_>>())
+            .unwrap()
+    }
+
+    fn new_poller(projects: &Arc<ProjectService>, pipelines: &Arc<PipelineService>, builds: &Arc<BuildService>) -> RepositoryPoller {
+        let config = Config::default();
+        let secrets = SecretService::new(Arc::new(InMemorySecretRepository::new()), &config.security);
+        RepositoryPoller::new(config.git, projects.clone(), pipelines.clone(), builds.clone(), Arc::new(secrets))
+    }
+
+    /// Create services and a pipeline polling `origin` for the given branches and tags
+    async fn poll_pipeline_of(
+        origin: &Path,
+        branches: &[&str],
+        tags: &[&str],
+        build_repository: Arc<dyn BuildRepository>,
+    ) -> (Arc<ProjectService>, Arc<PipelineService>, Arc<BuildService>, Project, Pipeline) {
+        let events = Arc::new(EventBus::new(EventsConfig::default()));
+        let projects = Arc::new(ProjectService::new(Arc::new(InMemoryProjectRepository::new()), events.clone()));
+        let pipelines = Arc::new(PipelineService::new(Arc::new(InMemoryPipelineRepository::new()), events.clone()));
+        let builds = Arc::new(BuildService::new(build_repository, events));
+
+        let project = projects
+            .create_project(
+                "polled".to_string(),
+                origin.to_str().unwrap().to_string(),
+                "main".to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let mut job = Job::new("test".to_string());
+        job.add_command("cargo test".to_string());
+        let config = PipelineConfig::new(
+            vec![Stage::new("test".to_string(), vec![job])],
+            vec![Trigger::Poll {
+                interval: 300,
+                branches: branches.iter().map(ToString::to_string).collect(),
+                tags: tags.iter().map(ToString::to_string).collect(),
+            }],
+        );
+        let pipeline = pipelines.create_pipeline(project.id().clone(), "poll".to_string(), config).await.unwrap();
+
+        (projects, pipelines, builds, project, pipeline)
+    }
+
+    #[tokio::test]
+    async fn test_poll_pipeline() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let first = commit(&origin, "# Project");
+
+        let (projects, pipelines, builds, project, pipeline) = poll_pipeline_of(origin_dir.path(), &["main"], &["v*"], Arc::new(InMemoryBuildRepository::new())).await;
+        let poller = new_poller(&projects, &pipelines, &builds);
+
+        // The first poll only records refs that were never built
+        assert!(poller.poll_pipeline(&project, &pipeline).await.unwrap().is_empty());
+        assert!(poller.poll_pipeline(&project, &pipeline).await.unwrap().is_empty());
+
+        let second = commit(&origin, "# Project v2");
+        let created = poller.poll_pipeline(&project, &pipeline).await.unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].commit_sha(), second.to_string());
+        assert_eq!(created[0].branch(), "main");
+        assert_eq!(created[0].trigger(), &BuildTrigger::Poll);
+
+        // Unmatched branches are ignored; matching tags are built
+        origin.branch("feature", &origin.find_commit(first).unwrap(), false).unwrap();
+        let head = origin.find_object(second, None).unwrap();
+        origin.tag("v1.0.0", &head, &Signature::now("Test", "test@example.com").unwrap(), "Release", false).unwrap();
+        let created = poller.poll_pipeline(&project, &pipeline).await.unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].branch(), "v1.0.0");
+        assert_eq!(created[0].commit_sha(), second.to_string());
+
+        // Pipelines are only polled again once their interval has passed
+        assert!(poller.poll_due().await.unwrap().is_empty());
+        let third = commit(&origin, "# Project v3");
+        assert!(poller.poll_due().await.unwrap().is_empty());
+
+        // After a restart, commits pushed since the last build are built
+        let restarted = new_poller(&projects, &pipelines, &builds);
+        let created = restarted.poll_pipeline(&project, &pipeline).await.unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].commit_sha(), third.to_string());
+        assert_eq!(created[0].branch(), "main");
+        assert!(restarted.poll_pipeline(&project, &pipeline).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_branch_and_tag_with_the_same_name() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let first = commit(&origin, "# Project");
+        origin.branch("v1", &origin.find_commit(first).unwrap(), false).unwrap();
+
+        let (projects, pipelines, builds, project, pipeline) = poll_pipeline_of(origin_dir.path(), &["v1"], &["v1"], Arc::new(InMemoryBuildRepository::new())).await;
+        let poller = new_poller(&projects, &pipelines, &builds);
+        assert!(poller.poll_pipeline(&project, &pipeline).await.unwrap().is_empty());
+
+        let second = commit(&origin, "# Project v2");
+        origin.branch("v1", &origin.find_commit(second).unwrap(), true).unwrap();
+        let created = poller.poll_pipeline(&project, &pipeline).await.unwrap();
+        assert_eq!(created.len(), 1);
+        assert!(!created[0].is_tag());
+
+        let third = commit(&origin, "# Project v3");
+        let head = origin.find_object(third, None).unwrap();
+        origin.tag_lightweight("v1", &head, false).unwrap();
+        let created = poller.poll_pipeline(&project, &pipeline).await.unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].git_ref(), "refs/tags/v1");
+
+        // Neither ref moved since it was built
+        let restarted = new_poller(&projects, &pipelines, &builds);
+        assert!(restarted.poll_pipeline(&project, &pipeline).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_build_is_retried() {
+        let origin_dir = tempfile::tempdir().unwrap();
+        let origin = Repository::init(origin_dir.path()).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        commit(&origin, "# Project");
+
+        let repository = Arc::new(FlakyBuildRepository {
+            inner: InMemoryBuildRepository::new(),
+            failing: AtomicBool::new(false),
+        });
+        let (projects, pipelines, builds, project, pipeline) =
+            poll_pipeline_of(origin_dir.path(), &["main"], &[], repository.clone()).await;
+        let poller = new_poller(&projects, &pipelines, &builds);
+        assert!(poller.poll_pipeline(&project, &pipeline).await.unwrap().is_empty());
+
+        let second = commit(&origin, "# Project v2");
+        repository.failing.store(true, Ordering::SeqCst);
+        assert!(poller.poll_pipeline(&project, &pipeline).await.is_err());
+
+        repository.failing.store(false, Ordering::SeqCst);
+        let created = poller.poll_pipeline(&project, &pipeline).await.unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].commit_sha(), second.to_string());
+        assert!(poller.poll_pipeline(&project, &pipeline).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+//! Network transfers shared by checkouts and mirrors
+//!
+//! Every fetch authenticates with the configured SSH key or an HTTPS token,
+//! verifies SSH host keys against `known_hosts`, and is aborted from the
+//! transfer progress callback once it is cancelled or exceeds the maximum
+//! repository size.
+
+use crate::config::GitConfig;
+use git2::{CertificateCheckStatus, Cred, CredentialType, FetchOptions, RemoteCallbacks};
+use reqwest::Url;
+use std::cell::Cell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// User name sent with HTTPS tokens when the URL does not carry one
+const TOKEN_USERNAME: &str = "x-access-token";
+
+/// Port of SSH URLs without one, including `user@host:path` ones
+const DEFAULT_SSH_PORT: u16 = 22;
+
+/// Why a transfer was aborted from the progress callback
+#[derive(Clone, Copy, PartialEq)]
+enum Abort {
+    Cancelled,
+    TooLarge,
+}
+
+/// Credentials and limits shared by the transfers of one operation
+pub(super) struct Transfer<'a> {
+    config: &'a GitConfig,
+    /// Port host keys are checked for
+    ssh_port: u16,
+    https_token: Option<&'a str>,
+    cancelled: &'a AtomicBool,
+    abort: Cell<Option<Abort>>,
+}
+
+impl<'a> Transfer<'a> {
+    pub(super) fn new(
+        config: &'a GitConfig,
+        repository_url: &str,
+        https_token: Option<&'a str>,
+        cancelled: &'a AtomicBool,
+    ) -> Self {
+        Self {
+            config,
+            ssh_port: ssh_port(repository_url),
+            https_token,
+            cancelled,
+            abort: Cell::new(None),
+        }
+    }
+
+    /// Fetch options with credentials, host key checks and limits
+    pub(super) fn fetch_options(&self) -> FetchOptions<'_> {
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(self.remote_callbacks());
+        options
+    }
+
+    /// Callbacks providing credentials, host key checks and limits
+    pub(super) fn remote_callbacks(&self) -> RemoteCallbacks<'_> {
+        let config = self.config;
+        let ssh_port = self.ssh_port;
+        let max_size = max_size_bytes(config);
+
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.transfer_progress(move |progress| {
+            if self.cancelled.load(Ordering::Relaxed) {
+                self.abort.set(Some(Abort::Cancelled));
+            } else if progress.received_bytes() as u64 > max_size {
+                self.abort.set(Some(Abort::TooLarge));
+            }
+            self.abort.get().is_none()
+        });
+
+        // libgit2 asks again after rejected credentials; one attempt is enough
+        let mut attempted = false;
+        callbacks.credentials(move |_url, username, allowed| {
+            if allowed.contains(CredentialType::USERNAME) {
+                return Cred::username(username.unwrap_or("git"));
+            }
+            if attempted {
+                return Err(git2::Error::from_str("Repository credentials were rejected"));
+            }
+            attempted = true;
+
+            if allowed.contains(CredentialType::SSH_KEY) {
+                if let Some(key) = &config.ssh_key_path {
+                    return Cred::ssh_key(username.unwrap_or("git"), None, Path::new(key), None);
+                }
+            }
+            if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+                if let Some(token) = self.https_token {
+                    return Cred::userpass_plaintext(username.unwrap_or(TOKEN_USERNAME), token);
+                }
+            }
+            Err(git2::Error::from_str("No credentials configured for repository"))
+        });
+
+        callbacks.certificate_check(move |cert, host| {
+            // TLS certificates are left to libgit2's own validation
+            let Some(key) = cert.as_hostkey().and_then(|hostkey| hostkey.hostkey()) else {
+                return Ok(CertificateCheckStatus::CertificatePassthrough);
+            };
+            let known_hosts = known_hosts_path(config)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .unwrap_or_default();
+            if is_known_host(&known_hosts, host, ssh_port, key) {
+                Ok(CertificateCheckStatus::CertificateOk)
+            } else {
+                Err(git2::Error::from_str(&format!("Host key of {host}:{ssh_port} is not in known_hosts")))
+            }
+        });
+
+        callbacks
+    }
+
+    /// Turn the result of a transfer into a crate error, explaining aborts
+    pub(super) fn check(&self, result: Result<(), git2::Error>) -> crate::Result<()> {
+        match (result, self.abort.get()) {
+            (_, Some(Abort::TooLarge)) => Err(too_large(self.config)),
+            (_, Some(Abort::Cancelled)) => Err(crate::Error::timeout("Transfer cancelled")),
+            (result, None) => Ok(result?),
+        }
+    }
+}
+
+/// Run blocking git work on the blocking thread pool with a timeout
+///
+/// A blocking task cannot be killed; on timeout the flag passed to `work` is
+/// set, and transfers using it stop at their next progress report.
+pub(super) async fn run_blocking<T, F>(timeout: Duration, work: F) -> crate::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&AtomicBool) -> crate::Result<T> + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let task = tokio::task::spawn_blocking({
+        let cancelled = cancelled.clone();
+        move || work(&cancelled)
+    });
+
+    if let Ok(joined) = tokio::time::timeout(timeout, task).await {
+        joined.unwrap_or_else(|e| Err(crate::Error::internal(format!("Git task failed: {e}"))))
+    } else {
+        cancelled.store(true, Ordering::Relaxed);
+        Err(crate::Error::timeout(format!("Git operation timed out after {}s", timeout.as_secs())))
+    }
+}
+
+pub(super) fn max_size_bytes(config: &GitConfig) -> u64 {
+    config.max_repo_size.saturating_mul(1024 * 1024)
+}
+
+pub(super) fn too_large(config: &GitConfig) -> crate::Error {
+    crate::Error::git(format!("Repository exceeds the maximum size of {} MB", config.max_repo_size))
+}
+
+/// Get the configured `known_hosts` file, falling back to the user's
+fn known_hosts_path(config: &GitConfig) -> Option<PathBuf> {
+    config
+        .known_hosts_path
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".ssh/known_hosts")))
+}
+
+/// Get the SSH port of a repository URL
+fn ssh_port(repository_url: &str) -> u16 {
+    Url::parse(repository_url)
+        .ok()
+        .and_then(|url| url.port())
+        .unwrap_or(DEFAULT_SSH_PORT)
+}
+
+/// Check a raw SSH host key against the contents of a `known_hosts` file
+///
+/// Host patterns are matched literally: plain host names for the default
+/// port, and `[host]:port` for any other. Hashed host names and
+/// `@cert-authority` lines are not supported; a key listed under `@revoked`
+/// is always rejected.
+fn is_known_host(known_hosts: &str, host: &str, port: u16, key: &[u8]) -> bool {
+    let mut known = false;
+
+    for line in known_hosts.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let marker = line.starts_with('@').then(|| fields.next()).flatten();
+        let (Some(hosts), Some(_key_type), Some(encoded)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+
+        let host_matches = hosts.split(',').any(|pattern| {
+            match pattern.strip_prefix('[').and_then(|p| p.split_once("]:")) {
+                Some((name, pattern_port)) => name == host && pattern_port.parse() == Ok(port),
+                None => pattern == host && port == DEFAULT_SSH_PORT,
+            }
+        });
+        let key_matches = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+            .is_ok_and(|decoded| decoded == key);
+
+        match marker {
+            Some("@revoked") if key_matches => return false,
+            None if host_matches && key_matches => known = true,
+            _ => {}
+        }
+    }
+
+    known
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_hosts() {
+        let key = b"\x00\x00\x00\x0bssh-ed25519key-bytes";
+        let other = b"\x00\x00\x00\x0bssh-ed25519other-key";
+        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, key);
+        let known_hosts = format!(
+            "# comment\n\
+             github.com,140.82.121.4 ssh-ed25519 {encoded}\n\
+             [git.example.com]:2222 ssh-ed25519 {encoded}\n"
+        );
+
+        assert!(is_known_host(&known_hosts, "github.com", 22, key));
+        assert!(is_known_host(&known_hosts, "git.example.com", 2222, key));
+        assert!(!is_known_host(&known_hosts, "github.com", 22, other));
+        assert!(!is_known_host(&known_hosts, "gitlab.com", 22, key));
+        assert!(!is_known_host("", "github.com", 22, key));
+
+        // Entries only vouch for their own port
+        assert!(!is_known_host(&known_hosts, "github.com", 2222, key));
+        assert!(!is_known_host(&known_hosts, "git.example.com", 22, key));
+        assert!(!is_known_host(&known_hosts, "git.example.com", 2223, key));
+
+        let revoked = format!("{known_hosts}@revoked * ssh-ed25519 {encoded}\n");
+        assert!(!is_known_host(&revoked, "github.com", 22, key));
+    }
+
+    #[test]
+    fn test_ssh_port() {
+        assert_eq!(ssh_port("ssh://git@git.example.com:2222/team/app.git"), 2222);
+        assert_eq!(ssh_port("ssh://git@github.com/team/app.git"), 22);
+        assert_eq!(ssh_port("git@github.com:team/app.git"), 22);
+    }
+}
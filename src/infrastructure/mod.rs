@@ -4,4 +4,7 @@ pub mod repositories;
 pub mod git;
 pub mod storage;
 pub mod database;
+pub mod events;
+pub mod webhooks;
+pub mod executor;
 
@@ -0,0 +1,663 @@
+//! File-backed repository implementations
+
+use super::in_memory::{InMemoryArtifactRepository, InMemoryBlobRepository, InMemoryCacheRepository};
+use crate::domain::entities::artifact::Artifact;
+use crate::domain::events::DomainEvent;
+use crate::domain::repositories::artifact::ArtifactRepository;
+use crate::domain::repositories::blob::{Blob, BlobRepository, BlobStats};
+use crate::domain::repositories::cache::{CacheEntry, CacheRepository};
+use crate::domain::repositories::event::{EventQueryOptions, EventRepository, StoredEvent};
+use crate::domain::value_objects::{artifact_id::ArtifactId, build_id::BuildId, project_id::ProjectId};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, RwLock};
+
+/// Event log stored as an append-only JSON lines file
+///
+/// The log is loaded into memory when opened; every appended event is written to
+/// the file before it becomes visible to queries.
+pub struct FileEventRepository {
+    path: PathBuf,
+    state: RwLock<FileEventLog>,
+}
+
+struct FileEventLog {
+    file: File,
+    events: Vec<StoredEvent>,
+}
+
+impl FileEventRepository {
+    /// Open the log, creating it if it does not exist
+    pub async fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = open_log(&path).await?;
+        let events = read_log(&path, &file, "event log").await?;
+
+        Ok(Self {
+            path,
+            state: RwLock::new(FileEventLog { file, events }),
+        })
+    }
+
+    /// Get the log file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Open a log for appending, creating it and its directory if needed
+async fn open_log(path: &Path) -> crate::Result<File> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    Ok(OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .await?)
+}
+
+/// Read the entries of a JSON lines log
+///
+/// A crash while appending can leave a partial entry at the end of the log.
+/// That line is truncated away so the next append starts on a fresh line;
+/// any other line that does not parse is reported as corruption.
+async fn read_log<T: DeserializeOwned>(path: &Path, file: &File, what: &str) -> crate::Result<Vec<T>> {
+    let content = tokio::fs::read(path).await?;
+    let mut entries = Vec::new();
+    let mut start = 0;
+    let mut number = 0;
+
+    while start < content.len() {
+        let end = content[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(content.len(), |i| start + i + 1);
+        let line = &content[start..end];
+        number += 1;
+
+        if !line.trim_ascii().is_empty() {
+            match serde_json::from_slice(line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) if content[end..].trim_ascii().is_empty() => {
+                    tracing::warn!(
+                        "Dropping incomplete last line {} of {} {}: {}",
+                        number,
+                        what,
+                        path.display(),
+                        e
+                    );
+                    file.set_len(start as u64).await?;
+                    return Ok(entries);
+                }
+                Err(e) => {
+                    return Err(crate::Error::storage(format!(
+                        "Corrupt {} {} at line {}: {}",
+                        what,
+                        path.display(),
+                        number,
+                        e
+                    )));
+                }
+            }
+        }
+        start = end;
+    }
+
+    if content.last().is_some_and(|&b| b != b'\n') {
+        // The last entry was written but its line ending was not
+        let mut file = file.try_clone().await?;
+        file.write_all(b"\n").await?;
+        file.flush().await?;
+    }
+
+    Ok(entries)
+}
+
+/// Replace a log with the entries describing its current state, returning
+/// it opened for appending
+///
+/// The entries are written to a temporary file that then replaces the log, so
+/// a crash leaves either the old or the new log.
+async fn rewrite_log<T: Serialize>(path: &Path, entries: &[T]) -> crate::Result<File> {
+    let mut content = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut content, entry)?;
+        content.push(b'\n');
+    }
+
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    let mut file = File::create(&temporary).await?;
+    file.write_all(&content).await?;
+    file.sync_all().await?;
+    tokio::fs::rename(&temporary, path).await?;
+
+    open_log(path).await
+}
+
+/// Append a change to a log
+async fn write_change<T: Serialize>(file: &mut File, change: &T) -> crate::Result<()> {
+    let mut line = serde_json::to_string(change)?;
+    line.push('\n');
+    file.write_all(line.as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+#[async_trait]
+impl EventRepository for FileEventRepository {
+    async fn append(&self, event: &DomainEvent, project_id: Option<ProjectId>) -> crate::Result<StoredEvent> {
+        let mut state = self.state.write().await;
+        let sequence = state.events.last().map_or(0, |e| e.sequence) + 1;
+        let stored = StoredEvent::new(sequence, event.clone(), project_id);
+
+        let mut line = serde_json::to_string(&stored)?;
+        line.push('\n');
+        state.file.write_all(line.as_bytes()).await?;
+        state.file.flush().await?;
+
+        state.events.push(stored.clone());
+        Ok(stored)
+    }
+
+    async fn query(&self, options: EventQueryOptions) -> crate::Result<Vec<StoredEvent>> {
+        let state = self.state.read().await;
+        Ok(state
+            .events
+            .iter()
+            .filter(|e| options.matches(e))
+            .take(options.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+
+    async fn latest_sequence(&self) -> crate::Result<u64> {
+        let state = self.state.read().await;
+        Ok(state.events.last().map_or(0, |e| e.sequence))
+    }
+}
+
+/// Blob index stored as an append-only JSON lines log of its changes
+///
+/// The log is replayed into memory and compacted to the current links and
+/// blobs when opened; every change is written to the file before it becomes
+/// visible to lookups.
+pub struct FileBlobRepository {
+    file: Mutex<File>,
+    blobs: InMemoryBlobRepository,
+}
+
+/// A change to the blob index, as written to the log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+enum BlobChange {
+    Link { key: String, checksum: String, size: u64 },
+    Unlink { key: String },
+    Delete { checksum: String },
+    /// A blob no key links to that has not been deleted yet
+    Unreferenced { checksum: String, size: u64 },
+}
+
+impl FileBlobRepository {
+    /// Open the index, creating it if it does not exist
+    pub async fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = open_log(path).await?;
+
+        let blobs = InMemoryBlobRepository::new();
+        let mut links = BTreeMap::new();
+        for change in read_log(path, &file, "blob index").await? {
+            match change {
+                BlobChange::Link { key, checksum, size } => {
+                    blobs.link(&key, &checksum, size).await?;
+                    links.insert(key, (checksum, size));
+                }
+                BlobChange::Unlink { key } => {
+                    blobs.unlink(&key).await?;
+                    links.remove(&key);
+                }
+                BlobChange::Delete { checksum } => {
+                    blobs.delete_unreferenced(&checksum).await?;
+                }
+                BlobChange::Unreferenced { checksum, size } => blobs.add_unreferenced(&checksum, size).await,
+            }
+        }
+
+        let mut snapshot: Vec<BlobChange> = blobs
+            .find_unreferenced()
+            .await?
+            .into_iter()
+            .map(|blob| BlobChange::Unreferenced {
+                checksum: blob.checksum,
+                size: blob.size,
+            })
+            .collect();
+        snapshot.extend(
+            links
+                .into_iter()
+                .map(|(key, (checksum, size))| BlobChange::Link { key, checksum, size }),
+        );
+        let file = rewrite_log(path, &snapshot).await?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            blobs,
+        })
+    }
+}
+
+#[async_trait]
+impl BlobRepository for FileBlobRepository {
+    async fn link(&self, key: &str, checksum: &str, size: u64) -> crate::Result<()> {
+        let mut file = self.file.lock().await;
+        let change = BlobChange::Link {
+            key: key.to_string(),
+            checksum: checksum.to_string(),
+            size,
+        };
+        write_change(&mut file, &change).await?;
+        self.blobs.link(key, checksum, size).await
+    }
+
+    async fn unlink(&self, key: &str) -> crate::Result<Option<Blob>> {
+        let mut file = self.file.lock().await;
+        if self.blobs.find(key).await?.is_none() {
+            return Ok(None);
+        }
+        write_change(&mut file, &BlobChange::Unlink { key: key.to_string() }).await?;
+        self.blobs.unlink(key).await
+    }
+
+    async fn find(&self, key: &str) -> crate::Result<Option<Blob>> {
+        self.blobs.find(key).await
+    }
+
+    async fn find_unreferenced(&self) -> crate::Result<Vec<Blob>> {
+        self.blobs.find_unreferenced().await
+    }
+
+    async fn delete_unreferenced(&self, checksum: &str) -> crate::Result<bool> {
+        let mut file = self.file.lock().await;
+        let deleted = self.blobs.delete_unreferenced(checksum).await?;
+        if deleted {
+            write_change(&mut file, &BlobChange::Delete { checksum: checksum.to_string() }).await?;
+        }
+        Ok(deleted)
+    }
+
+    async fn stats(&self) -> crate::Result<BlobStats> {
+        self.blobs.stats().await
+    }
+}
+
+/// Artifact records stored as an append-only JSON lines log of their changes
+///
+/// The log is replayed into memory and compacted to the current records when
+/// opened, like the blob index.
+pub struct FileArtifactRepository {
+    file: Mutex<File>,
+    artifacts: InMemoryArtifactRepository,
+}
+
+/// A change to the artifact records, as written to the log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+enum ArtifactChange {
+    Save { artifact: Box<Artifact> },
+    Delete { id: ArtifactId },
+}
+
+impl FileArtifactRepository {
+    /// Open the records, creating them if they do not exist
+    pub async fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = open_log(path).await?;
+
+        let artifacts = InMemoryArtifactRepository::new();
+        for change in read_log(path, &file, "artifact records").await? {
+            match change {
+                ArtifactChange::Save { artifact } => artifacts.save(&artifact).await?,
+                ArtifactChange::Delete { id } => artifacts.delete(&id).await?,
+            }
+        }
+
+        let snapshot: Vec<ArtifactChange> = artifacts
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|artifact| ArtifactChange::Save {
+                artifact: Box::new(artifact),
+            })
+            .collect();
+        let file = rewrite_log(path, &snapshot).await?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            artifacts,
+        })
+    }
+}
+
+#[async_trait]
+impl ArtifactRepository for FileArtifactRepository {
+    async fn save(&self, artifact: &Artifact) -> crate::Result<()> {
+        let mut file = self.file.lock().await;
+        let change = ArtifactChange::Save {
+            artifact: Box::new(artifact.clone()),
+        };
+        write_change(&mut file, &change).await?;
+        self.artifacts.save(artifact).await
+    }
+
+    async fn find_by_id(&self, id: &ArtifactId) -> crate::Result<Option<Artifact>> {
+        self.artifacts.find_by_id(id).await
+    }
+
+    async fn find_by_build(&self, build_id: &BuildId) -> crate::Result<Vec<Artifact>> {
+        self.artifacts.find_by_build(build_id).await
+    }
+
+    async fn find_all(&self) -> crate::Result<Vec<Artifact>> {
+        self.artifacts.find_all().await
+    }
+
+    async fn update(&self, artifact: &Artifact) -> crate::Result<()> {
+        self.save(artifact).await
+    }
+
+    async fn delete(&self, id: &ArtifactId) -> crate::Result<()> {
+        let mut file = self.file.lock().await;
+        write_change(&mut file, &ArtifactChange::Delete { id: id.clone() }).await?;
+        self.artifacts.delete(id).await
+    }
+}
+
+/// Dependency cache entries stored as an append-only JSON lines log of their
+/// changes
+///
+/// The log is replayed into memory and compacted to the current entries when
+/// opened, like the blob index.
+pub struct FileCacheRepository {
+    file: Mutex<File>,
+    entries: InMemoryCacheRepository,
+}
+
+/// A change to the cache entries, as written to the log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+enum CacheChange {
+    Save { entry: CacheEntry },
+    Delete { project_id: ProjectId, git_ref: String, key: String },
+}
+
+impl FileCacheRepository {
+    /// Open the entries, creating them if they do not exist
+    pub async fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = open_log(path).await?;
+
+        let entries = InMemoryCacheRepository::new();
+        for change in read_log(path, &file, "cache entries").await? {
+            match change {
+                CacheChange::Save { entry } => entries.save(&entry).await?,
+                CacheChange::Delete { project_id, git_ref, key } => entries.delete(&project_id, &git_ref, &key).await?,
+            }
+        }
+
+        let snapshot: Vec<CacheChange> = entries
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|entry| CacheChange::Save { entry })
+            .collect();
+        let file = rewrite_log(path, &snapshot).await?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            entries,
+        })
+    }
+}
+
+#[async_trait]
+impl CacheRepository for FileCacheRepository {
+    async fn save(&self, entry: &CacheEntry) -> crate::Result<()> {
+        let mut file = self.file.lock().await;
+        write_change(&mut file, &CacheChange::Save { entry: entry.clone() }).await?;
+        self.entries.save(entry).await
+    }
+
+    async fn find(&self, project_id: &ProjectId, git_ref: &str, key: &str) -> crate::Result<Option<CacheEntry>> {
+        self.entries.find(project_id, git_ref, key).await
+    }
+
+    async fn find_by_prefix(&self, project_id: &ProjectId, git_ref: &str, prefix: &str) -> crate::Result<Vec<CacheEntry>> {
+        self.entries.find_by_prefix(project_id, git_ref, prefix).await
+    }
+
+    async fn find_all(&self) -> crate::Result<Vec<CacheEntry>> {
+        self.entries.find_all().await
+    }
+
+    async fn delete(&self, project_id: &ProjectId, git_ref: &str, key: &str) -> crate::Result<()> {
+        let mut file = self.file.lock().await;
+        let change = CacheChange::Delete {
+            project_id: project_id.clone(),
+            git_ref: git_ref.to_string(),
+            key: key.to_string(),
+        };
+        write_change(&mut file, &change).await?;
+        self.entries.delete(project_id, git_ref, key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::artifact::ArtifactType;
+    use crate::domain::value_objects::agent_id::AgentId;
+    use chrono::Utc;
+
+    fn agent_disconnected() -> DomainEvent {
+        DomainEvent::AgentDisconnected {
+            agent_id: AgentId::new(),
+            disconnected_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log").join("events.jsonl");
+
+        let repository = FileEventRepository::open(&path).await.unwrap();
+        let first = agent_disconnected();
+        repository.append(&first, None).await.unwrap();
+        repository.append(&agent_disconnected(), None).await.unwrap();
+        drop(repository);
+
+        let reopened = FileEventRepository::open(&path).await.unwrap();
+        assert_eq!(reopened.latest_sequence().await.unwrap(), 2);
+
+        let stored = reopened.append(&agent_disconnected(), None).await.unwrap();
+        assert_eq!(stored.sequence, 3);
+
+        let events = reopened.query(EventQueryOptions {
+            aggregate_id: Some(first.aggregate_id()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, first);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_log_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let stored = serde_json::to_string(&StoredEvent::new(1, agent_disconnected(), None)).unwrap();
+        tokio::fs::write(&path, format!("{stored}\n\nnot json\n{stored}\n")).await.unwrap();
+
+        let error = FileEventRepository::open(&path).await.err().unwrap();
+        assert!(error.to_string().contains("at line 3"));
+    }
+
+    #[tokio::test]
+    async fn test_torn_last_line_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+
+        let repository = FileEventRepository::open(&path).await.unwrap();
+        repository.append(&agent_disconnected(), None).await.unwrap();
+        drop(repository);
+        let mut file = OpenOptions::new().append(true).open(&path).await.unwrap();
+        file.write_all(b"{\"sequence\":2,\"ev").await.unwrap();
+        drop(file);
+
+        let reopened = FileEventRepository::open(&path).await.unwrap();
+        assert_eq!(reopened.latest_sequence().await.unwrap(), 1);
+        reopened.append(&agent_disconnected(), None).await.unwrap();
+        drop(reopened);
+
+        let reopened = FileEventRepository::open(&path).await.unwrap();
+        assert_eq!(reopened.latest_sequence().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_blob_index_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index").join("blobs.jsonl");
+
+        let repository = FileBlobRepository::open(&path).await.unwrap();
+        repository.link("p/1/app", "aaaa", 5).await.unwrap();
+        repository.link("p/2/app", "aaaa", 5).await.unwrap();
+        repository.link("p/2/lib", "bbbb", 3).await.unwrap();
+        repository.link("p/3/lib", "cccc", 2).await.unwrap();
+        repository.link("p/4/doc", "dddd", 4).await.unwrap();
+        repository.unlink("p/1/app").await.unwrap();
+        repository.unlink("p/3/lib").await.unwrap();
+        repository.unlink("p/4/doc").await.unwrap();
+        assert!(repository.unlink("p/missing").await.unwrap().is_none());
+        assert!(repository.delete_unreferenced("cccc").await.unwrap());
+        drop(repository);
+
+        // Reopening compacts the log to the current links and blobs, keeping
+        // the blobs not collected yet
+        for _ in 0..2 {
+            let reopened = FileBlobRepository::open(&path).await.unwrap();
+            assert!(reopened.find("p/1/app").await.unwrap().is_none());
+            assert_eq!(reopened.find("p/2/app").await.unwrap().unwrap().ref_count, 1);
+            assert_eq!(reopened.find("p/2/lib").await.unwrap().unwrap().checksum, "bbbb");
+            let unreferenced = reopened.find_unreferenced().await.unwrap();
+            assert_eq!(unreferenced.len(), 1);
+            assert_eq!(unreferenced[0].checksum, "dddd");
+            assert_eq!(
+                reopened.stats().await.unwrap(),
+                BlobStats { objects: 2, blobs: 3, logical_bytes: 8, physical_bytes: 12 }
+            );
+            assert_eq!(tokio::fs::read_to_string(&path).await.unwrap().lines().count(), 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blob_index_torn_last_line_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blobs.jsonl");
+
+        let repository = FileBlobRepository::open(&path).await.unwrap();
+        repository.link("p/1/app", "aaaa", 5).await.unwrap();
+        drop(repository);
+        let mut file = OpenOptions::new().append(true).open(&path).await.unwrap();
+        file.write_all(b"{\"change\":\"link\",\"key\":\"p/2").await.unwrap();
+        drop(file);
+
+        let reopened = FileBlobRepository::open(&path).await.unwrap();
+        assert_eq!(reopened.find("p/1/app").await.unwrap().unwrap().checksum, "aaaa");
+        reopened.link("p/2/app", "aaaa", 5).await.unwrap();
+        drop(reopened);
+
+        let reopened = FileBlobRepository::open(&path).await.unwrap();
+        assert_eq!(reopened.find("p/2/app").await.unwrap().unwrap().ref_count, 2);
+
+        tokio::fs::write(&path, "not json\n{\"change\":\"unlink\",\"key\":\"p/1/app\"}\n").await.unwrap();
+        let error = FileBlobRepository::open(&path).await.err().unwrap();
+        assert!(error.to_string().contains("at line 1"));
+    }
+
+    #[tokio::test]
+    async fn test_artifact_records_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("records").join("artifacts.jsonl");
+        let build_id = BuildId::new();
+        let artifact = |name: &str| {
+            Artifact::new(
+                build_id.clone(),
+                name.to_string(),
+                format!("p/{build_id}/{name}"),
+                5,
+                "aaaa".to_string(),
+                ArtifactType::BuildOutput,
+            )
+        };
+
+        let repository = FileArtifactRepository::open(&path).await.unwrap();
+        let mut app = artifact("app");
+        let docs = artifact("docs");
+        repository.save(&app).await.unwrap();
+        repository.save(&docs).await.unwrap();
+        app.expire();
+        repository.update(&app).await.unwrap();
+        repository.delete(docs.id()).await.unwrap();
+        drop(repository);
+
+        for _ in 0..2 {
+            let reopened = FileArtifactRepository::open(&path).await.unwrap();
+            let artifacts = reopened.find_by_build(&build_id).await.unwrap();
+            assert_eq!(artifacts.len(), 1);
+            assert!(artifacts[0].is_marked_expired());
+            assert!(reopened.find_by_id(docs.id()).await.unwrap().is_none());
+            assert_eq!(tokio::fs::read_to_string(&path).await.unwrap().lines().count(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_entries_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("records").join("caches.jsonl");
+        let project_id = ProjectId::new();
+        let entry = |key: &str| CacheEntry {
+            project_id: project_id.clone(),
+            git_ref: "refs/heads/main".to_string(),
+            key: key.to_string(),
+            path: format!("{project_id}/refs/heads/main/{key}.tar.zst"),
+            size: 5,
+            checksum: "aaaa".to_string(),
+            created_at: Utc::now(),
+            last_used_at: Utc::now(),
+        };
+
+        let repository = FileCacheRepository::open(&path).await.unwrap();
+        let mut cargo = entry("cargo-1");
+        repository.save(&cargo).await.unwrap();
+        repository.save(&entry("npm-1")).await.unwrap();
+        cargo.last_used_at = Utc::now();
+        repository.save(&cargo).await.unwrap();
+        repository.delete(&project_id, "refs/heads/main", "npm-1").await.unwrap();
+        drop(repository);
+
+        for _ in 0..2 {
+            let reopened = FileCacheRepository::open(&path).await.unwrap();
+            assert_eq!(reopened.find(&project_id, "refs/heads/main", "cargo-1").await.unwrap().as_ref(), Some(&cargo));
+            assert!(reopened.find(&project_id, "refs/heads/main", "npm-1").await.unwrap().is_none());
+            assert_eq!(tokio::fs::read_to_string(&path).await.unwrap().lines().count(), 1);
+        }
+    }
+}
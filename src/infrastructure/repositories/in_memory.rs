@@ -4,6 +4,12 @@ use crate::domain::entities::{
     pipeline::Pipeline,
     build::Build,
     agent::{Agent, AgentStatus},
+    project::Project,
+    user::{User, UserRole},
+    membership::ProjectMember,
+    api_token::ApiToken,
+    secret::{Secret, SecretScope},
+    artifact::Artifact,
 };
 use crate::domain::value_objects::{
     pipeline_id::PipelineId,
@@ -11,11 +17,24 @@ use crate::domain::value_objects::{
     project_id::ProjectId,
     agent_id::AgentId,
     build_status::BuildStatus,
+    user_id::UserId,
+    api_token_id::ApiTokenId,
+    artifact_id::ArtifactId,
 };
+use crate::domain::events::DomainEvent;
 use crate::domain::repositories::{
     pipeline::PipelineRepository,
     build::{BuildRepository, BuildQueryOptions},
     agent::AgentRepository,
+    project::ProjectRepository,
+    user::UserRepository,
+    membership::MembershipRepository,
+    api_token::ApiTokenRepository,
+    secret::SecretRepository,
+    event::{EventRepository, EventQueryOptions, StoredEvent},
+    artifact::ArtifactRepository,
+    cache::{CacheEntry, CacheRepository},
+    blob::{Blob, BlobRepository, BlobStats},
 };
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -131,10 +150,29 @@ impl BuildRepository for InMemoryBuildRepository {
             .collect())
     }
     
-    async fn query(&self, _options: BuildQueryOptions) -> crate::Result<Vec<Build>> {
-        // Simplified implementation
+    async fn query(&self, options: BuildQueryOptions) -> crate::Result<Vec<Build>> {
         let builds = self.builds.read().await;
-        Ok(builds.values().cloned().collect())
+        let mut matching: Vec<Build> = builds
+            .values()
+            .filter(|b| options.project_id.as_ref().is_none_or(|id| b.project_id() == id))
+            .filter(|b| options.project_ids.as_ref().is_none_or(|ids| ids.contains(b.project_id())))
+            .filter(|b| options.pipeline_id.as_ref().is_none_or(|id| b.pipeline_id() == id))
+            .filter(|b| options.status.as_ref().is_none_or(|status| b.status() == status))
+            .filter(|b| options.branch.as_deref().is_none_or(|branch| b.branch() == branch))
+            .cloned()
+            .collect();
+        
+        // Only creation order is supported for sorting
+        matching.sort_by_key(Build::created_at);
+        if options.sort_desc {
+            matching.reverse();
+        }
+        
+        Ok(matching
+            .into_iter()
+            .skip(options.offset.unwrap_or(0))
+            .take(options.limit.unwrap_or(usize::MAX))
+            .collect())
     }
     
     async fn find_running(&self) -> crate::Result<Vec<Build>> {
@@ -259,3 +297,593 @@ impl AgentRepository for InMemoryAgentRepository {
     }
 }
 
+/// In-memory project repository
+pub struct InMemoryProjectRepository {
+    projects: Arc<RwLock<HashMap<String, Project>>>,
+}
+
+impl InMemoryProjectRepository {
+    pub fn new() -> Self {
+        Self {
+            projects: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryProjectRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ProjectRepository for InMemoryProjectRepository {
+    async fn save(&self, project: &Project) -> crate::Result<()> {
+        let mut projects = self.projects.write().await;
+        projects.insert(project.id().to_string(), project.clone());
+        Ok(())
+    }
+    
+    async fn find_by_id(&self, id: &ProjectId) -> crate::Result<Option<Project>> {
+        let projects = self.projects.read().await;
+        Ok(projects.get(&id.to_string()).cloned())
+    }
+    
+    async fn find_by_name(&self, name: &str) -> crate::Result<Option<Project>> {
+        let projects = self.projects.read().await;
+        Ok(projects.values().find(|p| p.name() == name).cloned())
+    }
+    
+    async fn find_all(&self) -> crate::Result<Vec<Project>> {
+        let projects = self.projects.read().await;
+        Ok(projects.values().cloned().collect())
+    }
+    
+    async fn update(&self, project: &Project) -> crate::Result<()> {
+        self.save(project).await
+    }
+    
+    async fn delete(&self, id: &ProjectId) -> crate::Result<()> {
+        let mut projects = self.projects.write().await;
+        projects.remove(&id.to_string());
+        Ok(())
+    }
+    
+    async fn exists(&self, id: &ProjectId) -> crate::Result<bool> {
+        let projects = self.projects.read().await;
+        Ok(projects.contains_key(&id.to_string()))
+    }
+    
+    async fn name_exists(&self, name: &str) -> crate::Result<bool> {
+        let projects = self.projects.read().await;
+        Ok(projects.values().any(|p| p.name() == name))
+    }
+}
+
+/// In-memory user repository
+pub struct InMemoryUserRepository {
+    users: Arc<RwLock<HashMap<String, User>>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self {
+            users: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryUserRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn save(&self, user: &User) -> crate::Result<()> {
+        let mut users = self.users.write().await;
+        users.insert(user.id().to_string(), user.clone());
+        Ok(())
+    }
+    
+    async fn find_by_id(&self, id: &UserId) -> crate::Result<Option<User>> {
+        let users = self.users.read().await;
+        Ok(users.get(&id.to_string()).cloned())
+    }
+    
+    async fn find_by_username(&self, username: &str) -> crate::Result<Option<User>> {
+        let users = self.users.read().await;
+        Ok(users.values().find(|u| u.username() == username).cloned())
+    }
+    
+    async fn find_by_email(&self, email: &str) -> crate::Result<Option<User>> {
+        let users = self.users.read().await;
+        Ok(users.values().find(|u| u.email() == email).cloned())
+    }
+    
+    async fn find_all(&self) -> crate::Result<Vec<User>> {
+        let users = self.users.read().await;
+        Ok(users.values().cloned().collect())
+    }
+    
+    async fn find_by_role(&self, role: &UserRole) -> crate::Result<Vec<User>> {
+        let users = self.users.read().await;
+        Ok(users
+            .values()
+            .filter(|u| u.role() == role)
+            .cloned()
+            .collect())
+    }
+    
+    async fn find_active(&self) -> crate::Result<Vec<User>> {
+        let users = self.users.read().await;
+        Ok(users
+            .values()
+            .filter(|u| u.is_active())
+            .cloned()
+            .collect())
+    }
+    
+    async fn update(&self, user: &User) -> crate::Result<()> {
+        self.save(user).await
+    }
+    
+    async fn delete(&self, id: &UserId) -> crate::Result<()> {
+        let mut users = self.users.write().await;
+        users.remove(&id.to_string());
+        Ok(())
+    }
+    
+    async fn exists(&self, id: &UserId) -> crate::Result<bool> {
+        let users = self.users.read().await;
+        Ok(users.contains_key(&id.to_string()))
+    }
+    
+    async fn username_exists(&self, username: &str) -> crate::Result<bool> {
+        let users = self.users.read().await;
+        Ok(users.values().any(|u| u.username() == username))
+    }
+    
+    async fn email_exists(&self, email: &str) -> crate::Result<bool> {
+        let users = self.users.read().await;
+        Ok(users.values().any(|u| u.email() == email))
+    }
+}
+
+/// In-memory project membership repository
+pub struct InMemoryMembershipRepository {
+    members: Arc<RwLock<HashMap<(String, String), ProjectMember>>>,
+}
+
+impl InMemoryMembershipRepository {
+    pub fn new() -> Self {
+        Self {
+            members: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryMembershipRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MembershipRepository for InMemoryMembershipRepository {
+    async fn save(&self, member: &ProjectMember) -> crate::Result<()> {
+        let mut members = self.members.write().await;
+        let key = (member.project_id().to_string(), member.user_id().to_string());
+        members.insert(key, member.clone());
+        Ok(())
+    }
+    
+    async fn find(&self, project_id: &ProjectId, user_id: &UserId) -> crate::Result<Option<ProjectMember>> {
+        let members = self.members.read().await;
+        Ok(members.get(&(project_id.to_string(), user_id.to_string())).cloned())
+    }
+    
+    async fn find_by_project(&self, project_id: &ProjectId) -> crate::Result<Vec<ProjectMember>> {
+        let members = self.members.read().await;
+        Ok(members
+            .values()
+            .filter(|m| m.project_id() == project_id)
+            .cloned()
+            .collect())
+    }
+    
+    async fn find_by_user(&self, user_id: &UserId) -> crate::Result<Vec<ProjectMember>> {
+        let members = self.members.read().await;
+        Ok(members
+            .values()
+            .filter(|m| m.user_id() == user_id)
+            .cloned()
+            .collect())
+    }
+    
+    async fn delete(&self, project_id: &ProjectId, user_id: &UserId) -> crate::Result<()> {
+        let mut members = self.members.write().await;
+        members.remove(&(project_id.to_string(), user_id.to_string()));
+        Ok(())
+    }
+    
+    async fn delete_by_project(&self, project_id: &ProjectId) -> crate::Result<()> {
+        let mut members = self.members.write().await;
+        members.retain(|_, m| m.project_id() != project_id);
+        Ok(())
+    }
+}
+
+/// In-memory API token repository
+pub struct InMemoryApiTokenRepository {
+    tokens: Arc<RwLock<HashMap<String, ApiToken>>>,
+}
+
+impl InMemoryApiTokenRepository {
+    pub fn new() -> Self {
+        Self {
+            tokens: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryApiTokenRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ApiTokenRepository for InMemoryApiTokenRepository {
+    async fn save(&self, token: &ApiToken) -> crate::Result<()> {
+        let mut tokens = self.tokens.write().await;
+        tokens.insert(token.id().to_string(), token.clone());
+        Ok(())
+    }
+    
+    async fn find_by_id(&self, id: &ApiTokenId) -> crate::Result<Option<ApiToken>> {
+        let tokens = self.tokens.read().await;
+        Ok(tokens.get(&id.to_string()).cloned())
+    }
+    
+    async fn find_by_secret_hash(&self, secret_hash: &str) -> crate::Result<Option<ApiToken>> {
+        let tokens = self.tokens.read().await;
+        Ok(tokens.values().find(|t| t.secret_hash() == secret_hash).cloned())
+    }
+    
+    async fn find_by_user(&self, user_id: &UserId) -> crate::Result<Vec<ApiToken>> {
+        let tokens = self.tokens.read().await;
+        let mut found: Vec<ApiToken> = tokens
+            .values()
+            .filter(|t| t.user_id() == user_id)
+            .cloned()
+            .collect();
+        found.sort_by_key(ApiToken::created_at);
+        Ok(found)
+    }
+    
+    async fn update(&self, token: &ApiToken) -> crate::Result<()> {
+        self.save(token).await
+    }
+}
+
+/// In-memory secret repository
+pub struct InMemorySecretRepository {
+    secrets: Arc<RwLock<HashMap<(SecretScope, String), Secret>>>,
+}
+
+impl InMemorySecretRepository {
+    pub fn new() -> Self {
+        Self {
+            secrets: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemorySecretRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SecretRepository for InMemorySecretRepository {
+    async fn save(&self, secret: &Secret) -> crate::Result<()> {
+        let mut secrets = self.secrets.write().await;
+        let key = (secret.scope().clone(), secret.name().to_string());
+        secrets.insert(key, secret.clone());
+        Ok(())
+    }
+    
+    async fn find(&self, scope: &SecretScope, name: &str) -> crate::Result<Option<Secret>> {
+        let secrets = self.secrets.read().await;
+        Ok(secrets.get(&(scope.clone(), name.to_string())).cloned())
+    }
+    
+    async fn find_by_scope(&self, scope: &SecretScope) -> crate::Result<Vec<Secret>> {
+        let secrets = self.secrets.read().await;
+        let mut found: Vec<Secret> = secrets
+            .values()
+            .filter(|s| s.scope() == scope)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.name().cmp(b.name()));
+        Ok(found)
+    }
+    
+    async fn delete(&self, scope: &SecretScope, name: &str) -> crate::Result<()> {
+        let mut secrets = self.secrets.write().await;
+        secrets.remove(&(scope.clone(), name.to_string()));
+        Ok(())
+    }
+    
+    async fn delete_by_scope(&self, scope: &SecretScope) -> crate::Result<()> {
+        let mut secrets = self.secrets.write().await;
+        secrets.retain(|_, s| s.scope() != scope);
+        Ok(())
+    }
+}
+
+/// In-memory artifact repository
+pub struct InMemoryArtifactRepository {
+    artifacts: Arc<RwLock<HashMap<ArtifactId, Artifact>>>,
+}
+
+impl InMemoryArtifactRepository {
+    pub fn new() -> Self {
+        Self {
+            artifacts: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryArtifactRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ArtifactRepository for InMemoryArtifactRepository {
+    async fn save(&self, artifact: &Artifact) -> crate::Result<()> {
+        let mut artifacts = self.artifacts.write().await;
+        artifacts.insert(artifact.id().clone(), artifact.clone());
+        Ok(())
+    }
+    
+    async fn find_by_id(&self, id: &ArtifactId) -> crate::Result<Option<Artifact>> {
+        let artifacts = self.artifacts.read().await;
+        Ok(artifacts.get(id).cloned())
+    }
+    
+    async fn find_by_build(&self, build_id: &BuildId) -> crate::Result<Vec<Artifact>> {
+        let artifacts = self.artifacts.read().await;
+        let mut found: Vec<Artifact> = artifacts
+            .values()
+            .filter(|a| a.build_id() == build_id)
+            .cloned()
+            .collect();
+        found.sort_by_key(Artifact::created_at);
+        Ok(found)
+    }
+    
+    async fn find_all(&self) -> crate::Result<Vec<Artifact>> {
+        let artifacts = self.artifacts.read().await;
+        let mut found: Vec<Artifact> = artifacts.values().cloned().collect();
+        found.sort_by_key(Artifact::created_at);
+        Ok(found)
+    }
+    
+    async fn update(&self, artifact: &Artifact) -> crate::Result<()> {
+        self.save(artifact).await
+    }
+    
+    async fn delete(&self, id: &ArtifactId) -> crate::Result<()> {
+        let mut artifacts = self.artifacts.write().await;
+        artifacts.remove(id);
+        Ok(())
+    }
+}
+
+/// In-memory dependency cache repository
+pub struct InMemoryCacheRepository {
+    entries: Arc<RwLock<HashMap<(ProjectId, String, String), CacheEntry>>>,
+}
+
+impl InMemoryCacheRepository {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryCacheRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CacheRepository for InMemoryCacheRepository {
+    async fn save(&self, entry: &CacheEntry) -> crate::Result<()> {
+        let mut entries = self.entries.write().await;
+        let id = (entry.project_id.clone(), entry.git_ref.clone(), entry.key.clone());
+        entries.insert(id, entry.clone());
+        Ok(())
+    }
+    
+    async fn find(&self, project_id: &ProjectId, git_ref: &str, key: &str) -> crate::Result<Option<CacheEntry>> {
+        let entries = self.entries.read().await;
+        Ok(entries.get(&(project_id.clone(), git_ref.to_string(), key.to_string())).cloned())
+    }
+    
+    async fn find_by_prefix(&self, project_id: &ProjectId, git_ref: &str, prefix: &str) -> crate::Result<Vec<CacheEntry>> {
+        let entries = self.entries.read().await;
+        let mut found: Vec<CacheEntry> = entries
+            .values()
+            .filter(|e| &e.project_id == project_id && e.git_ref == git_ref && e.key.starts_with(prefix))
+            .cloned()
+            .collect();
+        found.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        Ok(found)
+    }
+    
+    async fn find_all(&self) -> crate::Result<Vec<CacheEntry>> {
+        let entries = self.entries.read().await;
+        Ok(entries.values().cloned().collect())
+    }
+    
+    async fn delete(&self, project_id: &ProjectId, git_ref: &str, key: &str) -> crate::Result<()> {
+        let mut entries = self.entries.write().await;
+        entries.remove(&(project_id.clone(), git_ref.to_string(), key.to_string()));
+        Ok(())
+    }
+}
+
+/// In-memory blob repository
+pub struct InMemoryBlobRepository {
+    state: Arc<RwLock<BlobState>>,
+}
+
+/// Links and blobs, changed together under one lock
+#[derive(Default)]
+struct BlobState {
+    links: HashMap<String, String>,
+    blobs: HashMap<String, Blob>,
+}
+
+impl InMemoryBlobRepository {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(RwLock::new(BlobState::default())),
+        }
+    }
+    
+    /// Add a blob no key links to, as restored from a saved index
+    pub(super) async fn add_unreferenced(&self, checksum: &str, size: u64) {
+        let mut state = self.state.write().await;
+        state.blobs.entry(checksum.to_string()).or_insert_with(|| Blob {
+            checksum: checksum.to_string(),
+            size,
+            ref_count: 0,
+        });
+    }
+}
+
+impl Default for InMemoryBlobRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlobState {
+    fn release(&mut self, key: &str) -> Option<Blob> {
+        let checksum = self.links.remove(key)?;
+        let blob = self.blobs.get_mut(&checksum)?;
+        blob.ref_count = blob.ref_count.saturating_sub(1);
+        Some(blob.clone())
+    }
+}
+
+#[async_trait]
+impl BlobRepository for InMemoryBlobRepository {
+    async fn link(&self, key: &str, checksum: &str, size: u64) -> crate::Result<()> {
+        let mut state = self.state.write().await;
+        if state.links.get(key).is_some_and(|linked| linked == checksum) {
+            return Ok(());
+        }
+        
+        state.release(key);
+        state
+            .blobs
+            .entry(checksum.to_string())
+            .or_insert_with(|| Blob {
+                checksum: checksum.to_string(),
+                size,
+                ref_count: 0,
+            })
+            .ref_count += 1;
+        state.links.insert(key.to_string(), checksum.to_string());
+        Ok(())
+    }
+    
+    async fn unlink(&self, key: &str) -> crate::Result<Option<Blob>> {
+        let mut state = self.state.write().await;
+        Ok(state.release(key))
+    }
+    
+    async fn find(&self, key: &str) -> crate::Result<Option<Blob>> {
+        let state = self.state.read().await;
+        Ok(state.links.get(key).and_then(|checksum| state.blobs.get(checksum)).cloned())
+    }
+    
+    async fn find_unreferenced(&self) -> crate::Result<Vec<Blob>> {
+        let state = self.state.read().await;
+        Ok(state.blobs.values().filter(|b| b.ref_count == 0).cloned().collect())
+    }
+    
+    async fn delete_unreferenced(&self, checksum: &str) -> crate::Result<bool> {
+        let mut state = self.state.write().await;
+        if state.blobs.get(checksum).is_some_and(|b| b.ref_count == 0) {
+            state.blobs.remove(checksum);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+    
+    async fn stats(&self) -> crate::Result<BlobStats> {
+        let state = self.state.read().await;
+        Ok(BlobStats {
+            objects: state.links.len() as u64,
+            blobs: state.blobs.len() as u64,
+            logical_bytes: state.blobs.values().map(|b| b.size * b.ref_count).sum(),
+            physical_bytes: state.blobs.values().map(|b| b.size).sum(),
+        })
+    }
+}
+
+/// In-memory event log repository
+pub struct InMemoryEventRepository {
+    events: Arc<RwLock<Vec<StoredEvent>>>,
+}
+
+impl InMemoryEventRepository {
+    pub fn new() -> Self {
+        Self {
+            events: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+impl Default for InMemoryEventRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventRepository for InMemoryEventRepository {
+    async fn append(&self, event: &DomainEvent, project_id: Option<ProjectId>) -> crate::Result<StoredEvent> {
+        let mut events = self.events.write().await;
+        let stored = StoredEvent::new(events.len() as u64 + 1, event.clone(), project_id);
+        events.push(stored.clone());
+        Ok(stored)
+    }
+    
+    async fn query(&self, options: EventQueryOptions) -> crate::Result<Vec<StoredEvent>> {
+        let events = self.events.read().await;
+        Ok(events
+            .iter()
+            .filter(|e| options.matches(e))
+            .take(options.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+    
+    async fn latest_sequence(&self) -> crate::Result<u64> {
+        let events = self.events.read().await;
+        Ok(events.len() as u64)
+    }
+}
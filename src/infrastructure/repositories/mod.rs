@@ -1,5 +1,6 @@
 //! Repository implementations
 
 pub mod in_memory;
+pub mod file;
 pub mod postgres;
 
@@ -0,0 +1,324 @@
+//! Content-addressed artifact store
+//!
+//! Wraps another store so identical contents are kept once. Uploads land
+//! under `uploads/` first; once their SHA256 is known, they become the blob
+//! `blobs/{aa}/{sha256}`, or are dropped if that blob already exists. Keys only
+//! link to blobs once they are in place, with the links and reference counts kept in a
+//! [`BlobRepository`], which must outlive the process for keys to be found
+//! after a restart.
+//!
+//! Deleting a key only releases its reference. Garbage collection deletes
+//! the blobs no key references anymore.
+
+use super::{ArtifactStore, ArtifactStream, StoredArtifact, validate_key};
+use crate::domain::repositories::blob::{BlobRepository, BlobStats};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{RwLock, watch};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// How often unreferenced blobs are collected
+const GC_INTERVAL: Duration = Duration::from_hours(1);
+
+/// What a garbage collection deleted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Number of blobs deleted
+    pub blobs: usize,
+    /// Bytes deleted from the store
+    pub reclaimed_bytes: u64,
+}
+
+/// Artifact store keeping identical contents once
+pub struct DedupArtifactStore {
+    blobs: Arc<dyn ArtifactStore>,
+    index: Arc<dyn BlobRepository>,
+    /// Read while putting blobs in place and linking keys to them, and
+    /// written while collecting garbage, so a blob is not deleted as a new
+    /// key links to it
+    gc_lock: RwLock<()>,
+}
+
+impl DedupArtifactStore {
+    /// Create a store keeping blobs in another store
+    pub fn new(blobs: Arc<dyn ArtifactStore>, index: Arc<dyn BlobRepository>) -> Self {
+        Self {
+            blobs,
+            index,
+            gc_lock: RwLock::new(()),
+        }
+    }
+
+    /// Get the storage used by the store
+    pub async fn stats(&self) -> crate::Result<BlobStats> {
+        self.index.stats().await
+    }
+
+    /// Delete the blobs no key references
+    pub async fn collect_garbage(&self) -> crate::Result<GcReport> {
+        let _lock = self.gc_lock.write().await;
+        let mut report = GcReport::default();
+
+        for blob in self.index.find_unreferenced().await? {
+            let key = blob_key(&blob.checksum);
+            // Deleted from the store first, so a failure leaves it for the
+            // next collection
+            if let Err(e) = self.blobs.delete(&key).await {
+                tracing::warn!("Could not delete blob {}: {}", key, e);
+                continue;
+            }
+            if self.index.delete_unreferenced(&blob.checksum).await? {
+                report.blobs += 1;
+                report.reclaimed_bytes += blob.size;
+            }
+        }
+
+        if report.blobs > 0 {
+            tracing::info!(
+                "Collected {} unreferenced blob(s), reclaiming {} bytes",
+                report.blobs,
+                report.reclaimed_bytes
+            );
+        }
+        Ok(report)
+    }
+
+    /// Collect garbage until `shutdown` becomes true
+    pub async fn run(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(GC_INTERVAL);
+
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+
+            if let Err(e) = self.collect_garbage().await {
+                tracing::warn!("Blob garbage collection failed: {}", e);
+            }
+        }
+    }
+
+    /// Run garbage collection on a background task
+    pub fn spawn(self: Arc<Self>, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(shutdown))
+    }
+
+    /// Get the blob a key links to, failing if there is none
+    async fn linked_blob(&self, key: &str) -> crate::Result<String> {
+        validate_key(key)?;
+        let blob = self
+            .index
+            .find(key)
+            .await?
+            .ok_or_else(|| crate::Error::not_found(format!("Artifact {key} not found")))?;
+        Ok(blob_key(&blob.checksum))
+    }
+}
+
+#[async_trait]
+impl ArtifactStore for DedupArtifactStore {
+    async fn put(
+        &self,
+        key: &str,
+        contents: ArtifactStream,
+        expected_checksum: Option<&str>,
+    ) -> crate::Result<StoredArtifact> {
+        validate_key(key)?;
+        let upload = format!("uploads/{}", Uuid::new_v4());
+        let stored = self.blobs.put(&upload, contents, expected_checksum).await?;
+        let blob = blob_key(&stored.checksum);
+
+        // The key is only linked once the blob is in place, so a failure
+        // leaves whatever it linked to before untouched
+        let _lock = self.gc_lock.read().await;
+        let placed = match self.blobs.exists(&blob).await {
+            Ok(true) => self.blobs.delete(&upload).await,
+            Ok(false) => self.blobs.rename(&upload, &blob).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = placed {
+            let _ = self.blobs.delete(&upload).await;
+            return Err(e);
+        }
+        self.index.link(key, &stored.checksum, stored.size).await?;
+
+        Ok(StoredArtifact {
+            key: key.to_string(),
+            size: stored.size,
+            checksum: stored.checksum,
+        })
+    }
+
+    async fn get(&self, key: &str) -> crate::Result<ArtifactStream> {
+        self.blobs.get(&self.linked_blob(key).await?).await
+    }
+
+    async fn exists(&self, key: &str) -> crate::Result<bool> {
+        validate_key(key)?;
+        Ok(self.index.find(key).await?.is_some())
+    }
+
+    async fn delete(&self, key: &str) -> crate::Result<()> {
+        validate_key(key)?;
+        self.index.unlink(key).await?;
+        Ok(())
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> crate::Result<()> {
+        validate_key(to)?;
+        let blob = self
+            .index
+            .find(from)
+            .await?
+            .ok_or_else(|| crate::Error::not_found(format!("Artifact {from} not found")))?;
+        let _lock = self.gc_lock.read().await;
+        self.index.link(to, &blob.checksum, blob.size).await?;
+        self.index.unlink(from).await?;
+        Ok(())
+    }
+
+    async fn download_url(&self, key: &str, expires_in: Duration) -> crate::Result<Option<String>> {
+        self.blobs.download_url(&self.linked_blob(key).await?, expires_in).await
+    }
+}
+
+/// Get the key of the blob with a checksum
+fn blob_key(checksum: &str) -> String {
+    format!("blobs/{}/{}", &checksum[..2], checksum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::{file::FileBlobRepository, in_memory::InMemoryBlobRepository};
+    use crate::infrastructure::storage::local::LocalArtifactStore;
+    use tokio::io::AsyncReadExt;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn stream(contents: &'static [u8]) -> ArtifactStream {
+        Box::pin(contents)
+    }
+
+    /// Local store whose renames fail
+    struct FailingRenames(LocalArtifactStore);
+
+    #[async_trait]
+    impl ArtifactStore for FailingRenames {
+        async fn put(&self, key: &str, contents: ArtifactStream, expected_checksum: Option<&str>) -> crate::Result<StoredArtifact> {
+            self.0.put(key, contents, expected_checksum).await
+        }
+
+        async fn get(&self, key: &str) -> crate::Result<ArtifactStream> {
+            self.0.get(key).await
+        }
+
+        async fn exists(&self, key: &str) -> crate::Result<bool> {
+            self.0.exists(key).await
+        }
+
+        async fn delete(&self, key: &str) -> crate::Result<()> {
+            self.0.delete(key).await
+        }
+
+        async fn rename(&self, _from: &str, _to: &str) -> crate::Result<()> {
+            Err(crate::Error::storage("rename failed"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identical_uploads_are_stored_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DedupArtifactStore::new(
+            Arc::new(LocalArtifactStore::new(dir.path(), 1024)),
+            Arc::new(InMemoryBlobRepository::new()),
+        );
+
+        store.put("p/1/toolchain.tar", stream(b"hello"), None).await.unwrap();
+        let stored = store.put("p/2/toolchain.tar", stream(b"hello"), Some(HELLO_SHA256)).await.unwrap();
+        store.put("p/2/app", stream(b"world"), None).await.unwrap();
+        assert_eq!(stored.checksum, HELLO_SHA256);
+        assert!(dir.path().join(blob_key(HELLO_SHA256)).is_file());
+        assert!(!dir.path().join("uploads").exists());
+        assert_eq!(
+            store.stats().await.unwrap(),
+            BlobStats { objects: 3, blobs: 2, logical_bytes: 15, physical_bytes: 10 }
+        );
+
+        let mut contents = String::new();
+        store.get("p/2/toolchain.tar").await.unwrap().read_to_string(&mut contents).await.unwrap();
+        assert_eq!(contents, "hello");
+        store.verify("p/1/toolchain.tar", HELLO_SHA256).await.unwrap();
+
+        // Rejected uploads leave nothing behind
+        assert!(store.put("p/3/bad", stream(b"hello"), Some(&"0".repeat(64))).await.is_err());
+        assert!(!store.exists("p/3/bad").await.unwrap());
+        assert_eq!(store.stats().await.unwrap().objects, 3);
+
+        // Blobs are only collected once no key references them
+        store.delete("p/1/toolchain.tar").await.unwrap();
+        store.rename("p/2/app", "p/2/app.bin").await.unwrap();
+        assert_eq!(store.collect_garbage().await.unwrap(), GcReport::default());
+        assert!(matches!(store.get("p/1/toolchain.tar").await, Err(crate::Error::NotFound(_))));
+
+        store.delete("p/2/toolchain.tar").await.unwrap();
+        assert_eq!(store.collect_garbage().await.unwrap(), GcReport { blobs: 1, reclaimed_bytes: 5 });
+        assert!(!dir.path().join(blob_key(HELLO_SHA256)).exists());
+        assert_eq!(
+            store.stats().await.unwrap(),
+            BlobStats { objects: 1, blobs: 1, logical_bytes: 5, physical_bytes: 5 }
+        );
+
+        // Uploading collected contents again restores the blob
+        store.put("p/4/toolchain.tar", stream(b"hello"), None).await.unwrap();
+        store.verify("p/4/toolchain.tar", HELLO_SHA256).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_put_keeps_previous_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = Arc::new(InMemoryBlobRepository::new());
+        let store = DedupArtifactStore::new(Arc::new(LocalArtifactStore::new(dir.path(), 1024)), index.clone());
+        store.put("p/1/app", stream(b"hello"), None).await.unwrap();
+
+        let failing = DedupArtifactStore::new(Arc::new(FailingRenames(LocalArtifactStore::new(dir.path(), 1024))), index);
+        assert!(failing.put("p/1/app", stream(b"world"), None).await.is_err());
+        assert!(failing.put("p/1/new", stream(b"world"), None).await.is_err());
+        assert!(!failing.exists("p/1/new").await.unwrap());
+        assert!(!dir.path().join("uploads").exists());
+
+        assert_eq!(failing.collect_garbage().await.unwrap(), GcReport::default());
+        failing.verify("p/1/app", HELLO_SHA256).await.unwrap();
+        assert_eq!(
+            failing.stats().await.unwrap(),
+            BlobStats { objects: 1, blobs: 1, logical_bytes: 5, physical_bytes: 5 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keys_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join("index.jsonl");
+        let open = || async {
+            DedupArtifactStore::new(
+                Arc::new(LocalArtifactStore::new(dir.path().join("store"), 1024)),
+                Arc::new(FileBlobRepository::open(&index).await.unwrap()),
+            )
+        };
+
+        let store = open().await;
+        store.put("p/1/app", stream(b"hello"), None).await.unwrap();
+        store.put("p/1/old", stream(b"world"), None).await.unwrap();
+        store.delete("p/1/old").await.unwrap();
+        drop(store);
+
+        // Blobs released before the restart are still collected
+        let store = open().await;
+        store.verify("p/1/app", HELLO_SHA256).await.unwrap();
+        assert_eq!(store.collect_garbage().await.unwrap(), GcReport { blobs: 1, reclaimed_bytes: 5 });
+        store.verify("p/1/app", HELLO_SHA256).await.unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+//! Artifact store on the local filesystem
+//!
+//! Artifacts are files under the artifacts directory at their key, so the
+//! artifacts of a build live in `{artifacts_path}/{project}/{build}/`.
+//! Uploads are written to a temporary file next to their destination and
+//! only renamed into place once complete and verified.
+
+use super::{ArtifactStore, ArtifactStream, StoredArtifact, check_checksum, copy_hashed, validate_key};
+use crate::config::StorageConfig;
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File};
+use uuid::Uuid;
+
+/// Artifact store keeping artifacts as files in a directory
+pub struct LocalArtifactStore {
+    root: PathBuf,
+    max_size_bytes: u64,
+}
+
+impl LocalArtifactStore {
+    /// Create a store in a directory, accepting artifacts up to a size
+    pub fn new(root: impl AsRef<Path>, max_size_bytes: u64) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            max_size_bytes,
+        }
+    }
+
+    /// Create a store from the storage configuration
+    pub fn from_config(config: &StorageConfig) -> Self {
+        Self::new(&config.artifacts_path, config.max_artifact_size.saturating_mul(1024 * 1024))
+    }
+
+    /// Get the path of the file stored under a key
+    pub fn path(&self, key: &str) -> crate::Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+
+    /// Remove the directories left empty between a deleted file and the root
+    async fn remove_empty_parents(&self, path: &Path) {
+        let mut dir = path.parent();
+        while let Some(current) = dir.filter(|dir| *dir != self.root) {
+            // Fails once a directory is not empty
+            if fs::remove_dir(current).await.is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+    }
+}
+
+#[async_trait]
+impl ArtifactStore for LocalArtifactStore {
+    async fn put(
+        &self,
+        key: &str,
+        mut contents: ArtifactStream,
+        expected_checksum: Option<&str>,
+    ) -> crate::Result<StoredArtifact> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let temp = path.with_file_name(format!(
+            ".{}.{}.tmp",
+            path.file_name().and_then(|name| name.to_str()).unwrap_or_default(),
+            Uuid::new_v4()
+        ));
+        let written = async {
+            let mut file = File::create(&temp).await?;
+            let (size, checksum) = copy_hashed(&mut contents, &mut file, self.max_size_bytes).await?;
+            if let Some(expected) = expected_checksum {
+                check_checksum(key, expected, &checksum)?;
+            }
+            file.sync_all().await?;
+            Ok::<_, crate::Error>((size, checksum))
+        }
+        .await;
+
+        match written {
+            Ok((size, checksum)) => {
+                fs::rename(&temp, &path).await?;
+                Ok(StoredArtifact {
+                    key: key.to_string(),
+                    size,
+                    checksum,
+                })
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&temp).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn get(&self, key: &str) -> crate::Result<ArtifactStream> {
+        match File::open(self.path(key)?).await {
+            Ok(file) => Ok(Box::pin(file)),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Err(crate::Error::not_found(format!("Artifact {key} not found")))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> crate::Result<()> {
+        let (source, destination) = (self.path(from)?, self.path(to)?);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        match fs::rename(&source, &destination).await {
+            Ok(()) => {
+                self.remove_empty_parents(&source).await;
+                Ok(())
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Err(crate::Error::not_found(format!("Artifact {from} not found")))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> crate::Result<bool> {
+        Ok(fs::try_exists(self.path(key)?).await?)
+    }
+
+    async fn delete(&self, key: &str) -> crate::Result<()> {
+        let path = self.path(key)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => {
+                self.remove_empty_parents(&path).await;
+                Ok(())
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::artifact_key;
+    use crate::domain::value_objects::{build_id::BuildId, project_id::ProjectId};
+    use tokio::io::AsyncReadExt;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn stream(contents: &'static [u8]) -> ArtifactStream {
+        Box::pin(contents)
+    }
+
+    #[tokio::test]
+    async fn test_put_and_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(dir.path(), 1024);
+        let project_id = ProjectId::new();
+        let build_id = BuildId::new();
+        let key = artifact_key(&project_id, &build_id, "dist/hello.txt").unwrap();
+
+        let stored = store.put(&key, stream(b"hello"), Some(HELLO_SHA256)).await.unwrap();
+        assert_eq!(stored.size, 5);
+        assert_eq!(stored.checksum, HELLO_SHA256);
+        assert!(dir.path().join(project_id.to_string()).join(build_id.to_string()).join("dist/hello.txt").is_file());
+        assert!(store.exists(&key).await.unwrap());
+
+        let mut contents = String::new();
+        store.get(&key).await.unwrap().read_to_string(&mut contents).await.unwrap();
+        assert_eq!(contents, "hello");
+        store.verify(&key, HELLO_SHA256).await.unwrap();
+        assert!(store.verify(&key, &"0".repeat(64)).await.is_err());
+
+        store.rename(&key, "moved/hello.txt").await.unwrap();
+        assert!(!store.exists(&key).await.unwrap());
+        store.verify("moved/hello.txt", HELLO_SHA256).await.unwrap();
+        store.rename("moved/hello.txt", &key).await.unwrap();
+        assert!(!dir.path().join("moved").exists());
+        assert!(matches!(store.rename("moved/hello.txt", &key).await, Err(crate::Error::NotFound(_))));
+
+        // Deleting removes the emptied build directories too
+        store.delete(&key).await.unwrap();
+        store.delete(&key).await.unwrap();
+        assert!(!store.exists(&key).await.unwrap());
+        assert!(!dir.path().join(project_id.to_string()).exists());
+        assert!(matches!(store.get(&key).await, Err(crate::Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_uploads() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(dir.path(), 4);
+
+        assert!(store.put("p/b/big.bin", stream(b"hello"), None).await.is_err());
+        let store = LocalArtifactStore::new(dir.path(), 1024);
+        assert!(store.put("p/b/bad.bin", stream(b"hello"), Some(&"0".repeat(64))).await.is_err());
+        assert!(store.put("p/../escape.bin", stream(b"hello"), None).await.is_err());
+
+        // Nothing is left behind, not even temporary files
+        assert_eq!(std::fs::read_dir(dir.path().join("p/b")).unwrap().count(), 0);
+        assert!(!store.exists("p/b/big.bin").await.unwrap());
+
+        // A failed upload keeps the previous contents
+        store.put("p/b/app.bin", stream(b"hello"), None).await.unwrap();
+        assert!(store.put("p/b/app.bin", stream(b"world"), Some(HELLO_SHA256)).await.is_err());
+        store.verify("p/b/app.bin", HELLO_SHA256).await.unwrap();
+    }
+}
@@ -1,4 +1,178 @@
 //! Storage implementations
+//!
+//! Artifacts are stored under keys laid out per project and build, see
+//! [`artifact_key`]. Contents are streamed in and out of a store and their
+//! SHA256 checksum is computed while uploading. Stores are wrapped in a
+//! [`dedup::DedupArtifactStore`] so identical contents are kept once.
 
-// TODO: Implement file storage and artifact management
+pub mod dedup;
+pub mod local;
+pub mod retention;
+pub mod s3;
 
+use crate::domain::value_objects::{build_id::BuildId, project_id::ProjectId};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Contents of an artifact, read as a stream
+pub type ArtifactStream = Pin<Box<dyn AsyncRead + Send>>;
+
+/// Size of the chunks artifacts are streamed in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// An artifact as written to a store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredArtifact {
+    /// Key the artifact is stored under
+    pub key: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Hex encoded SHA256 of the contents
+    pub checksum: String,
+}
+
+/// Storage for artifact contents
+#[async_trait]
+pub trait ArtifactStore: Send + Sync {
+    /// Store the contents of an artifact under a key, replacing any previous
+    /// contents
+    ///
+    /// Uploads larger than the store's size limit, or whose checksum differs
+    /// from `expected_checksum`, are rejected and leave nothing behind.
+    async fn put(
+        &self,
+        key: &str,
+        contents: ArtifactStream,
+        expected_checksum: Option<&str>,
+    ) -> crate::Result<StoredArtifact>;
+
+    /// Read the contents of an artifact
+    async fn get(&self, key: &str) -> crate::Result<ArtifactStream>;
+
+    /// Check if an artifact is stored under a key
+    async fn exists(&self, key: &str) -> crate::Result<bool>;
+
+    /// Delete an artifact; deleting a missing artifact is not an error
+    async fn delete(&self, key: &str) -> crate::Result<()>;
+
+    /// Move an artifact to another key, replacing its contents
+    ///
+    /// The default copies the contents and deletes the original.
+    async fn rename(&self, from: &str, to: &str) -> crate::Result<()> {
+        let contents = self.get(from).await?;
+        self.put(to, contents, None).await?;
+        self.delete(from).await
+    }
+
+    /// Get a URL clients can download an artifact from directly, valid for
+    /// `expires_in`, if the store supports it
+    async fn download_url(&self, key: &str, expires_in: Duration) -> crate::Result<Option<String>> {
+        let _ = (key, expires_in);
+        Ok(None)
+    }
+
+    /// Check that the stored contents of an artifact match its checksum
+    async fn verify(&self, key: &str, checksum: &str) -> crate::Result<()> {
+        let (_, actual) = copy_hashed(&mut self.get(key).await?, &mut tokio::io::sink(), u64::MAX).await?;
+        check_checksum(key, checksum, &actual)
+    }
+}
+
+/// Build the key of a build's artifact, e.g. `{project}/{build}/dist/app.zip`
+pub fn artifact_key(project_id: &ProjectId, build_id: &BuildId, name: &str) -> crate::Result<String> {
+    let key = format!("{project_id}/{build_id}/{name}");
+    validate_key(&key)?;
+    Ok(key)
+}
+
+/// Check that a key is a relative path without `.` or `..` segments
+pub fn validate_key(key: &str) -> crate::Result<()> {
+    let valid = !key.is_empty()
+        && !key.contains('\\')
+        && key.split('/').all(|segment| !matches!(segment, "" | "." | ".."));
+    if valid {
+        Ok(())
+    } else {
+        Err(crate::Error::validation(format!("Invalid artifact key: {key}")))
+    }
+}
+
+/// Copy a stream into a writer, returning its size and hex encoded SHA256
+///
+/// Fails as soon as more than `max_size` bytes were read.
+pub(crate) async fn copy_hashed<R, W>(reader: &mut R, writer: &mut W, max_size: u64) -> crate::Result<(u64, String)>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut size = 0u64;
+
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        size += read as u64;
+        if size > max_size {
+            return Err(too_large(max_size));
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read]).await?;
+    }
+    writer.flush().await?;
+
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+/// Error for an upload over the size limit
+pub(crate) fn too_large(max_size: u64) -> crate::Error {
+    crate::Error::validation(format!("Artifact exceeds the maximum size of {max_size} bytes"))
+}
+
+/// Compare an expected checksum with the actual one, ignoring case
+pub(crate) fn check_checksum(key: &str, expected: &str, actual: &str) -> crate::Result<()> {
+    if expected.eq_ignore_ascii_case(actual) {
+        Ok(())
+    } else {
+        Err(crate::Error::validation(format!(
+            "Checksum mismatch for artifact {key}: expected {expected}, got {actual}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_key() {
+        let project_id = ProjectId::new();
+        let build_id = BuildId::new();
+
+        assert_eq!(
+            artifact_key(&project_id, &build_id, "dist/app.zip").unwrap(),
+            format!("{project_id}/{build_id}/dist/app.zip")
+        );
+        for name in ["", "../other/app.zip", "dist/./app.zip", "dist//app.zip", "/etc/passwd", "dist\\app.zip"] {
+            assert!(artifact_key(&project_id, &build_id, name).is_err(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_copy_hashed() {
+        let mut output = Vec::new();
+        let (size, checksum) = copy_hashed(&mut &b"hello"[..], &mut output, 5).await.unwrap();
+
+        assert_eq!(size, 5);
+        assert_eq!(checksum, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+        assert_eq!(output, b"hello");
+        assert!(copy_hashed(&mut &b"hello"[..], &mut Vec::new(), 4).await.is_err());
+        assert!(check_checksum("app.zip", &checksum.to_uppercase(), &checksum).is_ok());
+        assert!(check_checksum("app.zip", "abc", &checksum).is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! Artifact and cache retention
+//!
+//! The artifact sweeper expires artifacts past their own expiration date, set from a
+//! job's `expire_in`, or older than the global `retention_days`, and deletes
+//! their contents from the store. Expired artifacts stay listed so downstream
+//! jobs can tell why they are gone.
+//!
+//! The artifacts of the latest successful build of every pipeline ref, branch
+//! or tag, and pinned artifacts are always kept.
+//!
+//! The cache sweeper evicts dependency caches not saved or restored for
+//! `cache_retention_days`; a job missing an evicted cache saves it again.
+
+use super::ArtifactStore;
+use crate::domain::entities::artifact::Artifact;
+use crate::domain::repositories::{artifact::ArtifactRepository, build::BuildQueryOptions, cache::CacheRepository};
+use crate::domain::services::build::BuildService;
+use crate::domain::value_objects::{build_id::BuildId, build_status::BuildStatus};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// How often artifacts and caches are swept
+const SWEEP_INTERVAL: Duration = Duration::from_hours(1);
+
+/// What a sweep expired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SweepReport {
+    /// Number of artifacts or caches expired
+    pub expired: usize,
+    /// Bytes of the expired artifacts or caches; a content-addressed store only frees
+    /// them once garbage collection deletes blobs no other key references
+    pub released_bytes: u64,
+}
+
+/// Expires artifacts and deletes their contents
+pub struct ArtifactSweeper {
+    store: Arc<dyn ArtifactStore>,
+    artifacts: Arc<dyn ArtifactRepository>,
+    build_service: Arc<BuildService>,
+    retention_days: u32,
+}
+
+impl ArtifactSweeper {
+    /// Create a sweeper keeping artifacts for `retention_days` at most; 0
+    /// keeps them until their own expiration date
+    pub fn new(
+        store: Arc<dyn ArtifactStore>,
+        artifacts: Arc<dyn ArtifactRepository>,
+        build_service: Arc<BuildService>,
+        retention_days: u32,
+    ) -> Self {
+        Self {
+            store,
+            artifacts,
+            build_service,
+            retention_days,
+        }
+    }
+
+    /// Expire the artifacts due now
+    ///
+    /// Artifacts whose contents could not be deleted are left for the next
+    /// sweep.
+    pub async fn sweep(&self) -> crate::Result<SweepReport> {
+        let now = Utc::now();
+        let kept_builds = self.latest_successful_builds().await?;
+        let mut report = SweepReport::default();
+
+        for mut artifact in self.artifacts.find_all().await? {
+            if !self.is_due(&artifact, now) || kept_builds.contains(artifact.build_id()) {
+                continue;
+            }
+
+            if let Err(e) = self.store.delete(artifact.path()).await {
+                tracing::warn!("Could not delete artifact {}: {}", artifact.path(), e);
+                continue;
+            }
+            artifact.expire();
+            self.artifacts.update(&artifact).await?;
+
+            report.expired += 1;
+            report.released_bytes += artifact.size();
+        }
+
+        if report.expired > 0 {
+            tracing::info!(
+                "Expired {} artifact(s), releasing {} bytes",
+                report.expired,
+                report.released_bytes
+            );
+        }
+        Ok(report)
+    }
+
+    /// Check if an artifact should be expired, ignoring the build it is from
+    fn is_due(&self, artifact: &Artifact, now: DateTime<Utc>) -> bool {
+        if artifact.is_marked_expired() || artifact.is_pinned() {
+            return false;
+        }
+
+        let retained = self.retention_days > 0
+            && artifact.created_at() + ChronoDuration::days(self.retention_days.into()) < now;
+        retained || artifact.expires_at().is_some_and(|expires_at| expires_at < now)
+    }
+
+    /// Get the latest successful build of every pipeline ref
+    async fn latest_successful_builds(&self) -> crate::Result<HashSet<BuildId>> {
+        let builds = self
+            .build_service
+            .list_builds(BuildQueryOptions {
+                status: Some(BuildStatus::Success),
+                ..Default::default()
+            })
+            .await?;
+
+        let mut latest = HashMap::new();
+        for build in builds {
+            let key = (build.pipeline_id().clone(), build.git_ref());
+            let newer = latest
+                .get(&key)
+                .is_none_or(|(number, _)| *number < build.number());
+            if newer {
+                latest.insert(key, (build.number(), build.id().clone()));
+            }
+        }
+
+        Ok(latest.into_values().map(|(_, build_id)| build_id).collect())
+    }
+
+    /// Sweep until `shutdown` becomes true
+    pub async fn run(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+
+            if let Err(e) = self.sweep().await {
+                tracing::warn!("Artifact retention sweep failed: {}", e);
+            }
+        }
+    }
+
+    /// Run the sweeper on a background task
+    pub fn spawn(self: Arc<Self>, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(shutdown))
+    }
+}
+
+/// Evicts dependency caches that were not used for a while
+pub struct CacheSweeper {
+    store: Arc<dyn ArtifactStore>,
+    entries: Arc<dyn CacheRepository>,
+    retention_days: u32,
+}
+
+impl CacheSweeper {
+    /// Create a sweeper keeping caches for `retention_days` after their last
+    /// use; 0 keeps them forever
+    pub fn new(store: Arc<dyn ArtifactStore>, entries: Arc<dyn CacheRepository>, retention_days: u32) -> Self {
+        Self {
+            store,
+            entries,
+            retention_days,
+        }
+    }
+
+    /// Evict the caches unused for longer than the retention
+    ///
+    /// Caches whose archives could not be deleted are left for the next sweep.
+    pub async fn sweep(&self) -> crate::Result<SweepReport> {
+        let mut report = SweepReport::default();
+        if self.retention_days == 0 {
+            return Ok(report);
+        }
+
+        let cutoff = Utc::now() - ChronoDuration::days(self.retention_days.into());
+        for entry in self.entries.find_all().await? {
+            if entry.last_used_at >= cutoff {
+                continue;
+            }
+
+            if let Err(e) = self.store.delete(&entry.path).await {
+                tracing::warn!("Could not delete cache {}: {}", entry.path, e);
+                continue;
+            }
+            self.entries.delete(&entry.project_id, &entry.git_ref, &entry.key).await?;
+
+            report.expired += 1;
+            report.released_bytes += entry.size;
+        }
+
+        if report.expired > 0 {
+            tracing::info!(
+                "Evicted {} cache(s), releasing {} bytes",
+                report.expired,
+                report.released_bytes
+            );
+        }
+        Ok(report)
+    }
+
+    /// Sweep until `shutdown` becomes true
+    pub async fn run(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+
+            if let Err(e) = self.sweep().await {
+                tracing::warn!("Cache retention sweep failed: {}", e);
+            }
+        }
+    }
+
+    /// Run the sweeper on a background task
+    pub fn spawn(self: Arc<Self>, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(shutdown))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EventsConfig;
+    use crate::domain::entities::{artifact::ArtifactType, build::{Build, BuildTrigger}};
+    use crate::domain::repositories::{build::BuildRepository, cache::CacheEntry};
+    use crate::domain::value_objects::{agent_id::AgentId, pipeline_id::PipelineId, project_id::ProjectId};
+    use crate::infrastructure::events::EventBus;
+    use crate::infrastructure::repositories::in_memory::{
+        InMemoryArtifactRepository, InMemoryBuildRepository, InMemoryCacheRepository,
+    };
+    use crate::infrastructure::storage::local::LocalArtifactStore;
+
+    async fn build(builds: &InMemoryBuildRepository, pipeline_id: &PipelineId, number: u64, branch: &str, succeed: bool) -> BuildId {
+        tagged_build(builds, pipeline_id, number, branch, succeed, false).await
+    }
+
+    async fn tagged_build(
+        builds: &InMemoryBuildRepository,
+        pipeline_id: &PipelineId,
+        number: u64,
+        branch: &str,
+        succeed: bool,
+        tag: bool,
+    ) -> BuildId {
+        let mut build = Build::new(
+            pipeline_id.clone(),
+            ProjectId::new(),
+            number,
+            "a".repeat(40),
+            branch.to_string(),
+            BuildTrigger::Push,
+        );
+        build.set_tag(tag);
+        if succeed {
+            build.start(AgentId::new()).unwrap();
+            build.succeed().unwrap();
+        }
+        builds.save(&build).await.unwrap();
+        build.id().clone()
+    }
+
+    async fn artifact(
+        store: &dyn ArtifactStore,
+        artifacts: &InMemoryArtifactRepository,
+        build_id: &BuildId,
+        name: &str,
+        expired: bool,
+    ) -> Artifact {
+        let key = format!("project/{build_id}/{name}");
+        let stored = store.put(&key, Box::pin(&b"contents"[..]), None).await.unwrap();
+        let mut artifact = Artifact::new(build_id.clone(), name.to_string(), key, stored.size, stored.checksum, ArtifactType::BuildOutput);
+        if expired {
+            artifact.set_expiration(Utc::now() - ChronoDuration::hours(1));
+        }
+        artifacts.save(&artifact).await.unwrap();
+        artifact
+    }
+
+    #[tokio::test]
+    async fn test_sweep() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn ArtifactStore> = Arc::new(LocalArtifactStore::new(dir.path(), 1024));
+        let artifacts = Arc::new(InMemoryArtifactRepository::new());
+        let builds = Arc::new(InMemoryBuildRepository::new());
+        let build_service = Arc::new(BuildService::new(builds.clone(), Arc::new(EventBus::new(EventsConfig::default()))));
+        let sweeper = ArtifactSweeper::new(store.clone(), artifacts.clone(), build_service, 30);
+
+        let pipeline_id = PipelineId::new();
+        let old = build(&builds, &pipeline_id, 1, "main", true).await;
+        let latest = build(&builds, &pipeline_id, 2, "main", true).await;
+        let feature = build(&builds, &pipeline_id, 3, "feature", false).await;
+        // A tag named like a branch does not replace the branch's latest build
+        let release = tagged_build(&builds, &pipeline_id, 4, "main", true, true).await;
+
+        let expired = artifact(store.as_ref(), &artifacts, &old, "app", true).await;
+        let kept = artifact(store.as_ref(), &artifacts, &old, "docs", false).await;
+        let latest_success = artifact(store.as_ref(), &artifacts, &latest, "app", true).await;
+        let latest_release = artifact(store.as_ref(), &artifacts, &release, "app", true).await;
+        let mut pinned = artifact(store.as_ref(), &artifacts, &feature, "app", true).await;
+        pinned.pin();
+        artifacts.update(&pinned).await.unwrap();
+
+        let report = sweeper.sweep().await.unwrap();
+        assert_eq!(report, SweepReport { expired: 1, released_bytes: 8 });
+        assert!(!store.exists(expired.path()).await.unwrap());
+        assert!(artifacts.find_by_id(expired.id()).await.unwrap().unwrap().is_marked_expired());
+        for artifact in [&kept, &latest_success, &latest_release, &pinned] {
+            assert!(store.exists(artifact.path()).await.unwrap(), "{}", artifact.path());
+        }
+        assert_eq!(sweeper.sweep().await.unwrap(), SweepReport::default());
+
+        // The global retention applies to artifacts without an expiration date
+        let now = Utc::now();
+        assert!(!sweeper.is_due(&kept, now));
+        assert!(sweeper.is_due(&kept, now + ChronoDuration::days(31)));
+        assert!(!sweeper.is_due(&pinned, now + ChronoDuration::days(31)));
+    }
+
+    #[tokio::test]
+    async fn test_cache_sweep() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn ArtifactStore> = Arc::new(LocalArtifactStore::new(dir.path(), 1024));
+        let entries = Arc::new(InMemoryCacheRepository::new());
+        let project_id = ProjectId::new();
+
+        let mut caches = Vec::new();
+        for (key, days_unused) in [("stale", 8), ("fresh", 1)] {
+            let path = format!("{project_id}/refs/heads/main/{key}.tar.zst");
+            let stored = store.put(&path, Box::pin(&b"contents"[..]), None).await.unwrap();
+            let entry = CacheEntry {
+                project_id: project_id.clone(),
+                git_ref: "refs/heads/main".to_string(),
+                key: key.to_string(),
+                path,
+                size: stored.size,
+                checksum: stored.checksum,
+                created_at: Utc::now() - ChronoDuration::days(30),
+                last_used_at: Utc::now() - ChronoDuration::days(days_unused),
+            };
+            entries.save(&entry).await.unwrap();
+            caches.push(entry);
+        }
+
+        let keep_forever = CacheSweeper::new(store.clone(), entries.clone(), 0);
+        assert_eq!(keep_forever.sweep().await.unwrap(), SweepReport::default());
+
+        let sweeper = CacheSweeper::new(store.clone(), entries.clone(), 7);
+        let report = sweeper.sweep().await.unwrap();
+        assert_eq!(report, SweepReport { expired: 1, released_bytes: 8 });
+        assert!(!store.exists(&caches[0].path).await.unwrap());
+        assert!(entries.find(&project_id, "refs/heads/main", "stale").await.unwrap().is_none());
+        assert!(store.exists(&caches[1].path).await.unwrap());
+        assert_eq!(entries.find(&project_id, "refs/heads/main", "fresh").await.unwrap().as_ref(), Some(&caches[1]));
+    }
+}
@@ -0,0 +1,105 @@
+//! Gitea webhooks
+//!
+//! Deliveries are signed with HMAC-SHA256 of the raw body, sent as a bare hex
+//! digest in `X-Gitea-Signature`. Payloads follow GitHub's format, except that
+//! updated pull requests have the action `synchronized`.
+
+use super::{WebhookDelivery, WebhookReceiver, github, verify_hmac_sha256};
+use crate::domain::value_objects::scm_event::ScmEvent;
+
+/// Source name of Gitea events
+pub const SOURCE: &str = "gitea";
+
+const SIGNATURE_HEADER: &str = "X-Gitea-Signature";
+const EVENT_HEADER: &str = "X-Gitea-Event";
+
+/// Gitea webhook receiver
+pub struct Gitea;
+
+impl WebhookReceiver for Gitea {
+    fn source(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn verify(&self, secret: &str, delivery: &WebhookDelivery<'_>) -> crate::Result<()> {
+        verify_hmac_sha256(secret, delivery.body(), delivery.header(SIGNATURE_HEADER))
+    }
+
+    fn parse(&self, delivery: &WebhookDelivery<'_>) -> crate::Result<Option<ScmEvent>> {
+        match delivery.required_header(EVENT_HEADER)? {
+            "push" => github::parse_push(SOURCE, delivery.body()),
+            "pull_request" => github::parse_pull_request(
+                SOURCE,
+                delivery.body(),
+                &["opened", "synchronized", "reopened"],
+            ),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::scm_event::ScmEventKind;
+    use hmac::{Hmac, Mac};
+    use serde_json::json;
+    use sha2::Sha256;
+
+    fn delivery<'a>(event: &str, signature: &str, body: &'a [u8]) -> WebhookDelivery<'a> {
+        WebhookDelivery::new(
+            [
+                (EVENT_HEADER.to_string(), event.to_string()),
+                (SIGNATURE_HEADER.to_string(), signature.to_string()),
+            ],
+            body,
+        )
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let body = br#"{"ref":"refs/heads/main"}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"webhook-key").unwrap();
+        mac.update(body);
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        assert!(Gitea.verify("webhook-key", &delivery("push", &signature, body)).is_ok());
+        assert!(Gitea.verify("other-key", &delivery("push", &signature, body)).is_err());
+        assert!(Gitea.verify("webhook-key", &delivery("push", &format!("sha256={signature}"), body)).is_err());
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let push = json!({
+            "ref": "refs/heads/main",
+            "before": "0000000",
+            "after": "abc123",
+            "head_commit": {
+                "id": "abc123",
+                "message": "Fix tests\n",
+                "author": {"name": "Gitea User", "email": "user@example.com", "username": "user"}
+            }
+        }).to_string();
+        let event = Gitea.parse(&delivery("push", "", push.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.source, "gitea");
+        assert_eq!(event.kind, ScmEventKind::Push { branch: "main".to_string() });
+        assert_eq!(event.commit_author, "Gitea User");
+
+        let pull_request = |action: &str| json!({
+            "action": action,
+            "number": 3,
+            "pull_request": {
+                "title": "Add feature",
+                "user": {"login": "user"},
+                "head": {"ref": "feature", "sha": "def456"},
+                "base": {"ref": "main", "sha": "abc123"}
+            }
+        }).to_string();
+        let synchronized = pull_request("synchronized");
+        let event = Gitea.parse(&delivery("pull_request", "", synchronized.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.commit_sha, "def456");
+        let labeled = pull_request("label_updated");
+        assert!(Gitea.parse(&delivery("pull_request", "", labeled.as_bytes())).unwrap().is_none());
+        assert!(Gitea.parse(&delivery("create", "", b"{}")).unwrap().is_none());
+    }
+}
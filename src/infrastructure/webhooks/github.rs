@@ -0,0 +1,253 @@
+//! GitHub webhooks
+//!
+//! Deliveries are signed with HMAC-SHA256 of the raw body under the webhook
+//! secret, sent as `X-Hub-Signature-256: sha256=<hex>`. The event type comes
+//! in the `X-GitHub-Event` header. Gitea sends the same payloads, so its
+//! receiver reuses the parsers here.
+
+use super::{
+    CommitFiles,
+    WebhookDelivery,
+    WebhookReceiver,
+    changed_files,
+    parse_payload,
+    previous_commit,
+    ref_event_kind,
+    verify_hmac_sha256,
+};
+use crate::domain::value_objects::scm_event::{ScmEvent, ScmEventKind};
+use serde::Deserialize;
+
+/// Source name of GitHub events
+pub const SOURCE: &str = "github";
+
+const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+const EVENT_HEADER: &str = "X-GitHub-Event";
+
+/// GitHub webhook receiver
+pub struct GitHub;
+
+impl WebhookReceiver for GitHub {
+    fn source(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn verify(&self, secret: &str, delivery: &WebhookDelivery<'_>) -> crate::Result<()> {
+        let signature = delivery
+            .header(SIGNATURE_HEADER)
+            .and_then(|s| s.strip_prefix("sha256="));
+        verify_hmac_sha256(secret, delivery.body(), signature)
+    }
+
+    fn parse(&self, delivery: &WebhookDelivery<'_>) -> crate::Result<Option<ScmEvent>> {
+        match delivery.required_header(EVENT_HEADER)? {
+            "push" => parse_push(SOURCE, delivery.body()),
+            "pull_request" => parse_pull_request(SOURCE, delivery.body(), &["opened", "synchronize", "reopened"]),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PushPayload {
+    #[serde(rename = "ref")]
+    reference: String,
+    before: Option<String>,
+    after: String,
+    #[serde(default)]
+    deleted: bool,
+    head_commit: Option<Commit>,
+    #[serde(default)]
+    commits: Vec<CommitFiles>,
+    /// Number of pushed commits, sent by Gitea
+    total_commits: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Commit {
+    message: String,
+    author: CommitAuthor,
+}
+
+#[derive(Deserialize)]
+struct CommitAuthor {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequestPayload {
+    action: String,
+    number: u32,
+    pull_request: PullRequest,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    title: String,
+    user: Account,
+    head: Branch,
+    base: Branch,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    reference: String,
+    sha: String,
+}
+
+/// Parse a push payload; branch deletions are ignored
+pub(super) fn parse_push(source: &str, body: &[u8]) -> crate::Result<Option<ScmEvent>> {
+    let payload: PushPayload = parse_payload(source, body)?;
+    let Some(commit) = payload.head_commit.filter(|_| !payload.deleted) else {
+        return Ok(None);
+    };
+
+    Ok(Some(ScmEvent {
+        source: source.to_string(),
+        kind: ref_event_kind(&payload.reference)?,
+        commit_sha: payload.after,
+        commit_message: commit.message,
+        commit_author: commit.author.name,
+        before_sha: previous_commit(payload.before),
+        changed_files: changed_files(&payload.commits, payload.total_commits),
+    }))
+}
+
+/// Parse a pull request payload, ignoring actions other than `actions`
+///
+/// Pull request payloads do not list changed files.
+pub(super) fn parse_pull_request(
+    source: &str,
+    body: &[u8],
+    actions: &[&str],
+) -> crate::Result<Option<ScmEvent>> {
+    let payload: PullRequestPayload = parse_payload(source, body)?;
+    if !actions.contains(&payload.action.as_str()) {
+        return Ok(None);
+    }
+
+    let pull_request = payload.pull_request;
+    Ok(Some(ScmEvent {
+        source: source.to_string(),
+        kind: ScmEventKind::PullRequest {
+            number: payload.number,
+            source_branch: pull_request.head.reference,
+            target_branch: pull_request.base.reference,
+        },
+        commit_sha: pull_request.head.sha,
+        commit_message: pull_request.title,
+        commit_author: pull_request.user.login,
+        before_sha: None,
+        changed_files: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmac::{Hmac, Mac};
+    use serde_json::json;
+    use sha2::Sha256;
+
+    fn delivery<'a>(event: &str, signature: Option<&str>, body: &'a [u8]) -> WebhookDelivery<'a> {
+        let mut headers = vec![(EVENT_HEADER.to_string(), event.to_string())];
+        if let Some(signature) = signature {
+            headers.push((SIGNATURE_HEADER.to_string(), signature.to_string()));
+        }
+        WebhookDelivery::new(headers, body)
+    }
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let body = br#"{"zen":"Keep it logically awesome."}"#;
+        let signature = sign("webhook-key", body);
+
+        assert!(GitHub.verify("webhook-key", &delivery("ping", Some(&signature), body)).is_ok());
+        assert!(GitHub.verify("other-key", &delivery("ping", Some(&signature), body)).is_err());
+        assert!(GitHub.verify("webhook-key", &delivery("ping", Some(&signature), b"{}")).is_err());
+        assert!(GitHub.verify("webhook-key", &delivery("ping", None, body)).is_err());
+        assert!(GitHub.verify("webhook-key", &delivery("ping", Some("sha256=zz"), body)).is_err());
+    }
+
+    #[test]
+    fn test_parse_push() {
+        let body = json!({
+            "ref": "refs/heads/main",
+            "before": "0000000",
+            "after": "abc123",
+            "deleted": false,
+            "head_commit": {
+                "id": "abc123",
+                "message": "Fix tests",
+                "author": {"name": "Mona Lisa", "email": "mona@example.com"}
+            }
+        }).to_string();
+
+        let event = GitHub.parse(&delivery("push", None, body.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.kind, ScmEventKind::Push { branch: "main".to_string() });
+        assert_eq!(event.commit_sha, "abc123");
+        assert_eq!(event.commit_message, "Fix tests");
+        assert_eq!(event.commit_author, "Mona Lisa");
+        assert_eq!(event.before_sha, None);
+        assert_eq!(event.changed_files, None);
+
+        let with_commits = json!({
+            "ref": "refs/heads/main",
+            "before": "fff000",
+            "after": "abc123",
+            "head_commit": {"message": "Fix tests", "author": {"name": "Mona Lisa"}},
+            "commits": [{"added": ["src/new.rs"], "removed": [], "modified": ["Cargo.toml"]}]
+        }).to_string();
+        let event = GitHub.parse(&delivery("push", None, with_commits.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.before_sha.as_deref(), Some("fff000"));
+        assert_eq!(event.changed_files.unwrap(), ["Cargo.toml", "src/new.rs"]);
+
+        let tag = body.replace("refs/heads/main", "refs/tags/v1.0.0");
+        let event = GitHub.parse(&delivery("push", None, tag.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.kind, ScmEventKind::Tag { name: "v1.0.0".to_string() });
+
+        let deleted = json!({"ref": "refs/heads/old", "after": "0000000", "deleted": true, "head_commit": null}).to_string();
+        assert!(GitHub.parse(&delivery("push", None, deleted.as_bytes())).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_pull_request() {
+        let payload = |action: &str| json!({
+            "action": action,
+            "number": 42,
+            "pull_request": {
+                "title": "Add feature",
+                "user": {"login": "octocat"},
+                "head": {"ref": "feature", "sha": "def456"},
+                "base": {"ref": "main", "sha": "abc123"}
+            }
+        }).to_string();
+
+        let opened = payload("opened");
+        let event = GitHub.parse(&delivery("pull_request", None, opened.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.kind, ScmEventKind::PullRequest {
+            number: 42,
+            source_branch: "feature".to_string(),
+            target_branch: "main".to_string(),
+        });
+        assert_eq!(event.commit_sha, "def456");
+
+        let closed = payload("closed");
+        assert!(GitHub.parse(&delivery("pull_request", None, closed.as_bytes())).unwrap().is_none());
+        assert!(GitHub.parse(&delivery("ping", None, b"{}")).unwrap().is_none());
+        assert!(GitHub.parse(&delivery("push", None, b"not json")).is_err());
+        assert!(GitHub.parse(&WebhookDelivery::new([], b"{}")).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! GitLab webhooks
+//!
+//! GitLab does not sign deliveries; it sends the webhook secret itself in
+//! `X-Gitlab-Token`. The event type comes in `X-Gitlab-Event`.
+
+use super::{
+    CommitFiles,
+    WebhookDelivery,
+    WebhookReceiver,
+    changed_files,
+    parse_payload,
+    previous_commit,
+    ref_event_kind,
+};
+use crate::domain::value_objects::scm_event::{ScmEvent, ScmEventKind};
+use serde::Deserialize;
+
+/// Source name of GitLab events
+pub const SOURCE: &str = "gitlab";
+
+const TOKEN_HEADER: &str = "X-Gitlab-Token";
+const EVENT_HEADER: &str = "X-Gitlab-Event";
+
+/// GitLab webhook receiver
+pub struct GitLab;
+
+impl WebhookReceiver for GitLab {
+    fn source(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn verify(&self, secret: &str, delivery: &WebhookDelivery<'_>) -> crate::Result<()> {
+        let token = delivery
+            .header(TOKEN_HEADER)
+            .ok_or_else(|| crate::Error::authentication("Missing webhook token"))?;
+
+        if constant_time_eq(token.as_bytes(), secret.as_bytes()) {
+            Ok(())
+        } else {
+            Err(crate::Error::authentication("Invalid webhook token"))
+        }
+    }
+
+    fn parse(&self, delivery: &WebhookDelivery<'_>) -> crate::Result<Option<ScmEvent>> {
+        match delivery.required_header(EVENT_HEADER)? {
+            "Push Hook" | "Tag Push Hook" => parse_push(delivery.body()),
+            "Merge Request Hook" => parse_merge_request(delivery.body()),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PushPayload {
+    #[serde(rename = "ref")]
+    reference: String,
+    before: Option<String>,
+    after: String,
+    /// `None` when the ref was deleted
+    checkout_sha: Option<String>,
+    user_name: String,
+    /// Tag message of annotated tags
+    message: Option<String>,
+    #[serde(default)]
+    commits: Vec<Commit>,
+    /// Number of pushed commits; at most 20 are listed
+    total_commits_count: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Commit {
+    id: String,
+    message: String,
+    author: CommitAuthor,
+    #[serde(flatten)]
+    files: CommitFiles,
+}
+
+#[derive(Deserialize)]
+struct CommitAuthor {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct MergeRequestPayload {
+    user: User,
+    object_attributes: MergeRequest,
+}
+
+#[derive(Deserialize)]
+struct User {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct MergeRequest {
+    iid: u32,
+    title: String,
+    source_branch: String,
+    target_branch: String,
+    action: Option<String>,
+    /// Previous head, only present when an update pushed new commits
+    oldrev: Option<String>,
+    last_commit: LastCommit,
+}
+
+#[derive(Deserialize)]
+struct LastCommit {
+    id: String,
+}
+
+fn parse_push(body: &[u8]) -> crate::Result<Option<ScmEvent>> {
+    let payload: PushPayload = parse_payload(SOURCE, body)?;
+    if payload.checkout_sha.is_none() {
+        return Ok(None);
+    }
+
+    let changed_files = changed_files(
+        payload.commits.iter().map(|commit| &commit.files),
+        payload.total_commits_count,
+    );
+
+    // Tag pushes carry no commits; fall back to the tag message and the pusher
+    let head = payload.commits.into_iter().find(|commit| commit.id == payload.after);
+    let (commit_message, commit_author) = match head {
+        Some(commit) => (commit.message, commit.author.name),
+        None => (payload.message.unwrap_or_default(), payload.user_name),
+    };
+
+    Ok(Some(ScmEvent {
+        source: SOURCE.to_string(),
+        kind: ref_event_kind(&payload.reference)?,
+        commit_sha: payload.after,
+        commit_message,
+        commit_author,
+        before_sha: previous_commit(payload.before),
+        changed_files,
+    }))
+}
+
+fn parse_merge_request(body: &[u8]) -> crate::Result<Option<ScmEvent>> {
+    let payload: MergeRequestPayload = parse_payload(SOURCE, body)?;
+    let merge_request = payload.object_attributes;
+
+    let builds = match merge_request.action.as_deref() {
+        Some("open" | "reopen") => true,
+        Some("update") => merge_request.oldrev.is_some(),
+        _ => false,
+    };
+    if !builds {
+        return Ok(None);
+    }
+
+    Ok(Some(ScmEvent {
+        source: SOURCE.to_string(),
+        kind: ScmEventKind::PullRequest {
+            number: merge_request.iid,
+            source_branch: merge_request.source_branch,
+            target_branch: merge_request.target_branch,
+        },
+        commit_sha: merge_request.last_commit.id,
+        commit_message: merge_request.title,
+        commit_author: payload.user.username,
+        before_sha: None,
+        changed_files: None,
+    }))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn delivery<'a>(event: &str, token: &str, body: &'a [u8]) -> WebhookDelivery<'a> {
+        WebhookDelivery::new(
+            [
+                (EVENT_HEADER.to_string(), event.to_string()),
+                (TOKEN_HEADER.to_string(), token.to_string()),
+            ],
+            body,
+        )
+    }
+
+    #[test]
+    fn test_verify_token() {
+        assert!(GitLab.verify("webhook-key", &delivery("Push Hook", "webhook-key", b"{}")).is_ok());
+        assert!(GitLab.verify("webhook-key", &delivery("Push Hook", "webhook-kez", b"{}")).is_err());
+        assert!(GitLab.verify("webhook-key", &delivery("Push Hook", "", b"{}")).is_err());
+        assert!(GitLab.verify("webhook-key", &WebhookDelivery::new([], b"{}")).is_err());
+    }
+
+    #[test]
+    fn test_parse_push() {
+        let push = json!({
+            "object_kind": "push",
+            "ref": "refs/heads/main",
+            "before": "0000000",
+            "after": "abc123",
+            "checkout_sha": "abc123",
+            "user_name": "Pusher",
+            "commits": [
+                {"id": "fff000", "message": "Older", "author": {"name": "Someone"}, "modified": ["README.md"]},
+                {"id": "abc123", "message": "Fix tests", "author": {"name": "Jane Doe"}, "added": ["src/lib.rs"]}
+            ],
+            "total_commits_count": 2
+        });
+        let body = push.to_string();
+        let event = GitLab.parse(&delivery("Push Hook", "", body.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.kind, ScmEventKind::Push { branch: "main".to_string() });
+        assert_eq!(event.commit_message, "Fix tests");
+        assert_eq!(event.commit_author, "Jane Doe");
+        assert_eq!(event.before_sha, None);
+        assert_eq!(event.changed_files.unwrap(), ["README.md", "src/lib.rs"]);
+
+        let tag = json!({
+            "object_kind": "tag_push",
+            "ref": "refs/tags/v1.0.0",
+            "after": "abc123",
+            "checkout_sha": "abc123",
+            "user_name": "Pusher",
+            "message": "Release 1.0",
+            "commits": []
+        }).to_string();
+        let event = GitLab.parse(&delivery("Tag Push Hook", "", tag.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.kind, ScmEventKind::Tag { name: "v1.0.0".to_string() });
+        assert_eq!(event.commit_message, "Release 1.0");
+        assert_eq!(event.commit_author, "Pusher");
+
+        let mut deleted = push;
+        deleted["checkout_sha"] = json!(null);
+        let deleted = deleted.to_string();
+        assert!(GitLab.parse(&delivery("Push Hook", "", deleted.as_bytes())).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_merge_request() {
+        let merge_request = |action: &str, oldrev: Option<&str>| json!({
+            "object_kind": "merge_request",
+            "user": {"username": "jdoe", "name": "Jane Doe"},
+            "object_attributes": {
+                "iid": 12,
+                "title": "Add feature",
+                "source_branch": "feature",
+                "target_branch": "main",
+                "action": action,
+                "oldrev": oldrev,
+                "last_commit": {"id": "def456", "message": "WIP"}
+            }
+        }).to_string();
+
+        let opened = merge_request("open", None);
+        let event = GitLab.parse(&delivery("Merge Request Hook", "", opened.as_bytes())).unwrap().unwrap();
+        assert_eq!(event.kind, ScmEventKind::PullRequest {
+            number: 12,
+            source_branch: "feature".to_string(),
+            target_branch: "main".to_string(),
+        });
+        assert_eq!(event.commit_sha, "def456");
+        assert_eq!(event.commit_author, "jdoe");
+
+        // Updates only build when they pushed new commits
+        let pushed = merge_request("update", Some("abc123"));
+        assert!(GitLab.parse(&delivery("Merge Request Hook", "", pushed.as_bytes())).unwrap().is_some());
+        let retitled = merge_request("update", None);
+        assert!(GitLab.parse(&delivery("Merge Request Hook", "", retitled.as_bytes())).unwrap().is_none());
+        let merged = merge_request("merge", None);
+        assert!(GitLab.parse(&delivery("Merge Request Hook", "", merged.as_bytes())).unwrap().is_none());
+    }
+}
@@ -0,0 +1,189 @@
+//! Webhook receivers - Verify and parse forge payloads into SCM events
+//!
+//! Each forge implements `WebhookReceiver`; everything downstream of parsing
+//! only sees `ScmEvent`s. Supporting another forge means adding a receiver
+//! here and listing it in `receiver`.
+
+pub mod gitea;
+pub mod github;
+pub mod gitlab;
+
+use crate::domain::value_objects::scm_event::{ScmEvent, ScmEventKind};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+
+/// A webhook delivery as received over HTTP
+pub struct WebhookDelivery<'a> {
+    headers: HashMap<String, String>,
+    body: &'a [u8],
+}
+
+impl<'a> WebhookDelivery<'a> {
+    /// Create a delivery from its headers and raw body
+    pub fn new(headers: impl IntoIterator<Item = (String, String)>, body: &'a [u8]) -> Self {
+        Self {
+            headers: headers
+                .into_iter()
+                .map(|(name, value)| (name.to_ascii_lowercase(), value))
+                .collect(),
+            body,
+        }
+    }
+
+    /// Get a header value; names are case-insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// Get the raw body
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    fn required_header(&self, name: &str) -> crate::Result<&str> {
+        self.header(name)
+            .ok_or_else(|| crate::Error::validation(format!("Missing {name} header")))
+    }
+}
+
+/// Receiver for the webhooks of one forge
+pub trait WebhookReceiver: Send + Sync {
+    /// Source name, used in webhook URLs and as the source of events
+    fn source(&self) -> &'static str;
+
+    /// Check that a delivery was sent by someone knowing the webhook secret
+    fn verify(&self, secret: &str, delivery: &WebhookDelivery<'_>) -> crate::Result<()>;
+
+    /// Parse a verified delivery, returning `None` for events that never
+    /// trigger builds
+    fn parse(&self, delivery: &WebhookDelivery<'_>) -> crate::Result<Option<ScmEvent>>;
+}
+
+/// Get the receiver for a source name
+pub fn receiver(source: &str) -> Option<&'static dyn WebhookReceiver> {
+    match source {
+        github::SOURCE => Some(&github::GitHub),
+        gitlab::SOURCE => Some(&gitlab::GitLab),
+        gitea::SOURCE => Some(&gitea::Gitea),
+        _ => None,
+    }
+}
+
+/// Check a hex-encoded HMAC-SHA256 signature of a body
+fn verify_hmac_sha256(secret: &str, body: &[u8], signature: Option<&str>) -> crate::Result<()> {
+    let signature = signature
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or_else(|| crate::Error::authentication("Missing or malformed webhook signature"))?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| crate::Error::authentication("Invalid webhook signature"))
+}
+
+/// Files touched by a commit, as listed in push payloads
+#[derive(serde::Deserialize)]
+struct CommitFiles {
+    #[serde(default)]
+    added: Vec<String>,
+    #[serde(default)]
+    removed: Vec<String>,
+    #[serde(default)]
+    modified: Vec<String>,
+}
+
+/// Collect the files changed by the commits of a push
+///
+/// Forges cap the number of commits in a payload; when some were left out, or
+/// none are listed (e.g. a force push to an existing commit), the changes are
+/// unknown.
+fn changed_files<'a>(
+    commits: impl IntoIterator<Item = &'a CommitFiles>,
+    total_commits: Option<usize>,
+) -> Option<Vec<String>> {
+    let mut listed = 0;
+    let mut files = Vec::new();
+    for commit in commits {
+        listed += 1;
+        files.extend(commit.added.iter().chain(&commit.removed).chain(&commit.modified).cloned());
+    }
+    if listed == 0 || total_commits.is_some_and(|total| total > listed) {
+        return None;
+    }
+
+    files.sort();
+    files.dedup();
+    Some(files)
+}
+
+/// Turn the `before` commit of a push into `None` when the ref is new
+fn previous_commit(sha: Option<String>) -> Option<String> {
+    sha.filter(|sha| !sha.is_empty() && sha.bytes().any(|b| b != b'0'))
+}
+
+fn parse_payload<'a, T: serde::Deserialize<'a>>(source: &str, body: &'a [u8]) -> crate::Result<T> {
+    serde_json::from_slice(body)
+        .map_err(|e| crate::Error::validation(format!("Invalid {source} payload: {e}")))
+}
+
+/// Split a pushed ref into a branch push or a tag
+fn ref_event_kind(reference: &str) -> crate::Result<ScmEventKind> {
+    if let Some(branch) = reference.strip_prefix("refs/heads/") {
+        Ok(ScmEventKind::Push { branch: branch.to_string() })
+    } else if let Some(name) = reference.strip_prefix("refs/tags/") {
+        Ok(ScmEventKind::Tag { name: name.to_string() })
+    } else {
+        Err(crate::Error::validation(format!("Unsupported ref in push event: {reference}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receivers() {
+        for source in ["github", "gitlab", "gitea"] {
+            assert_eq!(receiver(source).unwrap().source(), source);
+        }
+        assert!(receiver("bitbucket").is_none());
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let commits = [
+            CommitFiles {
+                added: vec!["services/api/new.rs".to_string()],
+                removed: vec![],
+                modified: vec!["README.md".to_string()],
+            },
+            CommitFiles {
+                added: vec![],
+                removed: vec!["services/api/old.rs".to_string()],
+                modified: vec!["README.md".to_string()],
+            },
+        ];
+
+        assert_eq!(
+            changed_files(&commits, None).unwrap(),
+            ["README.md", "services/api/new.rs", "services/api/old.rs"]
+        );
+        assert_eq!(changed_files(&commits, Some(2)).unwrap().len(), 3);
+        assert!(changed_files(&commits, Some(30)).is_none());
+        assert!(changed_files(&[], None).is_none());
+        assert!(previous_commit(Some("0".repeat(40))).is_none());
+        assert_eq!(previous_commit(Some("abc123".to_string())).as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_headers_are_case_insensitive() {
+        let delivery = WebhookDelivery::new(
+            [("X-GitHub-Event".to_string(), "push".to_string())],
+            b"{}",
+        );
+        assert_eq!(delivery.header("x-github-event"), Some("push"));
+        assert!(delivery.required_header("X-Hub-Signature-256").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Agent endpoints
+
+use super::{ApiResult, Created, parse_id};
+use super::auth::AuthUser;
+use crate::application::Application;
+use crate::application::dto::{AgentDto, RegisterAgentRequest};
+use crate::domain::services::authorization::Action;
+use crate::domain::value_objects::agent_id::AgentId;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// List all agents
+pub(super) async fn list_agents(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+) -> ApiResult<Vec<AgentDto>> {
+    app.authorization_service().authorize(&user, Action::ReadAgents)?;
+    let agents = app.agent_service().list_agents().await?;
+    Ok(Json(agents.iter().map(AgentDto::from).collect()))
+}
+
+/// Register an agent
+pub(super) async fn register_agent(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Json(request): Json<RegisterAgentRequest>,
+) -> Created<AgentDto> {
+    app.authorization_service().authorize(&user, Action::ManageAgents)?;
+    let agent = app
+        .agent_service()
+        .register_agent(
+            request.name,
+            request.max_concurrent_jobs,
+            request.platform,
+            request.version,
+            request.ip_address,
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(AgentDto::from(&agent))))
+}
+
+/// Get an agent
+pub(super) async fn get_agent(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<AgentDto> {
+    app.authorization_service().authorize(&user, Action::ReadAgents)?;
+    let agent_id = parse_id(&id, AgentId::parse, "agent")?;
+    let agent = app.agent_service().get_agent(&agent_id).await?;
+    Ok(Json(AgentDto::from(&agent)))
+}
@@ -0,0 +1,109 @@
+//! Artifact endpoints
+
+use super::{ApiError, ApiResult, authorize_project, parse_id};
+use super::auth::AuthUser;
+use crate::application::Application;
+use crate::application::dto::ArtifactDto;
+use crate::domain::entities::artifact::Artifact;
+use crate::domain::services::authorization::Action;
+use crate::domain::value_objects::artifact_id::ArtifactId;
+use crate::infrastructure::storage::ArtifactStore;
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Redirect, Response},
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::io::ReaderStream;
+
+/// How long a presigned download URL stays valid
+const DOWNLOAD_URL_EXPIRY: Duration = Duration::from_mins(15);
+
+/// Download an artifact, redirecting to the storage backend when it can
+/// hand out a presigned URL and streaming the contents otherwise
+pub(super) async fn download_artifact(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Response, ApiError> {
+    let mut artifact = authorize_artifact(&app, &user, &id, Action::DownloadArtifacts).await?;
+    if artifact.is_expired() {
+        return Err(crate::Error::not_found(format!("Artifact {} has expired", artifact.id())).into());
+    }
+    artifact.record_access();
+    app.artifact_repository().update(&artifact).await?;
+
+    let store = app.artifact_store();
+    if let Some(url) = store.download_url(artifact.path(), DOWNLOAD_URL_EXPIRY).await? {
+        return Ok(Redirect::temporary(&url).into_response());
+    }
+    let contents = store.get(artifact.path()).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, artifact.content_type().to_string()),
+            (header::CONTENT_LENGTH, artifact.size().to_string()),
+            (header::CONTENT_DISPOSITION, content_disposition(artifact.name())),
+        ],
+        Body::from_stream(ReaderStream::new(contents)),
+    )
+        .into_response())
+}
+
+/// Keep an artifact regardless of retention
+pub(super) async fn pin_artifact(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<ArtifactDto> {
+    let mut artifact = authorize_artifact(&app, &user, &id, Action::ManageArtifacts).await?;
+    artifact.pin();
+    app.artifact_repository().update(&artifact).await?;
+    Ok(Json(ArtifactDto::from(&artifact)))
+}
+
+/// Let an artifact expire with retention again
+pub(super) async fn unpin_artifact(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<ArtifactDto> {
+    let mut artifact = authorize_artifact(&app, &user, &id, Action::ManageArtifacts).await?;
+    artifact.unpin();
+    app.artifact_repository().update(&artifact).await?;
+    Ok(Json(ArtifactDto::from(&artifact)))
+}
+
+/// Load an artifact and check that the caller may perform an action on the
+/// project of its build
+async fn authorize_artifact(
+    app: &Application,
+    user: &AuthUser,
+    id: &str,
+    action: Action,
+) -> crate::Result<Artifact> {
+    let artifact_id = parse_id(id, ArtifactId::parse, "artifact")?;
+    let artifact = app
+        .artifact_repository()
+        .find_by_id(&artifact_id)
+        .await?
+        .ok_or_else(|| crate::Error::not_found(format!("Artifact {artifact_id} not found")))?;
+    let build = app.build_service().get_build(artifact.build_id()).await?;
+    authorize_project(app, user, build.project_id(), action).await?;
+    Ok(artifact)
+}
+
+/// `Content-Disposition` header offering the artifact's file name, with
+/// characters that would break the quoted header value replaced
+fn content_disposition(name: &str) -> String {
+    let file_name: String = name
+        .rsplit('/')
+        .next()
+        .unwrap_or(name)
+        .chars()
+        .map(|c| if c == ' ' || (c.is_ascii_graphic() && !matches!(c, '"' | '\\')) { c } else { '_' })
+        .collect();
+    format!("attachment; filename=\"{file_name}\"")
+}
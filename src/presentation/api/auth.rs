@@ -0,0 +1,96 @@
+//! Authentication endpoints and extractor
+
+use super::{ApiError, ApiResult};
+use crate::application::Application;
+use crate::application::dto::{LoginRequest, RefreshTokenRequest, TokenResponse, UserDto};
+use crate::domain::services::{api_token::is_api_token, auth::AuthenticatedUser};
+use axum::{
+    Json,
+    extract::{FromRequestParts, Request, State},
+    http::{header::AUTHORIZATION, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// The authenticated caller of a request
+///
+/// Extracted from an `Authorization: Bearer <token>` header holding either a JWT
+/// access token or an API token. Deactivated users are rejected. Once extracted, the caller is cached in the request
+/// extensions.
+#[derive(Debug, Clone)]
+pub(super) struct AuthUser(pub AuthenticatedUser);
+
+impl std::ops::Deref for AuthUser {
+    type Target = AuthenticatedUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequestParts<Arc<Application>> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        app: &Arc<Application>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| crate::Error::authentication("Missing bearer token"))?;
+
+        let token = token.trim();
+        let user = if is_api_token(token) {
+            AuthUser(app.api_token_service().authenticate(token).await?)
+        } else {
+            AuthUser(app.auth_service().authenticate(token).await?)
+        };
+        parts.extensions.insert(user.clone());
+
+        Ok(user)
+    }
+}
+
+/// Middleware rejecting unauthenticated requests
+pub(super) async fn require_auth(user: AuthUser, request: Request, next: Next) -> Response {
+    tracing::debug!(user_id = %user.user_id, role = ?user.role, "Authenticated request");
+    next.run(request).await
+}
+
+/// Log in with a username and password
+pub(super) async fn login(
+    State(app): State<Arc<Application>>,
+    Json(request): Json<LoginRequest>,
+) -> ApiResult<TokenResponse> {
+    let tokens = app
+        .auth_service()
+        .login(&request.username, &request.password)
+        .await?;
+    Ok(Json(tokens.into()))
+}
+
+/// Exchange a refresh token for new tokens
+pub(super) async fn refresh(
+    State(app): State<Arc<Application>>,
+    Json(request): Json<RefreshTokenRequest>,
+) -> ApiResult<TokenResponse> {
+    let tokens = app.auth_service().refresh(&request.refresh_token).await?;
+    Ok(Json(tokens.into()))
+}
+
+/// Get the authenticated caller
+pub(super) async fn me(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+) -> ApiResult<UserDto> {
+    let current = app.auth_service().get_user(&user.user_id).await?;
+    Ok(Json(UserDto::from(&current)))
+}
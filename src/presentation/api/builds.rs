@@ -0,0 +1,136 @@
+//! Build endpoints
+
+use super::{ApiResult, Created, authorize_project, parse_id, readable_project_ids};
+use super::auth::AuthUser;
+use super::pipelines::authorize_pipeline;
+use crate::application::Application;
+use crate::application::dto::{BuildDto, ListBuildsQuery, TriggerBuildRequest};
+use crate::domain::entities::build::{Build, BuildTrigger};
+use crate::domain::repositories::build::BuildQueryOptions;
+use crate::domain::services::authorization::Action;
+use crate::domain::value_objects::{
+    build_id::BuildId,
+    pipeline_id::PipelineId,
+    project_id::ProjectId,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// List builds of projects the caller may read, newest first
+pub(super) async fn list_builds(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Query(query): Query<ListBuildsQuery>,
+) -> ApiResult<Vec<BuildDto>> {
+    let options = BuildQueryOptions {
+        project_id: query
+            .project_id
+            .map(|id| parse_id(&id, ProjectId::parse, "project"))
+            .transpose()?,
+        pipeline_id: query
+            .pipeline_id
+            .map(|id| parse_id(&id, PipelineId::parse, "pipeline"))
+            .transpose()?,
+        // Unreadable builds are filtered out before paginating, so they
+        // don't cut pages short
+        project_ids: readable_project_ids(&app, &user).await?,
+        status: query.status.map(|s| s.parse()).transpose()?,
+        branch: query.branch,
+        limit: query.limit,
+        offset: query.offset,
+        sort_by: Some("created_at".to_string()),
+        sort_desc: true,
+    };
+    let builds = app.build_service().list_builds(options).await?;
+    Ok(Json(builds.iter().map(BuildDto::from).collect()))
+}
+
+/// Trigger a build of an enabled pipeline
+pub(super) async fn trigger_build(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Json(request): Json<TriggerBuildRequest>,
+) -> Created<BuildDto> {
+    let pipeline = authorize_pipeline(&app, &user, &request.pipeline_id, Action::TriggerBuild).await?;
+    if !pipeline.is_enabled() {
+        return Err(crate::Error::conflict("Pipeline is disabled").into());
+    }
+    let trigger = match &user.api_token {
+        Some(grant) => BuildTrigger::Api {
+            token_id: grant.token_id.to_string(),
+        },
+        None => BuildTrigger::Manual {
+            user_id: user.user_id.to_string(),
+        },
+    };
+    let commit_sha = match request.commit_sha {
+        Some(commit_sha) => commit_sha,
+        None => {
+            app.build_service()
+                .resolve_branch(pipeline.project_id(), &request.branch)
+                .await?
+        }
+    };
+    
+    let build = app
+        .build_service()
+        .create_build(
+            pipeline.id().clone(),
+            pipeline.project_id().clone(),
+            commit_sha,
+            request.branch,
+            trigger,
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(BuildDto::from(&build))))
+}
+
+/// Get a build
+pub(super) async fn get_build(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<BuildDto> {
+    let build = authorize_build(&app, &user, &id, Action::ReadProject).await?;
+    Ok(Json(BuildDto::from(&build)))
+}
+
+/// Cancel a build
+pub(super) async fn cancel_build(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<BuildDto> {
+    let build = authorize_build(&app, &user, &id, Action::CancelBuild).await?;
+    app.build_service().cancel_build(build.id()).await?;
+    let build = app.build_service().get_build(build.id()).await?;
+    Ok(Json(BuildDto::from(&build)))
+}
+
+/// Retry a finished build as a new build
+pub(super) async fn retry_build(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> Created<BuildDto> {
+    let build = authorize_build(&app, &user, &id, Action::TriggerBuild).await?;
+    let build = app.build_service().retry_build(build.id()).await?;
+    Ok((StatusCode::CREATED, Json(BuildDto::from(&build))))
+}
+
+/// Load a build and check that the caller may perform an action on its project
+pub(super) async fn authorize_build(
+    app: &Application,
+    user: &AuthUser,
+    id: &str,
+    action: Action,
+) -> crate::Result<Build> {
+    let build_id = parse_id(id, BuildId::parse, "build")?;
+    let build = app.build_service().get_build(&build_id).await?;
+    authorize_project(app, user, build.project_id(), action).await?;
+    Ok(build)
+}
@@ -0,0 +1,140 @@
+//! Event log endpoints
+
+use super::{ApiResult, authorize_project, parse_id};
+use super::auth::AuthUser;
+use super::builds::authorize_build;
+use super::pipelines::authorize_pipeline;
+use crate::application::Application;
+use crate::application::dto::{EventDto, ListEventsQuery, ReplayEventsRequest, ReplayEventsResponse};
+use crate::domain::events::EventHandler;
+use crate::domain::repositories::event::EventQueryOptions;
+use crate::domain::services::{authorization::Action, event_log::replay_limit};
+use crate::domain::value_objects::{project_id::ProjectId, user_id::UserId};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use std::sync::Arc;
+
+/// List recorded events, oldest first
+pub(super) async fn list_events(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Query(query): Query<ListEventsQuery>,
+) -> ApiResult<Vec<EventDto>> {
+    app.authorization_service().authorize(&user, Action::ReadEvents)?;
+    let events = app.event_log_service().list_events(query.into()).await?;
+    Ok(Json(events.into_iter().map(EventDto::from).collect()))
+}
+
+/// List the events of a build
+pub(super) async fn list_build_events(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<ListEventsQuery>,
+) -> ApiResult<Vec<EventDto>> {
+    authorize_build(&app, &user, &id, Action::ReadProject).await?;
+    list_aggregate_events(&app, "build", id, query).await
+}
+
+/// List the events of a pipeline
+pub(super) async fn list_pipeline_events(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<ListEventsQuery>,
+) -> ApiResult<Vec<EventDto>> {
+    authorize_pipeline(&app, &user, &id, Action::ReadProject).await?;
+    list_aggregate_events(&app, "pipeline", id, query).await
+}
+
+/// List the events of a project, its pipelines and their builds
+pub(super) async fn list_project_events(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<ListEventsQuery>,
+) -> ApiResult<Vec<EventDto>> {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::ReadProject).await?;
+    let options = EventQueryOptions {
+        project_id: Some(project_id),
+        ..query.into()
+    };
+    let events = app.event_log_service().list_events(options).await?;
+    Ok(Json(events.into_iter().map(EventDto::from).collect()))
+}
+
+/// List the events of an agent
+pub(super) async fn list_agent_events(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<ListEventsQuery>,
+) -> ApiResult<Vec<EventDto>> {
+    app.authorization_service().authorize(&user, Action::ReadAgents)?;
+    list_aggregate_events(&app, "agent", id, query).await
+}
+
+/// List the events of a user; users may always read their own
+pub(super) async fn list_user_events(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<ListEventsQuery>,
+) -> ApiResult<Vec<EventDto>> {
+    let user_id = parse_id(&id, UserId::parse, "user")?;
+    if user_id != user.user_id {
+        app.authorization_service().authorize(&user, Action::ManageUsers)?;
+    }
+    list_aggregate_events(&app, "user", id, query).await
+}
+
+async fn list_aggregate_events(
+    app: &Application,
+    aggregate_type: &str,
+    aggregate_id: String,
+    query: ListEventsQuery,
+) -> ApiResult<Vec<EventDto>> {
+    let options = EventQueryOptions {
+        aggregate_type: Some(aggregate_type.to_string()),
+        aggregate_id: Some(aggregate_id),
+        ..query.into()
+    };
+    let events = app.event_log_service().list_events(options).await?;
+    Ok(Json(events.into_iter().map(EventDto::from).collect()))
+}
+
+/// Re-deliver a page of recorded events to a subscribed handler
+pub(super) async fn replay_events(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Json(request): Json<ReplayEventsRequest>,
+) -> ApiResult<ReplayEventsResponse> {
+    app.authorization_service().authorize(&user, Action::ReplayEvents)?;
+    if request.handler == app.event_log_service().name() {
+        return Err(crate::Error::validation("Events cannot be replayed into the event log").into());
+    }
+
+    let events = app
+        .event_log_service()
+        .list_events(EventQueryOptions {
+            since: request.since,
+            after_sequence: request.after,
+            limit: Some(replay_limit(request.limit)),
+            ..Default::default()
+        })
+        .await?;
+    let last_sequence = events.last().map(|e| e.sequence);
+    let delivered = app
+        .event_bus()
+        .redeliver(&request.handler, events.into_iter().map(|e| e.event).collect())
+        .await?;
+
+    Ok(Json(ReplayEventsResponse {
+        handler: request.handler,
+        delivered,
+        last_sequence,
+    }))
+}
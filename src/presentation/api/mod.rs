@@ -1,22 +1,227 @@
 //! REST API implementation
 
+mod agents;
+mod artifacts;
+mod auth;
+mod builds;
+mod events;
+mod pipelines;
+mod projects;
+mod secrets;
+mod storage;
+mod tokens;
+mod users;
+mod webhooks;
+
 use crate::application::Application;
+use crate::domain::entities::{project::Project, user::UserRole};
+use crate::domain::services::{auth::AuthenticatedUser, authorization::Action};
+use crate::domain::value_objects::project_id::ProjectId;
+use crate::error::ErrorResponse;
 use axum::{
+    Json,
     Router,
-    routing::get,
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
 };
+use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Create the API server
-pub async fn create_server(_app: Application) -> crate::Result<Router> {
+pub async fn create_server(app: Application) -> crate::Result<Router> {
+    let state = Arc::new(app);
+    
     // Create router
     let router = Router::new()
-        .route("/health", get(health_check));
+        .route("/health", get(health_check))
+        .nest("/api/v1", api_routes(state.clone()))
+        .with_state(state);
     
     Ok(router)
 }
 
+/// Versioned API routes; everything except the auth and webhook endpoints
+/// requires a token
+fn api_routes(state: Arc<Application>) -> Router<Arc<Application>> {
+    Router::new()
+        // Projects
+        .route("/projects", get(projects::list_projects).post(projects::create_project))
+        .route(
+            "/projects/{id}",
+            get(projects::get_project)
+                .put(projects::update_project)
+                .delete(projects::delete_project),
+        )
+        .route("/projects/{id}/pipelines", get(projects::list_project_pipelines))
+        .route("/projects/{id}/members", get(projects::list_members))
+        .route(
+            "/projects/{id}/members/{user_id}",
+            axum::routing::put(projects::set_member).delete(projects::remove_member),
+        )
+        .route("/projects/{id}/secrets", get(secrets::list_project_secrets))
+        .route(
+            "/projects/{id}/secrets/{name}",
+            axum::routing::put(secrets::set_project_secret).delete(secrets::delete_project_secret),
+        )
+        .route("/projects/{id}/webhook-secret", axum::routing::put(secrets::set_webhook_secret))
+        .route(
+            "/projects/{id}/repository-token",
+            axum::routing::put(secrets::set_repository_token).delete(secrets::delete_repository_token),
+        )
+        .route("/projects/{id}/events", get(events::list_project_events))
+        // Pipelines
+        .route("/pipelines", get(pipelines::list_pipelines).post(pipelines::create_pipeline))
+        .route(
+            "/pipelines/{id}",
+            get(pipelines::get_pipeline)
+                .put(pipelines::update_pipeline)
+                .delete(pipelines::delete_pipeline),
+        )
+        .route("/pipelines/{id}/enable", post(pipelines::enable_pipeline))
+        .route("/pipelines/{id}/disable", post(pipelines::disable_pipeline))
+        .route("/pipelines/{id}/builds", get(pipelines::list_pipeline_builds))
+        .route("/pipelines/{id}/secrets", get(secrets::list_pipeline_secrets))
+        .route(
+            "/pipelines/{id}/secrets/{name}",
+            axum::routing::put(secrets::set_pipeline_secret).delete(secrets::delete_pipeline_secret),
+        )
+        .route("/pipelines/{id}/events", get(events::list_pipeline_events))
+        // Builds
+        .route("/builds", get(builds::list_builds).post(builds::trigger_build))
+        .route("/builds/{id}", get(builds::get_build))
+        .route("/builds/{id}/cancel", post(builds::cancel_build))
+        .route("/builds/{id}/retry", post(builds::retry_build))
+        .route("/builds/{id}/events", get(events::list_build_events))
+        // Agents
+        .route("/agents", get(agents::list_agents).post(agents::register_agent))
+        .route("/agents/{id}", get(agents::get_agent))
+        .route("/agents/{id}/events", get(events::list_agent_events))
+        // Users and API tokens
+        .route("/users", post(users::create_user))
+        .route("/users/{id}/events", get(events::list_user_events))
+        .route("/tokens", get(tokens::list_tokens).post(tokens::create_token))
+        .route("/tokens/{id}", axum::routing::delete(tokens::revoke_token))
+        // Events
+        .route("/events", get(events::list_events))
+        .route("/events/replay", post(events::replay_events))
+        // Artifacts
+        .route("/artifacts/{id}/download", get(artifacts::download_artifact))
+        .route(
+            "/artifacts/{id}/pin",
+            post(artifacts::pin_artifact).delete(artifacts::unpin_artifact),
+        )
+        // Storage
+        .route("/storage/stats", get(storage::get_storage_stats))
+        .route("/auth/me", get(auth::me))
+        .route_layer(middleware::from_fn_with_state(state, auth::require_auth))
+        // Authentication
+        .route("/auth/login", post(auth::login))
+        .route("/auth/refresh", post(auth::refresh))
+        // Webhooks, authenticated by signature
+        .route("/webhooks/{source}/{project_id}", post(webhooks::receive_webhook))
+}
+
 /// Health check endpoint
 async fn health_check() -> &'static str {
     "OK"
 }
 
+/// Error returned from API handlers, rendered as an `ErrorResponse`
+struct ApiError(crate::Error);
+
+impl From<crate::Error> for ApiError {
+    fn from(err: crate::Error) -> Self {
+        Self(err)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.0.status_code())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(ErrorResponse::from(self.0))).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+/// Response for endpoints creating a resource
+type Created<T> = std::result::Result<(StatusCode, Json<T>), ApiError>;
+
+/// Response for endpoints without a body
+type NoContent = std::result::Result<StatusCode, ApiError>;
+
+/// Parse an identifier from a path or request body
+fn parse_id<T>(
+    id: &str,
+    parse: fn(&str) -> std::result::Result<T, uuid::Error>,
+    kind: &str,
+) -> crate::Result<T> {
+    parse(id).map_err(|_| crate::Error::validation(format!("Invalid {kind} ID: {id}")))
+}
+
+/// Load a project and check that the caller may perform an action on it
+async fn authorize_project(
+    app: &Application,
+    user: &AuthenticatedUser,
+    project_id: &ProjectId,
+    action: Action,
+) -> crate::Result<Project> {
+    let project = app.project_service().get_project(project_id).await?;
+    app.authorization_service()
+        .authorize_project(user, action, &project)
+        .await?;
+    Ok(project)
+}
+
+/// Get the projects the caller may read, `None` for administrators, who may
+/// read every project including the leftovers of deleted ones
+async fn readable_project_ids(app: &Application, user: &AuthenticatedUser) -> crate::Result<Option<Vec<ProjectId>>> {
+    if user.role == UserRole::Admin {
+        return Ok(None);
+    }
+    
+    let mut readable = Vec::new();
+    for project in app.project_service().list_projects().await? {
+        if app.authorization_service().is_allowed(user, Action::ReadProject, &project).await? {
+            readable.push(project.id().clone());
+        }
+    }
+    Ok(Some(readable))
+}
+
+/// Keep the items belonging to projects the caller may read
+async fn filter_readable<T>(
+    app: &Application,
+    user: &AuthenticatedUser,
+    items: Vec<T>,
+    project_id: impl Fn(&T) -> &ProjectId,
+) -> crate::Result<Vec<T>> {
+    let mut readable: HashMap<ProjectId, bool> = HashMap::new();
+    let mut kept = Vec::new();
+    
+    for item in items {
+        let id = project_id(&item).clone();
+        let allowed = if let Some(allowed) = readable.get(&id) {
+            *allowed
+        } else {
+            let allowed = match authorize_project(app, user, &id, Action::ReadProject).await {
+                Ok(_) => true,
+                Err(crate::Error::Authorization(_)) => false,
+                // Leftovers of deleted projects are only shown to administrators
+                Err(crate::Error::NotFound(_)) => user.role == UserRole::Admin,
+                Err(e) => return Err(e),
+            };
+            readable.insert(id, allowed);
+            allowed
+        };
+        
+        if allowed {
+            kept.push(item);
+        }
+    }
+    
+    Ok(kept)
+}
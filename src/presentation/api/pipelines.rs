@@ -0,0 +1,125 @@
+//! Pipeline endpoints
+
+use super::{ApiResult, Created, NoContent, authorize_project, filter_readable, parse_id};
+use super::auth::AuthUser;
+use crate::application::Application;
+use crate::application::dto::{BuildDto, CreatePipelineRequest, PipelineDto, UpdatePipelineRequest};
+use crate::domain::entities::{pipeline::Pipeline, secret::SecretScope};
+use crate::domain::services::authorization::Action;
+use crate::domain::value_objects::{pipeline_id::PipelineId, project_id::ProjectId};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// List the pipelines of projects the caller may read
+pub(super) async fn list_pipelines(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+) -> ApiResult<Vec<PipelineDto>> {
+    let pipelines = app.pipeline_service().list_pipelines().await?;
+    let pipelines = filter_readable(&app, &user, pipelines, Pipeline::project_id).await?;
+    Ok(Json(pipelines.iter().map(PipelineDto::from).collect()))
+}
+
+/// Create a pipeline in an existing project
+pub(super) async fn create_pipeline(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Json(request): Json<CreatePipelineRequest>,
+) -> Created<PipelineDto> {
+    let project_id = parse_id(&request.project_id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::ManagePipelines).await?;
+    
+    let pipeline = app
+        .pipeline_service()
+        .create_pipeline(project_id, request.name, request.config)
+        .await?;
+    Ok((StatusCode::CREATED, Json(PipelineDto::from(&pipeline))))
+}
+
+/// Get a pipeline
+pub(super) async fn get_pipeline(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<PipelineDto> {
+    let pipeline = authorize_pipeline(&app, &user, &id, Action::ReadProject).await?;
+    Ok(Json(PipelineDto::from(&pipeline)))
+}
+
+/// Replace a pipeline's configuration
+pub(super) async fn update_pipeline(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(request): Json<UpdatePipelineRequest>,
+) -> ApiResult<PipelineDto> {
+    let pipeline = authorize_pipeline(&app, &user, &id, Action::ManagePipelines).await?;
+    app.pipeline_service().update_config(pipeline.id(), request.config).await?;
+    let pipeline = app.pipeline_service().get_pipeline(pipeline.id()).await?;
+    Ok(Json(PipelineDto::from(&pipeline)))
+}
+
+/// Delete a pipeline
+pub(super) async fn delete_pipeline(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> NoContent {
+    let pipeline = authorize_pipeline(&app, &user, &id, Action::ManagePipelines).await?;
+    app.pipeline_service().delete_pipeline(pipeline.id()).await?;
+    app.secret_service().delete_all(&SecretScope::Pipeline(pipeline.id().clone())).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Enable a pipeline
+pub(super) async fn enable_pipeline(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<PipelineDto> {
+    let pipeline = authorize_pipeline(&app, &user, &id, Action::ManagePipelines).await?;
+    app.pipeline_service().enable_pipeline(pipeline.id()).await?;
+    let pipeline = app.pipeline_service().get_pipeline(pipeline.id()).await?;
+    Ok(Json(PipelineDto::from(&pipeline)))
+}
+
+/// Disable a pipeline
+pub(super) async fn disable_pipeline(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<PipelineDto> {
+    let pipeline = authorize_pipeline(&app, &user, &id, Action::ManagePipelines).await?;
+    app.pipeline_service().disable_pipeline(pipeline.id()).await?;
+    let pipeline = app.pipeline_service().get_pipeline(pipeline.id()).await?;
+    Ok(Json(PipelineDto::from(&pipeline)))
+}
+
+/// List the builds of a pipeline
+pub(super) async fn list_pipeline_builds(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<BuildDto>> {
+    let pipeline = authorize_pipeline(&app, &user, &id, Action::ReadProject).await?;
+    let mut builds = app.build_service().get_pipeline_builds(pipeline.id()).await?;
+    builds.sort_by_key(|b| std::cmp::Reverse(b.number()));
+    Ok(Json(builds.iter().map(BuildDto::from).collect()))
+}
+
+/// Load a pipeline and check that the caller may perform an action on its project
+pub(super) async fn authorize_pipeline(
+    app: &Application,
+    user: &AuthUser,
+    id: &str,
+    action: Action,
+) -> crate::Result<Pipeline> {
+    let pipeline_id = parse_id(id, PipelineId::parse, "pipeline")?;
+    let pipeline = app.pipeline_service().get_pipeline(&pipeline_id).await?;
+    authorize_project(app, user, pipeline.project_id(), action).await?;
+    Ok(pipeline)
+}
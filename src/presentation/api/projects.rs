@@ -0,0 +1,173 @@
+//! Project endpoints
+
+use super::{ApiResult, Created, NoContent, authorize_project, parse_id};
+use super::auth::AuthUser;
+use crate::application::Application;
+use crate::application::dto::{
+    CreateProjectRequest,
+    PipelineDto,
+    ProjectDto,
+    ProjectMemberDto,
+    SetProjectMemberRequest,
+    UpdateProjectRequest,
+};
+use crate::domain::entities::{membership::ProjectRole, secret::SecretScope};
+use crate::domain::services::authorization::Action;
+use crate::domain::value_objects::{project_id::ProjectId, user_id::UserId};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// List the projects the caller may read
+pub(super) async fn list_projects(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+) -> ApiResult<Vec<ProjectDto>> {
+    let mut projects = Vec::new();
+    for project in app.project_service().list_projects().await? {
+        if app.authorization_service().is_allowed(&user, Action::ReadProject, &project).await? {
+            projects.push(ProjectDto::from(&project));
+        }
+    }
+    Ok(Json(projects))
+}
+
+/// Create a project; the creator becomes its maintainer
+pub(super) async fn create_project(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Json(request): Json<CreateProjectRequest>,
+) -> Created<ProjectDto> {
+    app.authorization_service().authorize(&user, Action::CreateProject)?;
+    
+    let project = app
+        .project_service()
+        .create_project(
+            request.name,
+            request.repository_url,
+            request.default_branch.unwrap_or_else(|| "main".to_string()),
+            request.description,
+            request.visibility,
+        )
+        .await?;
+    app.authorization_service()
+        .set_member(project.id(), &user.user_id, ProjectRole::Maintainer)
+        .await?;
+    
+    Ok((StatusCode::CREATED, Json(ProjectDto::from(&project))))
+}
+
+/// Get a project
+pub(super) async fn get_project(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<ProjectDto> {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    let project = authorize_project(&app, &user, &project_id, Action::ReadProject).await?;
+    Ok(Json(ProjectDto::from(&project)))
+}
+
+/// Update a project
+pub(super) async fn update_project(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateProjectRequest>,
+) -> ApiResult<ProjectDto> {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::UpdateProject).await?;
+    
+    let project = app
+        .project_service()
+        .update_project(
+            &project_id,
+            request.name,
+            request.repository_url,
+            request.default_branch,
+            request.description,
+            request.visibility,
+        )
+        .await?;
+    Ok(Json(ProjectDto::from(&project)))
+}
+
+/// Delete a project that has no pipelines left
+pub(super) async fn delete_project(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> NoContent {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::DeleteProject).await?;
+    
+    if !app.pipeline_service().get_project_pipelines(&project_id).await?.is_empty() {
+        return Err(crate::Error::conflict("Project still has pipelines").into());
+    }
+    app.project_service().delete_project(&project_id).await?;
+    app.authorization_service().remove_all_members(&project_id).await?;
+    app.secret_service().delete_all(&SecretScope::Webhook(project_id.clone())).await?;
+    app.secret_service().delete_all(&SecretScope::Repository(project_id.clone())).await?;
+    app.secret_service().delete_all(&SecretScope::Project(project_id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the pipelines of a project
+pub(super) async fn list_project_pipelines(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<PipelineDto>> {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::ReadProject).await?;
+    let pipelines = app.pipeline_service().get_project_pipelines(&project_id).await?;
+    Ok(Json(pipelines.iter().map(PipelineDto::from).collect()))
+}
+
+/// List the members of a project
+pub(super) async fn list_members(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<ProjectMemberDto>> {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::ReadProject).await?;
+    let members = app.authorization_service().list_members(&project_id).await?;
+    Ok(Json(members.iter().map(ProjectMemberDto::from).collect()))
+}
+
+/// Add a member to a project or change their role
+pub(super) async fn set_member(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path((id, member_id)): Path<(String, String)>,
+    Json(request): Json<SetProjectMemberRequest>,
+) -> ApiResult<ProjectMemberDto> {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    let member_id = parse_id(&member_id, UserId::parse, "user")?;
+    authorize_project(&app, &user, &project_id, Action::ManageMembers).await?;
+    app.auth_service().get_user(&member_id).await?;
+    
+    let member = app
+        .authorization_service()
+        .set_member(&project_id, &member_id, request.role)
+        .await?;
+    Ok(Json(ProjectMemberDto::from(&member)))
+}
+
+/// Remove a member from a project
+pub(super) async fn remove_member(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path((id, member_id)): Path<(String, String)>,
+) -> NoContent {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    let member_id = parse_id(&member_id, UserId::parse, "user")?;
+    authorize_project(&app, &user, &project_id, Action::ManageMembers).await?;
+    
+    app.authorization_service().remove_member(&project_id, &member_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,144 @@
+//! Project and pipeline secret endpoints
+
+use super::{ApiResult, NoContent, authorize_project, parse_id};
+use super::auth::AuthUser;
+use super::pipelines::authorize_pipeline;
+use crate::application::Application;
+use crate::application::dto::{SecretDto, SetSecretRequest};
+use crate::domain::entities::secret::SecretScope;
+use crate::domain::services::authorization::Action;
+use crate::domain::value_objects::project_id::ProjectId;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// List the secrets of a project
+pub(super) async fn list_project_secrets(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<SecretDto>> {
+    let scope = project_scope(&app, &user, &id).await?;
+    list_secrets(&app, &scope).await
+}
+
+/// Set a project secret
+pub(super) async fn set_project_secret(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path((id, name)): Path<(String, String)>,
+    Json(request): Json<SetSecretRequest>,
+) -> ApiResult<SecretDto> {
+    let scope = project_scope(&app, &user, &id).await?;
+    set_secret(&app, scope, name, &request.value).await
+}
+
+/// Delete a project secret
+pub(super) async fn delete_project_secret(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path((id, name)): Path<(String, String)>,
+) -> NoContent {
+    let scope = project_scope(&app, &user, &id).await?;
+    app.secret_service().delete_secret(&scope, &name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Set the secret verifying a project's incoming webhooks
+pub(super) async fn set_webhook_secret(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(request): Json<SetSecretRequest>,
+) -> NoContent {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::ManageSecrets).await?;
+    app.secret_service().set_webhook_secret(&project_id, &request.value).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Set the token authenticating HTTPS fetches of a project's repository
+pub(super) async fn set_repository_token(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(request): Json<SetSecretRequest>,
+) -> NoContent {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::ManageSecrets).await?;
+    app.secret_service().set_repository_token(&project_id, &request.value).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Remove the token of a project's repository
+pub(super) async fn delete_repository_token(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> NoContent {
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    authorize_project(&app, &user, &project_id, Action::ManageSecrets).await?;
+    app.secret_service().delete_repository_token(&project_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the secrets of a pipeline
+pub(super) async fn list_pipeline_secrets(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<SecretDto>> {
+    let scope = pipeline_scope(&app, &user, &id).await?;
+    list_secrets(&app, &scope).await
+}
+
+/// Set a pipeline secret
+pub(super) async fn set_pipeline_secret(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path((id, name)): Path<(String, String)>,
+    Json(request): Json<SetSecretRequest>,
+) -> ApiResult<SecretDto> {
+    let scope = pipeline_scope(&app, &user, &id).await?;
+    set_secret(&app, scope, name, &request.value).await
+}
+
+/// Delete a pipeline secret
+pub(super) async fn delete_pipeline_secret(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path((id, name)): Path<(String, String)>,
+) -> NoContent {
+    let scope = pipeline_scope(&app, &user, &id).await?;
+    app.secret_service().delete_secret(&scope, &name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn project_scope(app: &Application, user: &AuthUser, id: &str) -> crate::Result<SecretScope> {
+    let project_id = parse_id(id, ProjectId::parse, "project")?;
+    authorize_project(app, user, &project_id, Action::ManageSecrets).await?;
+    Ok(SecretScope::Project(project_id))
+}
+
+async fn pipeline_scope(app: &Application, user: &AuthUser, id: &str) -> crate::Result<SecretScope> {
+    let pipeline = authorize_pipeline(app, user, id, Action::ManageSecrets).await?;
+    Ok(SecretScope::Pipeline(pipeline.id().clone()))
+}
+
+async fn list_secrets(app: &Application, scope: &SecretScope) -> ApiResult<Vec<SecretDto>> {
+    let secrets = app.secret_service().list_secrets(scope).await?;
+    Ok(Json(secrets.iter().map(SecretDto::from).collect()))
+}
+
+async fn set_secret(
+    app: &Application,
+    scope: SecretScope,
+    name: String,
+    value: &str,
+) -> ApiResult<SecretDto> {
+    let secret = app.secret_service().set_secret(scope, name, value).await?;
+    Ok(Json(SecretDto::from(&secret)))
+}
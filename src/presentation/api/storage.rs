@@ -0,0 +1,21 @@
+//! Storage usage endpoints
+
+use super::ApiResult;
+use super::auth::AuthUser;
+use crate::application::Application;
+use crate::application::dto::StorageStatsDto;
+use crate::domain::services::authorization::Action;
+use axum::{Json, extract::State};
+use std::sync::Arc;
+
+/// Get the logical and physical size of stored artifacts and caches
+pub(super) async fn get_storage_stats(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+) -> ApiResult<StorageStatsDto> {
+    app.authorization_service().authorize(&user, Action::ReadStorage)?;
+    Ok(Json(StorageStatsDto {
+        artifacts: app.artifact_store().stats().await?.into(),
+        caches: app.cache_store().stats().await?.into(),
+    }))
+}
@@ -0,0 +1,92 @@
+//! API token endpoints
+
+use super::{ApiResult, Created, NoContent, parse_id};
+use super::auth::AuthUser;
+use crate::application::Application;
+use crate::application::dto::{
+    ApiTokenDto,
+    CreateApiTokenRequest,
+    CreatedApiTokenDto,
+    ListApiTokensQuery,
+};
+use crate::domain::services::authorization::Action;
+use crate::domain::value_objects::{
+    api_token_id::ApiTokenId,
+    project_id::ProjectId,
+    user_id::UserId,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// List the caller's tokens, or another user's for administrators
+pub(super) async fn list_tokens(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Query(query): Query<ListApiTokensQuery>,
+) -> ApiResult<Vec<ApiTokenDto>> {
+    let owner = token_owner(&app, &user, query.user_id.as_deref())?;
+    let tokens = app.api_token_service().list_tokens(&owner).await?;
+    Ok(Json(tokens.iter().map(ApiTokenDto::from).collect()))
+}
+
+/// Create a token for the caller, or for another user for administrators
+pub(super) async fn create_token(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Json(request): Json<CreateApiTokenRequest>,
+) -> Created<CreatedApiTokenDto> {
+    let owner = token_owner(&app, &user, request.user_id.as_deref())?;
+    let project_id = request
+        .project_id
+        .map(|id| parse_id(&id, ProjectId::parse, "project"))
+        .transpose()?;
+    if let Some(project_id) = &project_id {
+        app.project_service().get_project(project_id).await?;
+    }
+    
+    let (token, secret) = app
+        .api_token_service()
+        .create_token(&owner, request.name, request.scopes, project_id, request.expires_at)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiTokenDto {
+            token: ApiTokenDto::from(&token),
+            secret,
+        }),
+    ))
+}
+
+/// Revoke a token
+pub(super) async fn revoke_token(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> NoContent {
+    let token_id = parse_id(&id, ApiTokenId::parse, "API token")?;
+    let token = app.api_token_service().get_token(&token_id).await?;
+    token_owner(&app, &user, Some(&token.user_id().to_string()))?;
+    
+    app.api_token_service().revoke_token(&token_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resolve whose tokens a request is about, checking the caller may manage them
+fn token_owner(app: &Application, user: &AuthUser, user_id: Option<&str>) -> crate::Result<UserId> {
+    app.authorization_service().authorize(user, Action::ManageTokens)?;
+    
+    match user_id {
+        Some(id) => {
+            let owner = parse_id(id, UserId::parse, "user")?;
+            if owner != user.user_id {
+                app.authorization_service().authorize(user, Action::ManageUsers)?;
+            }
+            Ok(owner)
+        }
+        None => Ok(user.user_id.clone()),
+    }
+}
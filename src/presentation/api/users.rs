@@ -0,0 +1,36 @@
+//! User endpoints
+
+use super::Created;
+use super::auth::AuthUser;
+use crate::application::Application;
+use crate::application::dto::{CreateUserRequest, UserDto};
+use crate::domain::entities::user::UserRole;
+use crate::domain::services::authorization::Action;
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// Create a user or service account
+pub(super) async fn create_user(
+    State(app): State<Arc<Application>>,
+    user: AuthUser,
+    Json(request): Json<CreateUserRequest>,
+) -> Created<UserDto> {
+    app.authorization_service().authorize(&user, Action::ManageUsers)?;
+    
+    let password = match request.password {
+        Some(password) => password,
+        // Service accounts cannot log in, so they get a password nobody knows
+        None if request.role == UserRole::Service => uuid::Uuid::new_v4().to_string(),
+        None => return Err(crate::Error::validation("Password is required").into()),
+    };
+    
+    let created = app
+        .auth_service()
+        .register_user(request.username, request.email, password, request.role)
+        .await?;
+    Ok((StatusCode::CREATED, Json(UserDto::from(&created))))
+}
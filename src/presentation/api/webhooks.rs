@@ -0,0 +1,58 @@
+//! Inbound webhook endpoints
+//!
+//! These endpoints are called by forges rather than users, so they sit outside
+//! the authentication layer and authenticate deliveries with the project's
+//! webhook secret instead.
+
+use super::{ApiResult, parse_id};
+use crate::application::Application;
+use crate::application::dto::{BuildDto, WebhookResponse};
+use crate::domain::value_objects::project_id::ProjectId;
+use crate::infrastructure::webhooks::{self, WebhookDelivery};
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use std::sync::Arc;
+
+/// Receive a webhook delivery for a project from a forge
+pub(super) async fn receive_webhook(
+    State(app): State<Arc<Application>>,
+    Path((source, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<WebhookResponse> {
+    let receiver = webhooks::receiver(&source)
+        .ok_or_else(|| crate::Error::not_found(format!("Unknown webhook source: {source}")))?;
+    let project_id = parse_id(&id, ProjectId::parse, "project")?;
+    
+    let delivery = WebhookDelivery::new(
+        headers.iter().filter_map(|(name, value)| {
+            Some((name.to_string(), value.to_str().ok()?.to_string()))
+        }),
+        &body,
+    );
+    let secret = webhook_secret(&app, &project_id).await?;
+    receiver.verify(&secret, &delivery)?;
+    
+    let Some(event) = receiver.parse(&delivery)? else {
+        return Ok(Json(WebhookResponse { event: None, builds: Vec::new() }));
+    };
+    
+    let project = app.project_service().get_project(&project_id).await?;
+    let builds = app.webhook_service().handle_event(&project, &event).await?;
+    Ok(Json(WebhookResponse {
+        event: Some(event.kind.to_string()),
+        builds: builds.iter().map(BuildDto::from).collect(),
+    }))
+}
+
+/// Get a project's webhook secret; projects without one do not accept webhooks
+async fn webhook_secret(app: &Application, project_id: &ProjectId) -> crate::Result<String> {
+    app.secret_service()
+        .webhook_secret(project_id)
+        .await?
+        .ok_or_else(|| crate::Error::authentication("Webhooks are not configured for this project"))
+}
@@ -0,0 +1,744 @@
+//! REST API integration tests
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Method, Request, StatusCode},
+};
+use ferrous_ci_cd::{
+    Config,
+    application::Application,
+    config::InitialAdminConfig,
+    domain::entities::artifact::{Artifact, ArtifactType},
+    domain::entities::user::UserRole,
+    domain::repositories::artifact::ArtifactRepository,
+    domain::value_objects::build_id::BuildId,
+    infrastructure::storage::{ArtifactStore, dedup::DedupArtifactStore},
+    presentation::api::create_server,
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Router with an `admin` user and that user's access token, plus the
+/// non-admin users `dev` (Developer) and `viewer` (Viewer), plus the
+/// artifact storage for seeding build outputs
+struct TestApi {
+    router: Router,
+    token: String,
+    artifacts: Arc<dyn ArtifactRepository>,
+    artifact_store: Arc<DedupArtifactStore>,
+    _cache: tempfile::TempDir,
+}
+
+impl TestApi {
+    async fn new() -> Self {
+        let cache = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.storage.cache_path = cache.path().to_str().unwrap().to_string();
+        config.storage.artifacts_path = cache.path().join("artifacts").to_str().unwrap().to_string();
+        config.security.password_hash_cost = 4;
+        config.security.initial_admin = Some(InitialAdminConfig {
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            password: "admin-password".to_string(),
+        });
+
+        let app = Application::new(config)
+            .await
+            .expect("Failed to create application");
+        for (username, role) in [("dev", UserRole::Developer), ("viewer", UserRole::Viewer)] {
+            app.auth_service()
+                .register_user(
+                    username.to_string(),
+                    format!("{username}@example.com"),
+                    format!("{username}-password"),
+                    role,
+                )
+                .await
+                .expect("Failed to register user");
+        }
+        let artifacts = app.artifact_repository().clone();
+        let artifact_store = app.artifact_store().clone();
+        let router = create_server(app).await.expect("Failed to create server");
+        let token = login(&router, "admin", "admin-password").await;
+
+        Self { router, token, artifacts, artifact_store, _cache: cache }
+    }
+
+    /// Log in as one of the non-admin users
+    async fn login_as(&self, username: &str) -> String {
+        login(&self.router, username, &format!("{username}-password")).await
+    }
+}
+
+async fn login(router: &Router, username: &str, password: &str) -> String {
+    let (status, tokens) = request(router, Method::POST, "/api/v1/auth/login", None, Some(json!({
+        "username": username,
+        "password": password
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    tokens["access_token"].as_str().unwrap().to_string()
+}
+
+async fn send(api: &TestApi, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    request(&api.router, method, uri, Some(&api.token), body).await
+}
+
+async fn request(
+    router: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {token}"));
+    }
+    let request = request
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap();
+
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap()
+    };
+    (status, body)
+}
+
+fn pipeline_config() -> Value {
+    json!({
+        "version": "1.0",
+        "stages": [{
+            "name": "build",
+            "jobs": [{
+                "name": "compile",
+                "image": null,
+                "commands": ["cargo build"],
+                "environment": {},
+                "working_directory": null,
+                "timeout": null,
+                "retry": null,
+                "artifacts": null,
+                "cache": null,
+                "needs": [],
+                "when": null
+            }],
+            "parallel": false,
+            "when": null
+        }],
+        "triggers": [{"type": "Manual"}],
+        "environment": {},
+        "notifications": null
+    })
+}
+
+#[tokio::test]
+async fn test_project_pipeline_build_flow() {
+    let api = TestApi::new().await;
+
+    let (status, project) = send(&api, Method::POST, "/api/v1/projects", Some(json!({
+        "name": "api-project",
+        "repository_url": "https://github.com/test/repo.git"
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let project_id = project["id"].as_str().unwrap();
+
+    let (status, pipeline) = send(&api, Method::POST, "/api/v1/pipelines", Some(json!({
+        "project_id": project_id,
+        "name": "ci",
+        "config": pipeline_config()
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let pipeline_id = pipeline["id"].as_str().unwrap();
+
+    // Disabled pipelines cannot be triggered
+    let (status, _) = send(&api, Method::POST, &format!("/api/v1/pipelines/{pipeline_id}/disable"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let trigger = json!({"pipeline_id": pipeline_id, "commit_sha": "abc123", "branch": "main"});
+    let (status, error) = send(&api, Method::POST, "/api/v1/builds", Some(trigger.clone())).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["code"], "CONFLICT");
+
+    send(&api, Method::POST, &format!("/api/v1/pipelines/{pipeline_id}/enable"), None).await;
+    let (status, build) = send(&api, Method::POST, "/api/v1/builds", Some(trigger)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(build["number"], 1);
+    assert_eq!(build["project_id"], project_id);
+    let build_id = build["id"].as_str().unwrap();
+
+    // Without a commit, the branch must be resolved from the repository
+    let branch_only = json!({"pipeline_id": pipeline_id, "branch": "main"});
+    let (status, error) = send(&api, Method::POST, "/api/v1/builds", Some(branch_only)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error["code"], "NOT_FOUND");
+
+    let (status, build) = send(&api, Method::POST, &format!("/api/v1/builds/{build_id}/cancel"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(build["status"], "Cancelled");
+
+    let (status, retried) = send(&api, Method::POST, &format!("/api/v1/builds/{build_id}/retry"), None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(retried["number"], 2);
+
+    let (_, builds) = send(&api, Method::GET, &format!("/api/v1/builds?pipeline_id={pipeline_id}&status=pending"), None).await;
+    assert_eq!(builds.as_array().unwrap().len(), 1);
+
+    // Projects with pipelines cannot be deleted
+    let (status, _) = send(&api, Method::DELETE, &format!("/api/v1/projects/{project_id}"), None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send(&api, Method::DELETE, &format!("/api/v1/pipelines/{pipeline_id}"), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&api, Method::DELETE, &format!("/api/v1/projects/{project_id}"), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_update_project() {
+    let api = TestApi::new().await;
+
+    let (_, project) = send(&api, Method::POST, "/api/v1/projects", Some(json!({
+        "name": "before",
+        "repository_url": "https://github.com/test/repo.git"
+    }))).await;
+    let uri = format!("/api/v1/projects/{}", project["id"].as_str().unwrap());
+
+    let (status, project) = send(&api, Method::PUT, &uri, Some(json!({"name": "after"}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(project["name"], "after");
+
+    let (_, projects) = send(&api, Method::GET, "/api/v1/projects", None).await;
+    assert_eq!(projects.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_errors_are_json() {
+    let api = TestApi::new().await;
+
+    let (status, error) = send(&api, Method::GET, "/api/v1/builds/not-a-uuid", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["code"], "VALIDATION_ERROR");
+
+    let uri = format!("/api/v1/agents/{}", uuid::Uuid::new_v4());
+    let (status, error) = send(&api, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error["code"], "NOT_FOUND");
+
+    let (status, agents) = send(&api, Method::GET, "/api/v1/agents", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(agents.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_authentication() {
+    let api = TestApi::new().await;
+
+    let (status, error) = request(&api.router, Method::GET, "/api/v1/projects", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error["code"], "AUTHENTICATION_ERROR");
+
+    let (status, _) = request(&api.router, Method::GET, "/api/v1/projects", Some("garbage"), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = request(&api.router, Method::POST, "/api/v1/auth/login", None, Some(json!({
+        "username": "admin",
+        "password": "wrong"
+    }))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, me) = send(&api, Method::GET, "/api/v1/auth/me", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me["username"], "admin");
+    assert_eq!(me["role"], "Admin");
+}
+
+#[tokio::test]
+async fn test_authorization() {
+    let api = TestApi::new().await;
+    let dev = api.login_as("dev").await;
+    let viewer = api.login_as("viewer").await;
+
+    // Viewers cannot create projects
+    let (status, error) = request(&api.router, Method::POST, "/api/v1/projects", Some(&viewer), Some(json!({
+        "name": "denied",
+        "repository_url": "https://github.com/test/denied.git"
+    }))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(error["code"], "AUTHORIZATION_ERROR");
+
+    // The creator of a project becomes its maintainer
+    let (status, project) = request(&api.router, Method::POST, "/api/v1/projects", Some(&dev), Some(json!({
+        "name": "internal",
+        "repository_url": "https://github.com/test/internal.git",
+        "visibility": "Internal"
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let project_id = project["id"].as_str().unwrap();
+    let (status, pipeline) = request(&api.router, Method::POST, "/api/v1/pipelines", Some(&dev), Some(json!({
+        "project_id": project_id,
+        "name": "ci",
+        "config": pipeline_config()
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let trigger = json!({"pipeline_id": pipeline["id"], "commit_sha": "abc123", "branch": "main"});
+
+    // Viewers can read Internal projects but not trigger builds
+    let uri = format!("/api/v1/projects/{project_id}");
+    let (status, _) = request(&api.router, Method::GET, &uri, Some(&viewer), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = request(&api.router, Method::POST, "/api/v1/builds", Some(&viewer), Some(trigger.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Private projects need explicit membership
+    let (status, _) = request(&api.router, Method::PUT, &uri, Some(&dev), Some(json!({"visibility": "Private"}))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = request(&api.router, Method::GET, &uri, Some(&viewer), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, projects) = request(&api.router, Method::GET, "/api/v1/projects", Some(&viewer), None).await;
+    assert!(projects.as_array().unwrap().is_empty());
+
+    let (_, me) = request(&api.router, Method::GET, "/api/v1/auth/me", Some(&viewer), None).await;
+    let member_uri = format!("/api/v1/projects/{project_id}/members/{}", me["id"].as_str().unwrap());
+    let (status, _) = request(&api.router, Method::PUT, &member_uri, Some(&viewer), Some(json!({"role": "Developer"}))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, member) = request(&api.router, Method::PUT, &member_uri, Some(&dev), Some(json!({"role": "Developer"}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(member["role"], "Developer");
+
+    // A membership never lifts a global viewer above read access
+    let (status, _) = request(&api.router, Method::GET, &uri, Some(&viewer), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = request(&api.router, Method::POST, "/api/v1/builds", Some(&viewer), Some(trigger)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, members) = send(&api, Method::GET, &format!("/api/v1/projects/{project_id}/members"), None).await;
+    assert_eq!(members.as_array().unwrap().len(), 2);
+
+    // Only admins can register agents
+    let agent = json!({
+        "name": "agent-1",
+        "max_concurrent_jobs": 2,
+        "platform": {
+            "os": "linux",
+            "os_version": "6.1",
+            "architecture": "x86_64",
+            "cpu_cores": 4,
+            "memory_mb": 8192,
+            "disk_gb": 100
+        },
+        "version": "1.0.0",
+        "ip_address": "10.0.0.1"
+    });
+    let (status, _) = request(&api.router, Method::POST, "/api/v1/agents", Some(&dev), Some(agent.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(&api, Method::POST, "/api/v1/agents", Some(agent)).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_build_pages_skip_unreadable_projects() {
+    let api = TestApi::new().await;
+    let viewer = api.login_as("viewer").await;
+
+    let mut pipelines = Vec::new();
+    for (name, visibility) in [("open", "Internal"), ("closed", "Private")] {
+        let (_, project) = send(&api, Method::POST, "/api/v1/projects", Some(json!({
+            "name": name,
+            "repository_url": format!("https://github.com/test/{name}.git"),
+            "visibility": visibility
+        }))).await;
+        let (_, pipeline) = send(&api, Method::POST, "/api/v1/pipelines", Some(json!({
+            "project_id": project["id"],
+            "name": "ci",
+            "config": pipeline_config()
+        }))).await;
+        pipelines.push(pipeline["id"].clone());
+    }
+    for pipeline_id in [&pipelines[0], &pipelines[0], &pipelines[1], &pipelines[1]] {
+        let trigger = json!({"pipeline_id": pipeline_id, "commit_sha": "abc123", "branch": "main"});
+        let (status, _) = send(&api, Method::POST, "/api/v1/builds", Some(trigger)).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    // The newest builds belong to the private project the viewer cannot read
+    let (_, page) = request(&api.router, Method::GET, "/api/v1/builds?limit=1", Some(&viewer), None).await;
+    assert_eq!(page.as_array().unwrap().len(), 1);
+    assert_eq!(page[0]["pipeline_id"], pipelines[0]);
+    let (_, page) = request(&api.router, Method::GET, "/api/v1/builds?limit=1&offset=1", Some(&viewer), None).await;
+    assert_eq!(page.as_array().unwrap().len(), 1);
+    let (_, page) = request(&api.router, Method::GET, "/api/v1/builds?offset=2", Some(&viewer), None).await;
+    assert!(page.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_api_tokens() {
+    let api = TestApi::new().await;
+
+    let (status, bot) = send(&api, Method::POST, "/api/v1/users", Some(json!({
+        "username": "deploy-bot",
+        "email": "deploy-bot@example.com",
+        "role": "Service"
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let bot_id = bot["id"].as_str().unwrap();
+
+    let (_, project) = send(&api, Method::POST, "/api/v1/projects", Some(json!({
+        "name": "tokens",
+        "repository_url": "https://github.com/test/tokens.git",
+        "visibility": "Internal"
+    }))).await;
+    let project_id = project["id"].as_str().unwrap();
+    let (_, pipeline) = send(&api, Method::POST, "/api/v1/pipelines", Some(json!({
+        "project_id": project_id,
+        "name": "ci",
+        "config": pipeline_config()
+    }))).await;
+    let trigger = json!({"pipeline_id": pipeline["id"], "commit_sha": "abc123", "branch": "main"});
+    let (status, _) = send(&api, Method::PUT, &format!("/api/v1/projects/{project_id}/members/{bot_id}"), Some(json!({
+        "role": "Developer"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only admins create tokens for other users
+    let dev = api.login_as("dev").await;
+    let request_body = json!({
+        "name": "deploy",
+        "scopes": ["builds:trigger"],
+        "project_id": project_id,
+        "user_id": bot_id
+    });
+    let (status, _) = request(&api.router, Method::POST, "/api/v1/tokens", Some(&dev), Some(request_body.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, token) = send(&api, Method::POST, "/api/v1/tokens", Some(request_body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let secret = token["secret"].as_str().unwrap();
+    let token_id = token["id"].as_str().unwrap();
+
+    // Builds record the token's ID, never its secret
+    let (status, build) = request(&api.router, Method::POST, "/api/v1/builds", Some(secret), Some(trigger.clone())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(build["trigger"], json!({"type": "Api", "token_id": token_id}));
+
+    // Scopes limit what the token can do
+    let (status, _) = request(&api.router, Method::GET, &format!("/api/v1/projects/{project_id}"), Some(secret), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = request(&api.router, Method::GET, "/api/v1/tokens", Some(secret), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (_, tokens) = send(&api, Method::GET, &format!("/api/v1/tokens?user_id={bot_id}"), None).await;
+    assert!(tokens[0]["last_used_at"].is_string());
+    assert!(tokens[0].get("secret").is_none());
+    assert!(tokens[0].get("secret_hash").is_none());
+
+    let (status, _) = send(&api, Method::DELETE, &format!("/api/v1/tokens/{token_id}"), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, error) = request(&api.router, Method::POST, "/api/v1/builds", Some(secret), Some(trigger)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error["code"], "AUTHENTICATION_ERROR");
+
+    // Service accounts cannot log in with a password
+    let (status, _) = request(&api.router, Method::POST, "/api/v1/auth/login", None, Some(json!({
+        "username": "deploy-bot",
+        "password": ""
+    }))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_secrets() {
+    let api = TestApi::new().await;
+
+    let (_, project) = send(&api, Method::POST, "/api/v1/projects", Some(json!({
+        "name": "secrets",
+        "repository_url": "https://github.com/test/secrets.git",
+        "visibility": "Internal"
+    }))).await;
+    let uri = format!("/api/v1/projects/{}/secrets", project["id"].as_str().unwrap());
+
+    let (status, secret) = send(&api, Method::PUT, &format!("{uri}/REGISTRY_PASSWORD"), Some(json!({
+        "value": "hunter2"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(secret["name"], "REGISTRY_PASSWORD");
+    assert!(secret.get("value").is_none());
+
+    let (status, error) = send(&api, Method::PUT, &format!("{uri}/not-valid"), Some(json!({"value": "x"}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["code"], "VALIDATION_ERROR");
+
+    // Secrets are write-only and need maintainer rights
+    let (status, secrets) = send(&api, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(secrets.as_array().unwrap().len(), 1);
+    assert!(!secrets.to_string().contains("hunter2"));
+
+    let viewer = api.login_as("viewer").await;
+    let (status, _) = request(&api.router, Method::GET, &uri, Some(&viewer), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(&api, Method::DELETE, &format!("{uri}/REGISTRY_PASSWORD"), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&api, Method::DELETE, &format!("{uri}/REGISTRY_PASSWORD"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Send a webhook delivery with the given headers
+async fn deliver(
+    api: &TestApi,
+    source: &str,
+    project_id: &str,
+    headers: &[(&str, String)],
+    body: String,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhooks/{source}/{project_id}"))
+        .header("content-type", "application/json");
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+
+    let response = api.router.clone().oneshot(request.body(Body::from(body)).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+fn hmac_sha256(secret: &str, body: &str) -> String {
+    use hmac::{Hmac, Mac};
+
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Send a signed GitHub webhook delivery
+async fn deliver_github(api: &TestApi, project_id: &str, event: &str, payload: &Value, secret: &str) -> (StatusCode, Value) {
+    let body = payload.to_string();
+    let headers = [
+        ("x-github-event", event.to_string()),
+        ("x-hub-signature-256", format!("sha256={}", hmac_sha256(secret, &body))),
+    ];
+    deliver(api, "github", project_id, &headers, body).await
+}
+
+/// Create a project with a webhook secret and a pipeline built on pushes to
+/// `main`, merge requests and tags
+async fn create_webhook_project(api: &TestApi, name: &str) -> String {
+    let (_, project) = send(api, Method::POST, "/api/v1/projects", Some(json!({
+        "name": name,
+        "repository_url": format!("https://git.example.com/test/{name}.git")
+    }))).await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+
+    let mut config = pipeline_config();
+    config["triggers"] = json!([
+        {"type": "Push", "branches": ["main"]},
+        {"type": "PullRequest", "branches": []},
+        {"type": "Tag", "patterns": []}
+    ]);
+    send(api, Method::POST, "/api/v1/pipelines", Some(json!({
+        "project_id": project_id,
+        "name": "ci",
+        "config": config
+    }))).await;
+
+    let uri = format!("/api/v1/projects/{project_id}/webhook-secret");
+    let (status, _) = send(api, Method::PUT, &uri, Some(json!({"value": "webhook-key"}))).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    project_id
+}
+
+#[tokio::test]
+async fn test_github_webhook() {
+    let api = TestApi::new().await;
+
+    let (_, project) = send(&api, Method::POST, "/api/v1/projects", Some(json!({
+        "name": "webhooks",
+        "repository_url": "https://github.com/test/webhooks.git"
+    }))).await;
+    let project_id = project["id"].as_str().unwrap();
+    let mut config = pipeline_config();
+    config["triggers"] = json!([{"type": "Push", "branches": ["main"]}, {"type": "Tag", "patterns": []}]);
+    send(&api, Method::POST, "/api/v1/pipelines", Some(json!({
+        "project_id": project_id,
+        "name": "ci",
+        "config": config
+    }))).await;
+
+    let push = |branch: &str| json!({
+        "ref": format!("refs/heads/{branch}"),
+        "after": "abc123",
+        "head_commit": {"message": "Fix tests", "author": {"name": "Mona Lisa"}}
+    });
+
+    // Projects without a webhook secret reject deliveries
+    let (status, _) = deliver_github(&api, project_id, "push", &push("main"), "webhook-key").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let uri = format!("/api/v1/projects/{project_id}/webhook-secret");
+    let (status, _) = send(&api, Method::PUT, &uri, Some(json!({"value": "webhook-key"}))).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = deliver_github(&api, project_id, "push", &push("main"), "wrong-key").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, response) = deliver_github(&api, project_id, "push", &push("main"), "webhook-key").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["event"], "push");
+    let build = &response["builds"][0];
+    assert_eq!(build["commit_sha"], "abc123");
+    assert_eq!(build["branch"], "main");
+    assert_eq!(build["commit_message"], "Fix tests");
+    assert_eq!(build["commit_author"], "Mona Lisa");
+    assert_eq!(build["trigger"], json!({"type": "Webhook", "source": "github"}));
+
+    // Other branches and events the pipeline does not listen to start nothing
+    let (_, response) = deliver_github(&api, project_id, "push", &push("feature"), "webhook-key").await;
+    assert_eq!(response["builds"].as_array().unwrap().len(), 0);
+    let (_, response) = deliver_github(&api, project_id, "ping", &json!({"zen": "Hi"}), "webhook-key").await;
+    assert!(response["event"].is_null());
+
+    let mut tag = push("main");
+    tag["ref"] = json!("refs/tags/v1.0.0");
+    let (_, response) = deliver_github(&api, project_id, "push", &tag, "webhook-key").await;
+    assert_eq!(response["builds"][0]["branch"], "v1.0.0");
+}
+
+#[tokio::test]
+async fn test_gitlab_and_gitea_webhooks() {
+    let api = TestApi::new().await;
+    let project_id = create_webhook_project(&api, "forges").await;
+
+    // GitLab sends the secret itself
+    let merge_request = json!({
+        "object_kind": "merge_request",
+        "user": {"username": "jdoe"},
+        "object_attributes": {
+            "iid": 12,
+            "title": "Add feature",
+            "source_branch": "feature",
+            "target_branch": "main",
+            "action": "open",
+            "last_commit": {"id": "def456"}
+        }
+    }).to_string();
+    let gitlab = |token: &str| [
+        ("x-gitlab-event", "Merge Request Hook".to_string()),
+        ("x-gitlab-token", token.to_string()),
+    ];
+    let (status, _) = deliver(&api, "gitlab", &project_id, &gitlab("wrong-key"), merge_request.clone()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, response) = deliver(&api, "gitlab", &project_id, &gitlab("webhook-key"), merge_request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["event"], "pull_request");
+    assert_eq!(response["builds"][0]["branch"], "feature");
+    assert_eq!(response["builds"][0]["trigger"], json!({"type": "PullRequest", "pr_number": 12}));
+
+    // Gitea signs the body like GitHub, without the algorithm prefix
+    let push = json!({
+        "ref": "refs/heads/main",
+        "after": "abc123",
+        "head_commit": {"message": "Fix tests", "author": {"name": "Gitea User"}}
+    }).to_string();
+    let headers = [
+        ("x-gitea-event", "push".to_string()),
+        ("x-gitea-signature", hmac_sha256("webhook-key", &push)),
+    ];
+    let (status, response) = deliver(&api, "gitea", &project_id, &headers, push).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["builds"][0]["commit_author"], "Gitea User");
+    assert_eq!(response["builds"][0]["trigger"], json!({"type": "Webhook", "source": "gitea"}));
+
+    let (status, _) = deliver(&api, "bitbucket", &project_id, &[], "{}".to_string()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_storage_stats() {
+    let api = TestApi::new().await;
+
+    let (status, stats) = send(&api, Method::GET, "/api/v1/storage/stats", None).await;
+    assert_eq!(status, StatusCode::OK);
+    for store in ["artifacts", "caches"] {
+        assert_eq!(stats[store]["objects"], 0);
+        assert_eq!(stats[store]["logical_bytes"], 0);
+        assert_eq!(stats[store]["physical_bytes"], 0);
+        assert_eq!(stats[store]["saved_bytes"], 0);
+    }
+
+    // Storage usage spans every project, so only admins can read it
+    let dev = api.login_as("dev").await;
+    let (status, _) = request(&api.router, Method::GET, "/api/v1/storage/stats", Some(&dev), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_pin_artifacts() {
+    let api = TestApi::new().await;
+
+    let uri = format!("/api/v1/artifacts/{}/pin", uuid::Uuid::new_v4());
+    let (status, _) = send(&api, Method::POST, &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&api, Method::DELETE, &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&api, Method::POST, "/api/v1/artifacts/not-an-id/pin", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_download_artifact() {
+    let api = TestApi::new().await;
+
+    let (_, project) = send(&api, Method::POST, "/api/v1/projects", Some(json!({
+        "name": "download-project",
+        "repository_url": "https://github.com/test/repo.git"
+    }))).await;
+    let project_id = project["id"].as_str().unwrap();
+    let (_, pipeline) = send(&api, Method::POST, "/api/v1/pipelines", Some(json!({
+        "project_id": project_id,
+        "name": "ci",
+        "config": pipeline_config()
+    }))).await;
+    let (status, build) = send(&api, Method::POST, "/api/v1/builds", Some(json!({
+        "pipeline_id": pipeline["id"],
+        "commit_sha": "abc123",
+        "branch": "main"
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let build_id = BuildId::parse(build["id"].as_str().unwrap()).unwrap();
+
+    // Local storage has no presigned URLs, so the contents are streamed
+    let key = format!("{project_id}/{build_id}/report.txt");
+    let contents = b"all tests passed".to_vec();
+    let stored = api
+        .artifact_store
+        .put(&key, Box::pin(std::io::Cursor::new(contents.clone())), None)
+        .await
+        .unwrap();
+    let artifact = Artifact::new(build_id, "report.txt".to_string(), key, stored.size, stored.checksum, ArtifactType::TestResults);
+    api.artifacts.save(&artifact).await.unwrap();
+
+    let request = Request::builder()
+        .uri(format!("/api/v1/artifacts/{}/download", artifact.id()))
+        .header("authorization", format!("Bearer {}", api.token))
+        .body(Body::empty())
+        .unwrap();
+    let response = api.router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-disposition"], "attachment; filename=\"report.txt\"");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(body.as_ref(), contents.as_slice());
+
+    let uri = format!("/api/v1/artifacts/{}/download", uuid::Uuid::new_v4());
+    let (status, _) = send(&api, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -198,6 +198,54 @@ async fn test_build_cancellation() {
     assert_eq!(cancelled_build.status(), &BuildStatus::Cancelled);
 }
 
+#[tokio::test]
+async fn test_build_retry() {
+    let fixture = TestFixture::new().await;
+    let project = TestFixture::create_test_project();
+    let config = TestFixture::create_test_pipeline_config();
+
+    let pipeline = fixture
+        .pipeline_service
+        .create_pipeline(
+            project.id().clone(),
+            "test-pipeline".to_string(),
+            config,
+        )
+        .await
+        .expect("Failed to create pipeline");
+
+    let build = fixture
+        .build_service
+        .create_build(
+            pipeline.id().clone(),
+            project.id().clone(),
+            "abc123".to_string(),
+            "main".to_string(),
+            BuildTrigger::Push,
+        )
+        .await
+        .expect("Failed to create build");
+
+    // Pending builds cannot be retried
+    assert!(fixture.build_service.retry_build(build.id()).await.is_err());
+
+    fixture
+        .build_service
+        .cancel_build(build.id())
+        .await
+        .expect("Failed to cancel build");
+
+    let retried = fixture
+        .build_service
+        .retry_build(build.id())
+        .await
+        .expect("Failed to retry build");
+
+    assert_eq!(retried.number(), 2);
+    assert_eq!(retried.commit_sha(), "abc123");
+    assert_eq!(retried.status(), &BuildStatus::Pending);
+}
+
 #[tokio::test]
 async fn test_multiple_builds_same_pipeline() {
     let fixture = TestFixture::new().await;
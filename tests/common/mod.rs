@@ -99,7 +99,7 @@ impl TestFixture {
 
         PipelineConfig::new(
             vec![stage],
-            vec![Trigger::Push { branches: vec!["main".to_string()] }],
+            vec![Trigger::Push { branches: vec!["main".to_string()], paths: vec![], paths_ignore: vec![] }],
         )
     }
 
@@ -153,8 +153,16 @@ impl E2EFixture {
         PipelineConfig::new(
             vec![build_stage, test_stage, lint_stage, deploy_stage],
             vec![
-                Trigger::Push { branches: vec!["main".to_string(), "develop".to_string()] },
-                Trigger::PullRequest { branches: vec!["main".to_string()] },
+                Trigger::Push {
+                    branches: vec!["main".to_string(), "develop".to_string()],
+                    paths: vec![],
+                    paths_ignore: vec![],
+                },
+                Trigger::PullRequest {
+                    branches: vec!["main".to_string()],
+                    paths: vec![],
+                    paths_ignore: vec![],
+                },
                 Trigger::Tag { patterns: vec!["v*".to_string()] },
             ],
         )
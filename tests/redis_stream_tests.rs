@@ -0,0 +1,212 @@
+//! Redis Streams event backend tests
+//!
+//! These tests start a throwaway `redis-server` process, so they are ignored by
+//! default. Run them with `cargo test --test redis_stream_tests -- --ignored`.
+
+use chrono::Utc;
+use ferrous_ci_cd::{
+    config::RedisStreamConfig,
+    domain::{
+        events::{DomainEvent, EventPublisher, InMemoryEventPublisher},
+        value_objects::{build_id::BuildId, build_status::BuildStatus},
+    },
+    infrastructure::events::{RedisStreamPublisher, RedisStreamSubscriber},
+};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// A `redis-server` process listening on a free local port
+struct RedisServer {
+    child: Child,
+    port: u16,
+    dir: tempfile::TempDir,
+}
+
+impl RedisServer {
+    fn start() -> Option<Self> {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .ok()?
+            .local_addr()
+            .ok()?
+            .port();
+        let dir = tempfile::tempdir().ok()?;
+        let child = Self::spawn(port, &dir)?;
+
+        Some(Self { child, port, dir })
+    }
+
+    fn spawn(port: u16, dir: &tempfile::TempDir) -> Option<Child> {
+        let child = Command::new("redis-server")
+            .args(["--port", &port.to_string(), "--save", "", "--appendonly", "no"])
+            .arg("--dir")
+            .arg(dir.path())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        for _ in 0..50 {
+            if std::net::TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return Some(child);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        None
+    }
+
+    fn restart(&mut self) {
+        self.stop();
+        self.child = Self::spawn(self.port, &self.dir).expect("Failed to restart redis-server");
+    }
+
+    fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    fn config(&self, instance_id: &str) -> RedisStreamConfig {
+        RedisStreamConfig {
+            url: format!("redis://127.0.0.1:{}/", self.port),
+            stream: "ferrous:test-events".to_string(),
+            instance_id: Some(instance_id.to_string()),
+            max_len: 1000,
+            exact_trim: false,
+            batch_size: 10,
+            block_ms: 100,
+            reconnect_delay_ms: 50,
+        }
+    }
+}
+
+impl Drop for RedisServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn build_completed() -> DomainEvent {
+    DomainEvent::BuildCompleted {
+        build_id: BuildId::new(),
+        status: BuildStatus::Success,
+        completed_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires redis-server"]
+async fn test_events_reach_other_replica() {
+    let server = RedisServer::start().expect("Failed to start redis-server");
+    let sink = Arc::new(InMemoryEventPublisher::new());
+
+    let mut subscriber = RedisStreamSubscriber::connect(&server.config("replica-b"), sink.clone())
+        .await
+        .expect("Failed to connect subscriber");
+    let publisher = RedisStreamPublisher::connect(&server.config("replica-a"))
+        .await
+        .expect("Failed to connect publisher");
+
+    let event = build_completed();
+    publisher.publish(event.clone()).await.expect("Failed to publish");
+
+    let mut delivered = 0;
+    for _ in 0..10 {
+        delivered += subscriber.poll().await.expect("Failed to poll");
+        if delivered > 0 {
+            break;
+        }
+    }
+
+    assert_eq!(delivered, 1);
+    assert_eq!(sink.get_events().await, vec![event]);
+}
+
+#[tokio::test]
+#[ignore = "requires redis-server"]
+async fn test_own_events_are_skipped() {
+    let server = RedisServer::start().expect("Failed to start redis-server");
+    let sink = Arc::new(InMemoryEventPublisher::new());
+
+    let mut subscriber = RedisStreamSubscriber::connect(&server.config("replica-a"), sink.clone())
+        .await
+        .expect("Failed to connect subscriber");
+    let publisher = RedisStreamPublisher::connect(&server.config("replica-a"))
+        .await
+        .expect("Failed to connect publisher");
+
+    publisher
+        .publish_batch(vec![build_completed(), build_completed()])
+        .await
+        .expect("Failed to publish");
+
+    for _ in 0..3 {
+        assert_eq!(subscriber.poll().await.expect("Failed to poll"), 0);
+    }
+    assert!(sink.get_events().await.is_empty());
+}
+
+#[tokio::test]
+#[ignore = "requires redis-server"]
+async fn test_stream_is_trimmed() {
+    let server = RedisServer::start().expect("Failed to start redis-server");
+    let config = RedisStreamConfig {
+        max_len: 5,
+        exact_trim: true,
+        ..server.config("replica-a")
+    };
+
+    let publisher = RedisStreamPublisher::connect(&config)
+        .await
+        .expect("Failed to connect publisher");
+    for _ in 0..20 {
+        publisher.publish(build_completed()).await.expect("Failed to publish");
+    }
+
+    let client = redis::Client::open(config.url.as_str()).unwrap();
+    let mut connection = client.get_multiplexed_async_connection().await.unwrap();
+    let length: usize = redis::cmd("XLEN")
+        .arg(&config.stream)
+        .query_async(&mut connection)
+        .await
+        .unwrap();
+
+    assert_eq!(length, 5);
+}
+
+#[tokio::test]
+#[ignore = "requires redis-server"]
+async fn test_subscriber_recovers_after_restart() {
+    let mut server = RedisServer::start().expect("Failed to start redis-server");
+    let sink = Arc::new(InMemoryEventPublisher::new());
+    let (shutdown, shutdown_signal) = watch::channel(false);
+
+    let subscriber = RedisStreamSubscriber::connect(&server.config("replica-b"), sink.clone())
+        .await
+        .expect("Failed to connect subscriber");
+    let task = subscriber.spawn(shutdown_signal);
+    let publisher = RedisStreamPublisher::connect(&server.config("replica-a"))
+        .await
+        .expect("Failed to connect publisher");
+
+    publisher.publish(build_completed()).await.expect("Failed to publish");
+
+    server.restart();
+
+    // The stream and group are gone after the restart; keep publishing until the
+    // subscriber has recreated its group and picked an event up again
+    let mut recovered = false;
+    for _ in 0..50 {
+        let _ = publisher.publish(build_completed()).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if sink.get_events().await.len() >= 2 {
+            recovered = true;
+            break;
+        }
+    }
+
+    shutdown.send(true).unwrap();
+    task.await.unwrap();
+
+    assert!(recovered, "Subscriber should resume after Redis restarts");
+}